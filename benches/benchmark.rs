@@ -63,7 +63,7 @@ mod hnsw {
             .bench_local_values(|(writer, mut wtxn, _)| {
                 let mut rng = rng();
                 let mut builder = writer.builder(&mut rng);
-                builder.ef_construction(32).build::<M, M0>(&mut wtxn).unwrap();
+                builder.ef_construction(32).m(M).m0(M0).build(&mut wtxn).unwrap();
             });
     }
 
@@ -78,7 +78,7 @@ mod hnsw {
         let (writer, mut wtxn, db) = create_db_and_fill_with_vecs::<DIM>(&env, 50000).unwrap();
         let mut rng = rng();
         let mut builder = writer.builder(&mut rng);
-        builder.ef_construction(32).build::<M, M0>(&mut wtxn).unwrap();
+        builder.ef_construction(32).m(M).m0(M0).build(&mut wtxn).unwrap();
         wtxn.commit().unwrap();
 
         // reader should have a lifetime to this
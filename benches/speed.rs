@@ -6,7 +6,6 @@ use std::io::Write;
 use hannoy::distances::Cosine;
 use hannoy::{Database, Reader, Writer};
 use heed::{Env, EnvOpenOptions, RwTxn};
-use hnsw_rs;
 use hnsw_rs::hnsw::Hnsw;
 use hnsw_rs::prelude::DistCosine;
 use rand::rngs::StdRng;
@@ -24,7 +23,6 @@ fn gen_vecs<const DIM: usize>(size: usize) -> Vec<[f32; DIM]> {
     let mut rng = rng();
 
     (0..size)
-        .into_iter()
         .map(|_| {
             let mut arr = [0.0; DIM];
             rng.fill(&mut arr);
@@ -46,7 +44,7 @@ fn setup_lmdb() -> Env {
 
 fn create_lmdb_and_fill_with_vecs<'a, const DIM: usize>(
     env: &'a Env,
-    vecs: &'a Vec<[f32; DIM]>,
+    vecs: &'a [[f32; DIM]],
 ) -> hannoy::Result<(Writer<Cosine>, RwTxn<'a>, Database<Cosine>)> {
     let mut wtxn = env.write_txn().unwrap();
 
@@ -71,7 +69,7 @@ fn search_hannoy<const DIM: usize>(bencher: divan::Bencher) {
     let (writer, mut wtxn, db) = create_lmdb_and_fill_with_vecs::<DIM>(&env, &vecs).unwrap();
     let mut rng = rng();
     let mut builder = writer.builder(&mut rng);
-    builder.ef_construction(32).build::<M, M0>(&mut wtxn).unwrap();
+    builder.ef_construction(32).m(M).m0(M0).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     let rtxn = env.read_txn().unwrap();
@@ -106,7 +104,7 @@ fn search_hannoy_in_cache<const DIM: usize>(bencher: divan::Bencher) {
     let (writer, mut wtxn, db) = create_lmdb_and_fill_with_vecs::<DIM>(&env, &vecs).unwrap();
     let mut rng = rng();
     let mut builder = writer.builder(&mut rng);
-    builder.ef_construction(32).build::<M, M0>(&mut wtxn).unwrap();
+    builder.ef_construction(32).m(M).m0(M0).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     let rtxn = env.read_txn().unwrap();
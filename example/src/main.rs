@@ -26,7 +26,7 @@ fn main() -> Result<()> {
     let mut rng = StdRng::seed_from_u64(42);
 
     let mut builder = writer.builder(&mut rng);
-    builder.ef_construction(100).build::<16,32>(&mut wtxn)?;
+    builder.ef_construction(100).m(16).m0(32).build(&mut wtxn)?;
     wtxn.commit()?;
 
     // search hnsw using a new lmdb read transaction
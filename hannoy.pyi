@@ -2,6 +2,8 @@
 # ruff: noqa: E501, F401
 
 import builtins
+import numpy
+import numpy.typing
 import os
 import pathlib
 import typing
@@ -42,6 +44,16 @@ class Reader:
         r"""
         Retrieve similar items from the db given a query.
         """
+    def by_vecs(self, queries:numpy.typing.NDArray[numpy.float32], n:builtins.int=10, ef_search:builtins.int=200) -> builtins.list[builtins.list[tuple[builtins.int, builtins.float]]]:
+        r"""
+        Retrieve similar items from the db for every row of a 2-D array of queries, searching them
+        in parallel.
+        
+        Every thread searches with its own read transaction, they are all opened before the
+        search and must see the same version of the database as this reader. A runtime error is
+        raised, without searching anything, if a write was committed since the reader was
+        opened: open a new reader to see it.
+        """
 
 class Writer:
     r"""
@@ -73,6 +85,7 @@ class Metric(Enum):
     COSINE = ...
     EUCLIDEAN = ...
     MANHATTAN = ...
+    DOT_PRODUCT = ...
     BQ_COSINE = ...
     BQ_EUCLIDEAN = ...
     BQ_MANHATTAN = ...
@@ -0,0 +1,545 @@
+//! The attributes of the items and the filters searching through them.
+//!
+//! The attributes of an item are stored under its [`NodeMode::Attributes`] key. They are indexed
+//! in [`NodeMode::Postings`] entries, mapping each value of a field to the bitmap of the items
+//! having it. The `layer` of their key is the id of the field and the `item` is a hash of the tag,
+//! or the high bits of the order-preserving encoding of the number, so that numbers of a range
+//! are stored next to each other. The names of the fields are stored in the index metadata.
+
+use std::borrow::Cow;
+use std::mem::size_of;
+use std::ops::{Bound, RangeBounds};
+
+use byteorder::{BigEndian, ByteOrder};
+use heed::{BoxedError, RoTxn, RwTxn};
+use roaring::RoaringBitmap;
+
+use crate::{Database, Distance, Error, ItemId, Key, Result};
+
+/// The maximum number of fields of an index, their ids must fit in the `layer` of a key.
+const MAX_FIELDS: usize = u8::MAX as usize + 1;
+
+/// The attributes of an item, made of tags and numbers stored under field names.
+///
+/// A field can hold several values, e.g. the tags of a document. Timestamps can be stored as
+/// numbers, e.g. the number of seconds since the epoch.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use hannoy::{distances::Euclidean, Writer};
+/// # let (writer, mut wtxn): (Writer<Euclidean>, heed::RwTxn) = todo!();
+/// use hannoy::Attributes;
+///
+/// let attributes = Attributes::new()
+///     .tag("color", "red")
+///     .tag("color", "blue")
+///     .number("price", 12.5)
+///     .number("created_at", 1_700_000_000.0);
+/// writer.set_attributes(&mut wtxn, 42, &attributes)?;
+/// # Ok::<(), hannoy::Error>(())
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Attributes {
+    values: Vec<(String, AttributeValue)>,
+}
+
+impl Attributes {
+    /// Creates an empty set of attributes.
+    pub fn new() -> Attributes {
+        Attributes::default()
+    }
+
+    /// Adds a tag to a field.
+    pub fn tag(mut self, field: impl Into<String>, value: impl Into<String>) -> Attributes {
+        self.values.push((field.into(), AttributeValue::Tag(value.into())));
+        self
+    }
+
+    /// Adds a number to a field, `NaN`s are stored but never match any filter.
+    pub fn number(mut self, field: impl Into<String>, value: f64) -> Attributes {
+        self.values.push((field.into(), AttributeValue::Number(value)));
+        self
+    }
+
+    /// Returns the fields and their values, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &AttributeValue)> {
+        self.values.iter().map(|(field, value)| (field.as_str(), value))
+    }
+
+    /// Returns the values of a field.
+    pub fn get<'a>(&'a self, field: &'a str) -> impl Iterator<Item = &'a AttributeValue> + 'a {
+        self.iter().filter(move |(f, _)| *f == field).map(|(_, value)| value)
+    }
+
+    /// Returns `true` if there aren't any attributes.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// A value of an attribute, see [`Attributes`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    /// A string matched exactly by [`Filter::tag`].
+    Tag(String),
+    /// A number matched by [`Filter::range`].
+    Number(f64),
+}
+
+impl AttributeValue {
+    fn kind(&self) -> FieldKind {
+        match self {
+            AttributeValue::Tag(_) => FieldKind::Tag,
+            AttributeValue::Number(_) => FieldKind::Number,
+        }
+    }
+}
+
+/// A boolean expression over the attributes of the items, see
+/// [`QueryBuilder::filter`](crate::QueryBuilder::filter).
+///
+/// # Examples
+///
+/// ```
+/// use hannoy::Filter;
+///
+/// // red items under 20 that weren't created in 2023
+/// let filter = Filter::tag("color", "red")
+///     .and(Filter::range("price", ..20.0))
+///     .and(!Filter::range("created_at", 1_672_531_200.0..1_704_067_200.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// The items with this tag in the field.
+    Tag {
+        /// The name of the field.
+        field: String,
+        /// The tag to match exactly.
+        value: String,
+    },
+    /// The items with a number within the bounds in the field.
+    Range {
+        /// The name of the field.
+        field: String,
+        /// The lower bound of the numbers.
+        min: Bound<f64>,
+        /// The upper bound of the numbers.
+        max: Bound<f64>,
+    },
+    /// The items matching all the filters, every item if there are none.
+    And(Vec<Filter>),
+    /// The items matching any of the filters, no item if there are none.
+    Or(Vec<Filter>),
+    /// The items not matching the filter.
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    /// The items with the `value` tag in the `field`.
+    pub fn tag(field: impl Into<String>, value: impl Into<String>) -> Filter {
+        Filter::Tag { field: field.into(), value: value.into() }
+    }
+
+    /// The items with a number equal to `value` in the `field`.
+    pub fn eq(field: impl Into<String>, value: f64) -> Filter {
+        Filter::range(field, value..=value)
+    }
+
+    /// The items with a number within `range` in the `field`.
+    pub fn range(field: impl Into<String>, range: impl RangeBounds<f64>) -> Filter {
+        Filter::Range {
+            field: field.into(),
+            min: range.start_bound().cloned(),
+            max: range.end_bound().cloned(),
+        }
+    }
+
+    /// The items matching both `self` and `other`.
+    pub fn and(self, other: Filter) -> Filter {
+        match self {
+            Filter::And(mut filters) => {
+                filters.push(other);
+                Filter::And(filters)
+            }
+            filter => Filter::And(vec![filter, other]),
+        }
+    }
+
+    /// The items matching `self` or `other`.
+    pub fn or(self, other: Filter) -> Filter {
+        match self {
+            Filter::Or(mut filters) => {
+                filters.push(other);
+                Filter::Or(filters)
+            }
+            filter => Filter::Or(vec![filter, other]),
+        }
+    }
+}
+
+impl std::ops::Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Filter {
+        match self {
+            Filter::Not(filter) => *filter,
+            filter => Filter::Not(Box::new(filter)),
+        }
+    }
+}
+
+/// Returns the attributes of an item.
+pub(crate) fn get<D: Distance>(
+    database: Database<D>,
+    index: u16,
+    rtxn: &RoTxn,
+    item: ItemId,
+) -> Result<Option<Attributes>> {
+    Ok(database.remap_data_type::<AttributesCodec>().get(rtxn, &Key::attributes(index, item))?)
+}
+
+/// Replaces the attributes of an item and updates the postings, removes them if `attributes` is
+/// `None`. Returns `true` if the item had attributes.
+pub(crate) fn put<D: Distance>(
+    database: Database<D>,
+    index: u16,
+    wtxn: &mut RwTxn,
+    item: ItemId,
+    attributes: Option<&Attributes>,
+) -> Result<bool> {
+    let payloads = database.remap_data_type::<AttributesCodec>();
+    let fields_db = database.remap_data_type::<FieldsCodec>();
+    let key = Key::attributes(index, item);
+
+    let old = payloads.get(wtxn, &key)?;
+    let mut fields = fields_db.get(wtxn, &Key::attribute_fields(index))?.unwrap_or_default();
+    let n_fields = fields.len();
+
+    for (field, value) in old.iter().flat_map(Attributes::iter) {
+        let Some(id) = field_id(&fields, value.kind(), field) else { continue };
+        if let Some((key, term)) = posting(index, id, value) {
+            update_posting(database, wtxn, &key, &term, item, false)?;
+        }
+    }
+
+    match attributes {
+        Some(attributes) => {
+            for (field, value) in attributes.iter() {
+                let id = match field_id(&fields, value.kind(), field) {
+                    Some(id) => id,
+                    None if fields.len() < MAX_FIELDS => {
+                        fields.push((value.kind(), field.to_string()));
+                        (fields.len() - 1) as u8
+                    }
+                    None => return Err(Error::TooManyAttributeFields(index)),
+                };
+                if let Some((key, term)) = posting(index, id, value) {
+                    update_posting(database, wtxn, &key, &term, item, true)?;
+                }
+            }
+            payloads.put(wtxn, &key, attributes)?;
+        }
+        None => {
+            payloads.delete(wtxn, &key)?;
+        }
+    }
+
+    if fields.len() != n_fields {
+        fields_db.put(wtxn, &Key::attribute_fields(index), &fields)?;
+    }
+
+    Ok(old.is_some())
+}
+
+/// Returns the items of `items` matching the filter.
+pub(crate) fn resolve<D: Distance>(
+    database: Database<D>,
+    index: u16,
+    rtxn: &RoTxn,
+    items: &RoaringBitmap,
+    filter: &Filter,
+) -> Result<RoaringBitmap> {
+    let fields = database
+        .remap_data_type::<FieldsCodec>()
+        .get(rtxn, &Key::attribute_fields(index))?
+        .unwrap_or_default();
+    let resolver = Resolver {
+        database: database.remap_data_type::<PostingsCodec>(),
+        index,
+        rtxn,
+        items,
+        fields,
+    };
+    Ok(resolver.resolve(filter)? & items)
+}
+
+struct Resolver<'t> {
+    database: heed::Database<crate::internals::KeyCodec, PostingsCodec>,
+    index: u16,
+    rtxn: &'t RoTxn<'t>,
+    items: &'t RoaringBitmap,
+    fields: Vec<(FieldKind, String)>,
+}
+
+impl Resolver<'_> {
+    fn resolve(&self, filter: &Filter) -> Result<RoaringBitmap> {
+        match filter {
+            Filter::Tag { field, value } => {
+                let Some(id) = field_id(&self.fields, FieldKind::Tag, field) else {
+                    return Ok(RoaringBitmap::new());
+                };
+                let (key, term) = tag_posting(self.index, id, value);
+                let postings = self.database.get(self.rtxn, &key)?.unwrap_or_default();
+                Ok(postings
+                    .into_iter()
+                    .find(|(t, _)| *t == term)
+                    .map(|(_, b)| b)
+                    .unwrap_or_default())
+            }
+            Filter::Range { field, min, max } => {
+                let Some(id) = field_id(&self.fields, FieldKind::Number, field) else {
+                    return Ok(RoaringBitmap::new());
+                };
+                let bucket = |bound: &Bound<f64>, unbounded| match bound {
+                    Bound::Included(x) | Bound::Excluded(x) => bucket(*x),
+                    Bound::Unbounded => unbounded,
+                };
+                let start = Key::postings(self.index, bucket(min, u32::MIN), 0);
+                let end = Key::postings(self.index, bucket(max, u32::MAX), u8::MAX);
+                if start.node > end.node {
+                    return Ok(RoaringBitmap::new());
+                }
+
+                let mut matching = RoaringBitmap::new();
+                for result in self.database.range(self.rtxn, &(start..=end))? {
+                    let (key, postings) = result?;
+                    if key.node.layer != id {
+                        continue;
+                    }
+                    for (term, bitmap) in postings {
+                        if (*min, *max).contains(&BigEndian::read_f64(&term)) {
+                            matching |= bitmap;
+                        }
+                    }
+                }
+                Ok(matching)
+            }
+            Filter::And(filters) => {
+                let mut matching = self.items.clone();
+                for filter in filters {
+                    if matching.is_empty() {
+                        break;
+                    }
+                    matching &= self.resolve(filter)?;
+                }
+                Ok(matching)
+            }
+            Filter::Or(filters) => {
+                let mut matching = RoaringBitmap::new();
+                for filter in filters {
+                    matching |= self.resolve(filter)?;
+                }
+                Ok(matching)
+            }
+            Filter::Not(filter) => Ok(self.items - self.resolve(filter)?),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub(crate) enum FieldKind {
+    Tag = 0,
+    Number = 1,
+}
+
+fn field_id(fields: &[(FieldKind, String)], kind: FieldKind, field: &str) -> Option<u8> {
+    fields.iter().position(|(k, f)| *k == kind && f == field).map(|id| id as u8)
+}
+
+/// Returns the key of the postings of a value and the term identifying it in the postings.
+fn posting(index: u16, field: u8, value: &AttributeValue) -> Option<(Key, Vec<u8>)> {
+    match value {
+        AttributeValue::Tag(tag) => Some(tag_posting(index, field, tag)),
+        AttributeValue::Number(x) if x.is_nan() => None,
+        AttributeValue::Number(x) => {
+            // -0.0 and 0.0 must be the same term
+            let x = if *x == 0.0 { 0.0 } else { *x };
+            Some((Key::postings(index, bucket(x), field), x.to_be_bytes().to_vec()))
+        }
+    }
+}
+
+fn tag_posting(index: u16, field: u8, tag: &str) -> (Key, Vec<u8>) {
+    (Key::postings(index, crc32fast::hash(tag.as_bytes()), field), tag.as_bytes().to_vec())
+}
+
+/// Returns the high bits of an encoding of `x` in which the numbers are ordered like their bytes.
+fn bucket(x: f64) -> u32 {
+    let x = if x == 0.0 { 0.0 } else { x };
+    let bits = x.to_bits();
+    let sortable = if bits >> 63 == 1 { !bits } else { bits | 1 << 63 };
+    (sortable >> 32) as u32
+}
+
+fn update_posting<D: Distance>(
+    database: Database<D>,
+    wtxn: &mut RwTxn,
+    key: &Key,
+    term: &[u8],
+    item: ItemId,
+    insert: bool,
+) -> Result<()> {
+    let database = database.remap_data_type::<PostingsCodec>();
+    let mut postings = database.get(wtxn, key)?.unwrap_or_default();
+
+    match postings.iter_mut().find(|(t, _)| t == term) {
+        Some((_, bitmap)) if insert => drop(bitmap.insert(item)),
+        Some((_, bitmap)) => drop(bitmap.remove(item)),
+        None if insert => postings.push((term.to_vec(), RoaringBitmap::from_iter([item]))),
+        None => (),
+    }
+    postings.retain(|(_, bitmap)| !bitmap.is_empty());
+
+    if postings.is_empty() {
+        database.delete(wtxn, key)?;
+    } else {
+        database.put(wtxn, key, &postings)?;
+    }
+    Ok(())
+}
+
+/// The attributes of an item: the number of values, then their kind, field and value.
+pub(crate) enum AttributesCodec {}
+
+impl<'a> heed::BytesEncode<'a> for AttributesCodec {
+    type EItem = Attributes;
+
+    fn bytes_encode(item: &'a Self::EItem) -> Result<Cow<'a, [u8]>, BoxedError> {
+        let mut output = Vec::new();
+        output.extend_from_slice(&u16::try_from(item.values.len())?.to_be_bytes());
+        for (field, value) in &item.values {
+            output.push(value.kind() as u8);
+            write_str(&mut output, field)?;
+            match value {
+                AttributeValue::Tag(tag) => write_str(&mut output, tag)?,
+                AttributeValue::Number(x) => output.extend_from_slice(&x.to_be_bytes()),
+            }
+        }
+        Ok(Cow::Owned(output))
+    }
+}
+
+impl heed::BytesDecode<'_> for AttributesCodec {
+    type DItem = Attributes;
+
+    fn bytes_decode(mut bytes: &[u8]) -> Result<Self::DItem, BoxedError> {
+        let len = BigEndian::read_u16(take(&mut bytes, size_of::<u16>())?);
+        let mut values = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            let kind = take(&mut bytes, 1)?[0];
+            let field = read_str(&mut bytes)?;
+            let value = match kind {
+                0 => AttributeValue::Tag(read_str(&mut bytes)?),
+                1 => AttributeValue::Number(BigEndian::read_f64(take(&mut bytes, 8)?)),
+                kind => return Err(format!("unknown attribute kind {kind}").into()),
+            };
+            values.push((field, value));
+        }
+        Ok(Attributes { values })
+    }
+}
+
+/// The fields of an index, their id is their position.
+pub(crate) enum FieldsCodec {}
+
+impl<'a> heed::BytesEncode<'a> for FieldsCodec {
+    type EItem = Vec<(FieldKind, String)>;
+
+    fn bytes_encode(item: &'a Self::EItem) -> Result<Cow<'a, [u8]>, BoxedError> {
+        let mut output = Vec::new();
+        output.extend_from_slice(&u16::try_from(item.len())?.to_be_bytes());
+        for (kind, field) in item {
+            output.push(*kind as u8);
+            write_str(&mut output, field)?;
+        }
+        Ok(Cow::Owned(output))
+    }
+}
+
+impl heed::BytesDecode<'_> for FieldsCodec {
+    type DItem = Vec<(FieldKind, String)>;
+
+    fn bytes_decode(mut bytes: &[u8]) -> Result<Self::DItem, BoxedError> {
+        let len = BigEndian::read_u16(take(&mut bytes, size_of::<u16>())?);
+        (0..len)
+            .map(|_| {
+                let kind = match take(&mut bytes, 1)?[0] {
+                    0 => FieldKind::Tag,
+                    1 => FieldKind::Number,
+                    kind => return Err(format!("unknown attribute kind {kind}").into()),
+                };
+                Ok((kind, read_str(&mut bytes)?))
+            })
+            .collect()
+    }
+}
+
+/// The terms sharing a postings key along with the items having them.
+pub(crate) enum PostingsCodec {}
+
+impl<'a> heed::BytesEncode<'a> for PostingsCodec {
+    type EItem = Vec<(Vec<u8>, RoaringBitmap)>;
+
+    fn bytes_encode(item: &'a Self::EItem) -> Result<Cow<'a, [u8]>, BoxedError> {
+        let mut output = Vec::new();
+        output.extend_from_slice(&u32::try_from(item.len())?.to_be_bytes());
+        for (term, bitmap) in item {
+            output.extend_from_slice(&u32::try_from(term.len())?.to_be_bytes());
+            output.extend_from_slice(term);
+            output.extend_from_slice(&u32::try_from(bitmap.serialized_size())?.to_be_bytes());
+            bitmap.serialize_into(&mut output)?;
+        }
+        Ok(Cow::Owned(output))
+    }
+}
+
+impl heed::BytesDecode<'_> for PostingsCodec {
+    type DItem = Vec<(Vec<u8>, RoaringBitmap)>;
+
+    fn bytes_decode(mut bytes: &[u8]) -> Result<Self::DItem, BoxedError> {
+        let len = BigEndian::read_u32(take(&mut bytes, size_of::<u32>())?);
+        (0..len)
+            .map(|_| {
+                let term_len = BigEndian::read_u32(take(&mut bytes, size_of::<u32>())?);
+                let term = take(&mut bytes, term_len as usize)?.to_vec();
+                let bitmap_len = BigEndian::read_u32(take(&mut bytes, size_of::<u32>())?);
+                let bitmap =
+                    RoaringBitmap::deserialize_from(take(&mut bytes, bitmap_len as usize)?)?;
+                Ok((term, bitmap))
+            })
+            .collect()
+    }
+}
+
+fn write_str(output: &mut Vec<u8>, s: &str) -> Result<(), BoxedError> {
+    output.extend_from_slice(&u32::try_from(s.len())?.to_be_bytes());
+    output.extend_from_slice(s.as_bytes());
+    Ok(())
+}
+
+fn read_str(bytes: &mut &[u8]) -> Result<String, BoxedError> {
+    let len = BigEndian::read_u32(take(bytes, size_of::<u32>())?);
+    Ok(std::str::from_utf8(take(bytes, len as usize)?)?.to_string())
+}
+
+/// Splits the first `n` bytes off `bytes`.
+fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Result<&'a [u8], BoxedError> {
+    if bytes.len() < n {
+        return Err(format!("expected {n} bytes but only {} remain", bytes.len()).into());
+    }
+    let (head, tail) = bytes.split_at(n);
+    *bytes = tail;
+    Ok(head)
+}
@@ -0,0 +1,528 @@
+//! A command-line tool to inspect and operate on the hannoy indexes of an LMDB environment.
+//!
+//! ```text
+//! hannoy info ./my-db
+//! hannoy query ./my-db --index 0 --item 42 -k 10
+//! hannoy build ./my-db --index 0 --distance cosine --input vectors.fvecs
+//! ```
+
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use hannoy::distances::*;
+use hannoy::internals::{KeyCodec, NodeCodec};
+use hannoy::vecs::{NpyReader, VecsFormat, VecsReader};
+use hannoy::{BuildParams, Database, Distance, ItemId, Reader, Writer};
+use heed::{Env, EnvOpenOptions, RoTxn};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+type Result<T, E = Box<dyn Error>> = std::result::Result<T, E>;
+
+/// Runs `$body` with `$d` aliased to the distance type named `$name`, see [`Distance::name`].
+macro_rules! with_distance {
+    ($name:expr, $d:ident => $body:expr) => {
+        with_distance!(@ $name, $d => $body;
+            BinaryQuantizedCosine, BinaryQuantizedEuclidean, BinaryQuantizedManhattan, Cosine,
+            CosineBf16, CosineF16, DotProduct, Euclidean, EuclideanBf16, EuclideanF16, Hamming,
+            Manhattan, ScalarQuantizedCosine, ScalarQuantizedEuclidean)
+    };
+    (@ $name:expr, $d:ident => $body:expr; $($distance:ident),+) => {{
+        let name: &str = $name;
+        $(
+            if same_distance(name, <$distance as Distance>::name()) {
+                type $d = $distance;
+                $body
+            } else
+        )+ {
+            Err(format!("unknown distance {name:?}").into())
+        }
+    }};
+}
+
+/// Compares distance names ignoring the separators, e.g. `binary-quantized-cosine` is the
+/// `binary quantized cosine` distance.
+fn same_distance(a: &str, b: &str) -> bool {
+    let normalize = |s: &str| s.replace(['-', '_'], " ").to_lowercase();
+    normalize(a) == normalize(b)
+}
+
+#[derive(Parser)]
+#[command(version, about = "Inspect and operate on hannoy databases")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prints the metadata, version, distance and dimensions of every index.
+    Info {
+        #[command(flatten)]
+        db: DbArgs,
+    },
+    /// Prints the statistics of the graph of an index.
+    Stats {
+        #[command(flatten)]
+        db: DbArgs,
+        #[arg(long, default_value_t = 0)]
+        index: u16,
+    },
+    /// Searches the nearest neighbours of an item or a vector.
+    Query {
+        #[command(flatten)]
+        db: DbArgs,
+        #[arg(long, default_value_t = 0)]
+        index: u16,
+        /// The item to search the neighbours of.
+        #[arg(long, conflicts_with = "vector", required_unless_present = "vector")]
+        item: Option<ItemId>,
+        /// The vector to search the neighbours of, as comma-separated floats.
+        #[arg(long, value_delimiter = ',', allow_hyphen_values = true)]
+        vector: Option<Vec<f32>>,
+        /// The number of neighbours to return.
+        #[arg(short, default_value_t = 10)]
+        k: usize,
+        #[arg(long, default_value_t = 100)]
+        ef_search: usize,
+    },
+    /// Adds the vectors of a file to an index and builds it, the items are numbered in the order
+    /// of the file unless an id-mapping file is given.
+    Build {
+        #[command(flatten)]
+        db: DbArgs,
+        #[arg(long, default_value_t = 0)]
+        index: u16,
+        /// The distance of the index, e.g. `cosine` or `binary-quantized-euclidean`.
+        #[arg(long)]
+        distance: String,
+        /// The file to read the vectors from.
+        #[arg(long)]
+        input: PathBuf,
+        /// The format of the input, guessed from its extension by default.
+        #[arg(long)]
+        format: Option<Format>,
+        /// The file to read the ids of the items from, one per line.
+        #[arg(long)]
+        ids: Option<PathBuf>,
+        #[command(flatten)]
+        params: BuildArgs,
+    },
+    /// Rebuilds the graph of an index from scratch.
+    Rebuild {
+        #[command(flatten)]
+        db: DbArgs,
+        #[arg(long, default_value_t = 0)]
+        index: u16,
+        #[command(flatten)]
+        params: BuildArgs,
+    },
+    /// Checks the integrity of the indexes, exits with an error if any is corrupted.
+    Check {
+        #[command(flatten)]
+        db: DbArgs,
+        /// Only check this index.
+        #[arg(long)]
+        index: Option<u16>,
+    },
+    /// Writes the vectors of an index to a file, or to the standard output as CSV.
+    Export {
+        #[command(flatten)]
+        db: DbArgs,
+        #[arg(long, default_value_t = 0)]
+        index: u16,
+        /// The file to write the vectors to.
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// The format of the output, guessed from its extension by default.
+        #[arg(long)]
+        format: Option<Format>,
+        /// The file to write the ids of the items to, one per line.
+        #[arg(long)]
+        ids: Option<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+struct DbArgs {
+    /// The directory of the LMDB environment.
+    path: PathBuf,
+    /// The name of the database in the environment, the unnamed one by default.
+    #[arg(long)]
+    database: Option<String>,
+    /// The maximum size of the environment, in bytes.
+    #[arg(long, default_value_t = 10 * 1024 * 1024 * 1024)]
+    map_size: usize,
+}
+
+#[derive(Args)]
+struct BuildArgs {
+    /// Defaults to the value the index was built with, or 16.
+    #[arg(long)]
+    m: Option<usize>,
+    /// Defaults to the value the index was built with, or 32.
+    #[arg(long)]
+    m0: Option<usize>,
+    #[arg(long, default_value_t = 100)]
+    ef_construction: usize,
+    #[arg(long, default_value_t = 42)]
+    seed: u64,
+}
+
+impl BuildArgs {
+    /// Returns `m` and `m0`, defaulting to the `previous` parameters the index was built with.
+    fn degree(&self, previous: Option<BuildParams>) -> (usize, usize) {
+        let m = self.m.or(previous.map(|p| p.m)).unwrap_or(16);
+        let m0 = self.m0.or(previous.map(|p| p.m0)).unwrap_or(32);
+        (m, m0)
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Little-endian `i32` dimensions followed by the `f32`s of each vector.
+    Fvecs,
+    /// Little-endian `i32` dimensions followed by the `u8`s of each vector.
+    Bvecs,
+    /// Little-endian `i32` dimensions followed by the `i32`s of each vector.
+    Ivecs,
+    /// A two-dimensional numpy array of floats.
+    Npy,
+    /// One vector per line, as comma-separated floats. Exported with the item id first.
+    Csv,
+}
+
+impl Format {
+    fn guess(format: Option<Format>, path: &Path) -> Result<Format> {
+        if let Some(format) = format {
+            return Ok(format);
+        }
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("fvecs") => Ok(Format::Fvecs),
+            Some("bvecs") => Ok(Format::Bvecs),
+            Some("ivecs") => Ok(Format::Ivecs),
+            Some("npy") => Ok(Format::Npy),
+            Some("csv") => Ok(Format::Csv),
+            _ => Err(format!("can't guess the format of {}, use --format", path.display()).into()),
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    match Cli::parse().command {
+        Command::Info { db } => {
+            let (env, database) = db.open()?;
+            let rtxn = env.read_txn()?;
+            for (index, distance) in hannoy::list_indexes(&rtxn, database)? {
+                with_distance!(&distance, D => info::<D>(&rtxn, database.remap_data_type(), index))?;
+            }
+        }
+        Command::Stats { db, index } => {
+            let (env, database) = db.open()?;
+            let rtxn = env.read_txn()?;
+            let distance = distance_of(&rtxn, database, index)?;
+            with_distance!(&distance, D => stats::<D>(&rtxn, database.remap_data_type(), index))?;
+        }
+        Command::Query { db, index, item, vector, k, ef_search } => {
+            let (env, database) = db.open()?;
+            let rtxn = env.read_txn()?;
+            let distance = distance_of(&rtxn, database, index)?;
+            let search = Query { item, vector, k, ef_search };
+            with_distance!(&distance, D => query::<D>(&rtxn, database.remap_data_type(), index, &search))?;
+        }
+        Command::Build { db, index, distance, input, format, ids, params } => {
+            let input = Input { format: Format::guess(format, &input)?, path: input, ids };
+            fs::create_dir_all(&db.path)?;
+            let (env, database) = db.create()?;
+            let mut wtxn = env.write_txn()?;
+            with_distance!(&distance, D => build::<D>(&mut wtxn, database.remap_data_type(), index, &input, &params))?;
+            wtxn.commit()?;
+        }
+        Command::Rebuild { db, index, params } => {
+            let (env, database) = db.open()?;
+            let mut wtxn = env.write_txn()?;
+            let distance = distance_of(&wtxn, database, index)?;
+            with_distance!(&distance, D => rebuild::<D>(&mut wtxn, database.remap_data_type(), index, &params))?;
+            wtxn.commit()?;
+        }
+        Command::Check { db, index } => {
+            let (env, database) = db.open()?;
+            let rtxn = env.read_txn()?;
+            let mut corrupted = false;
+            for (i, distance) in hannoy::list_indexes(&rtxn, database)? {
+                if index.is_some_and(|index| index != i) {
+                    continue;
+                }
+                let ok = with_distance!(&distance, D => check::<D>(&rtxn, database.remap_data_type(), i))?;
+                corrupted |= !ok;
+            }
+            if corrupted {
+                return Err("some indexes are corrupted".into());
+            }
+        }
+        Command::Export { db, index, output, format, ids } => {
+            let (env, database) = db.open()?;
+            let rtxn = env.read_txn()?;
+            let distance = distance_of(&rtxn, database, index)?;
+            let (format, output): (_, Box<dyn Write>) = match output {
+                Some(path) => (Format::guess(format, &path)?, Box::new(File::create(path)?)),
+                None => (format.unwrap_or(Format::Csv), Box::new(io::stdout().lock())),
+            };
+            let output = BufWriter::new(output);
+            let mut ids = ids.map(|path| File::create(path).map(BufWriter::new)).transpose()?;
+            let ids = ids.as_mut().map(|ids| ids as &mut dyn Write);
+            with_distance!(&distance, D => export::<D>(&rtxn, database.remap_data_type(), index, format, output, ids))?;
+        }
+    }
+
+    Ok(())
+}
+
+impl DbArgs {
+    fn options(&self) -> EnvOpenOptions<heed::WithTls> {
+        let mut options = EnvOpenOptions::new();
+        options.map_size(self.map_size).max_dbs(16);
+        options
+    }
+
+    /// Opens an existing environment and database, with any distance.
+    fn open(&self) -> Result<(Env, Database<Euclidean>)> {
+        let env = unsafe { self.options().open(&self.path) }?;
+        let rtxn = env.read_txn()?;
+        let database = env
+            .open_database::<KeyCodec, NodeCodec<Euclidean>>(&rtxn, self.database.as_deref())?
+            .ok_or_else(|| format!("no database in {}", self.path.display()))?;
+        drop(rtxn);
+        Ok((env, database))
+    }
+
+    /// Opens the environment and database, creating them if needed.
+    fn create(&self) -> Result<(Env, Database<Euclidean>)> {
+        let env = unsafe { self.options().open(&self.path) }?;
+        let mut wtxn = env.write_txn()?;
+        let database = env.create_database(&mut wtxn, self.database.as_deref())?;
+        wtxn.commit()?;
+        Ok((env, database))
+    }
+}
+
+/// Returns the name of the distance of an index.
+fn distance_of(rtxn: &RoTxn, database: Database<Euclidean>, index: u16) -> Result<String> {
+    hannoy::list_indexes(rtxn, database)?
+        .into_iter()
+        .find_map(|(i, distance)| (i == index).then_some(distance))
+        .ok_or_else(|| format!("there is no index {index}").into())
+}
+
+fn info<D: Distance>(rtxn: &RoTxn, database: Database<D>, index: u16) -> Result<()> {
+    println!("index {index}");
+    println!("  distance:     {}", D::name());
+    let reader = match Reader::open(rtxn, index, database) {
+        Ok(reader) => reader,
+        Err(hannoy::Error::NeedBuild(_)) => {
+            println!("  updated since the last build");
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+    println!("  version:      {}", reader.version());
+    println!("  dimensions:   {}", reader.dimensions());
+    println!("  items:        {}", reader.n_items());
+    println!("  entry points: {}", reader.n_entrypoints());
+    match reader.build_params() {
+        Some(params) => println!("  built with:   {params}"),
+        None => println!("  built with:   unknown parameters"),
+    }
+    Ok(())
+}
+
+fn stats<D: Distance>(rtxn: &RoTxn, database: Database<D>, index: u16) -> Result<()> {
+    let reader = Reader::open(rtxn, index, database)?;
+    let stats = reader.graph_stats(rtxn)?;
+
+    println!("entry points:      {:?}", stats.entry_points);
+    println!("layer 0 components: {}", stats.layer0_components);
+    println!("avg links size:    {:.1} bytes", stats.avg_links_size);
+    for (level, layer) in stats.layers.iter().enumerate() {
+        let avg = |histogram: &[u64]| {
+            let links: u64 = histogram.iter().enumerate().map(|(d, n)| d as u64 * n).sum();
+            links as f64 / layer.n_nodes.max(1) as f64
+        };
+        println!("layer {level}: {} nodes", layer.n_nodes);
+        println!("  avg degree:  {:.2}", avg(&layer.out_degrees));
+        println!("  out-degrees: {:?}", layer.out_degrees);
+        println!("  in-degrees:  {:?}", layer.in_degrees);
+    }
+    Ok(())
+}
+
+/// Prints the integrity report of an index and returns `true` if it's not corrupted.
+fn check<D: Distance>(rtxn: &RoTxn, database: Database<D>, index: u16) -> Result<bool> {
+    let report = hannoy::fsck(rtxn, database, index)?;
+    if report.is_ok() {
+        println!("index {index}: ok");
+    } else {
+        println!("index {index}: {report:#?}");
+    }
+    Ok(report.is_ok())
+}
+
+struct Query {
+    item: Option<ItemId>,
+    vector: Option<Vec<f32>>,
+    k: usize,
+    ef_search: usize,
+}
+
+fn query<D: Distance>(
+    rtxn: &RoTxn,
+    database: Database<D>,
+    index: u16,
+    query: &Query,
+) -> Result<()> {
+    let reader = Reader::open(rtxn, index, database)?;
+    let mut builder = reader.nns(query.k);
+    builder.ef_search(query.ef_search);
+
+    let searched = match (&query.vector, query.item) {
+        (Some(vector), _) => builder.by_vector(rtxn, vector)?,
+        (None, Some(item)) => {
+            builder.by_item(rtxn, item)?.ok_or_else(|| format!("there is no item {item}"))?
+        }
+        (None, None) => unreachable!("clap requires an item or a vector"),
+    };
+    for (item, distance) in searched.nns {
+        println!("{item}\t{distance}");
+    }
+    Ok(())
+}
+
+/// The file of vectors to build an index from.
+struct Input {
+    path: PathBuf,
+    format: Format,
+    ids: Option<PathBuf>,
+}
+
+fn build<D: Distance>(
+    wtxn: &mut heed::RwTxn,
+    database: Database<D>,
+    index: u16,
+    input: &Input,
+    params: &BuildArgs,
+) -> Result<()> {
+    let dimensions = match read_vectors(&input.path, input.format)?.next() {
+        Some(vector) => vector?.len(),
+        None => return Err(format!("{} is empty", input.path.display()).into()),
+    };
+    // the index may already have been built, keep its degree unless asked otherwise
+    let previous = Reader::open(wtxn, index, database).ok().and_then(|r| r.build_params());
+    let writer = Writer::new(database, index, dimensions);
+
+    let file = File::open(&input.path)?;
+    let mut ids = input.ids.as_ref().map(File::open).transpose()?;
+    let ids = ids.as_mut().map(|ids| ids as &mut dyn Read);
+    let n_items = match input.format {
+        Format::Fvecs => writer.import_fvecs(wtxn, file, ids)?,
+        Format::Bvecs => writer.import_bvecs(wtxn, file, ids)?,
+        Format::Ivecs => writer.import_ivecs(wtxn, file, ids)?,
+        Format::Npy => writer.import_npy(wtxn, file, ids)?,
+        Format::Csv if ids.is_some() => return Err("csv files can't be imported with ids".into()),
+        Format::Csv => {
+            let mut n_items = 0;
+            for vector in read_vectors(&input.path, input.format)? {
+                writer.add_item(wtxn, n_items.try_into()?, &vector?)?;
+                n_items += 1;
+            }
+            n_items
+        }
+    };
+    println!("imported {n_items} items");
+
+    let (m, m0) = params.degree(previous);
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let stats =
+        writer.builder(&mut rng).m(m).m0(m0).ef_construction(params.ef_construction).build(wtxn)?;
+    println!("built {} items in {:.2?}", stats.n_items_inserted(), stats.duration());
+    Ok(())
+}
+
+fn rebuild<D: Distance>(
+    wtxn: &mut heed::RwTxn,
+    database: Database<D>,
+    index: u16,
+    params: &BuildArgs,
+) -> Result<()> {
+    let reader = Reader::open(wtxn, index, database)?;
+    let previous = reader.build_params();
+    let writer = Writer::new(database, index, reader.dimensions());
+
+    let (m, m0) = params.degree(previous);
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let stats = writer
+        .builder(&mut rng)
+        .m(m)
+        .m0(m0)
+        .ef_construction(params.ef_construction)
+        .force_rebuild(wtxn)?;
+    println!("rebuilt {} items in {:.2?}", stats.n_items_inserted(), stats.duration());
+    Ok(())
+}
+
+fn export<D: Distance>(
+    rtxn: &RoTxn,
+    database: Database<D>,
+    index: u16,
+    format: Format,
+    mut output: impl Write,
+    ids: Option<&mut dyn Write>,
+) -> Result<()> {
+    let reader = Reader::open(rtxn, index, database)?;
+    let n_items = match format {
+        Format::Fvecs => reader.export_fvecs(rtxn, output, ids)?,
+        Format::Bvecs => reader.export_bvecs(rtxn, output, ids)?,
+        Format::Ivecs => reader.export_ivecs(rtxn, output, ids)?,
+        Format::Npy => reader.export_npy(rtxn, output, ids)?,
+        Format::Csv if ids.is_some() => return Err("the ids are part of the csv output".into()),
+        Format::Csv => {
+            let mut n_items = 0;
+            for result in reader.iter(rtxn)? {
+                let (item, vector) = result?;
+                write!(output, "{item}")?;
+                vector.iter().try_for_each(|x| write!(output, ",{x}"))?;
+                writeln!(output)?;
+                n_items += 1;
+            }
+            output.flush()?;
+            n_items
+        }
+    };
+    // the vectors may be written to the standard output
+    eprintln!("exported {n_items} items");
+    Ok(())
+}
+
+/// Iterates over the vectors of a file, a CSV file has one vector per line.
+fn read_vectors(path: &Path, format: Format) -> Result<Box<dyn Iterator<Item = Result<Vec<f32>>>>> {
+    let file = File::open(path)?;
+    let to_boxed = |result: hannoy::Result<_>| result.map_err(Into::into);
+    Ok(match format {
+        Format::Fvecs => Box::new(VecsReader::new(file, VecsFormat::Fvecs).map(to_boxed)),
+        Format::Bvecs => Box::new(VecsReader::new(file, VecsFormat::Bvecs).map(to_boxed)),
+        Format::Ivecs => Box::new(VecsReader::new(file, VecsFormat::Ivecs).map(to_boxed)),
+        Format::Npy => Box::new(NpyReader::new(file)?.map(to_boxed)),
+        Format::Csv => Box::new(
+            BufReader::new(file)
+                .lines()
+                .filter(|line| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+                .map(|line| {
+                    let vector =
+                        line?.split(',').map(|x| x.trim().parse()).collect::<Result<_, _>>()?;
+                    Ok(vector)
+                }),
+        ),
+    })
+}
@@ -0,0 +1,167 @@
+use std::borrow::Cow;
+use std::convert::Infallible;
+use std::mem::size_of;
+
+use byteorder::{BigEndian, ByteOrder};
+use heed::BoxedError;
+
+/// The number of levels a scalar quantized dimension can take.
+const LEVELS: f32 = u8::MAX as f32;
+
+/// The calibration used to scalar quantize the vectors of an index.
+///
+/// Every dimension `i` is mapped to a `u8` code with `c = round((x - min[i]) / step)`. The
+/// per-dimension offsets are folded into the item headers, but the `step` is shared by all the
+/// dimensions on purpose, it's a departure from a per-dimension scale. The distances between two
+/// items are computed directly on their codes, and a per-dimension scale would weight every term
+/// of these sums, which can't be folded into the headers of the items. The downside is that the
+/// narrow dimensions get fewer codes when another dimension has a much wider range.
+///
+/// It is computed over the full-precision vectors of the index when it is first built, or rebuilt
+/// with [`HannoyBuilder::force_rebuild`](crate::HannoyBuilder::force_rebuild), and persisted next
+/// to its metadata. A build inserting values out of its range computes it again, which re-encodes
+/// and relinks all the items.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Calibration {
+    mins: Vec<f32>,
+    step: f32,
+}
+
+impl Calibration {
+    /// Computes the calibration covering the range of all the given vectors.
+    ///
+    /// Returns `None` if no vectors were given.
+    pub fn from_vectors<V: AsRef<[f32]>>(vectors: impl IntoIterator<Item = V>) -> Option<Self> {
+        match Self::try_from_vectors(vectors.into_iter().map(Ok::<_, Infallible>)) {
+            Ok(calibration) => calibration,
+        }
+    }
+
+    /// Same as [`Self::from_vectors`] but stops at the first error.
+    pub(crate) fn try_from_vectors<V: AsRef<[f32]>, E>(
+        vectors: impl IntoIterator<Item = Result<V, E>>,
+    ) -> Result<Option<Self>, E> {
+        let mut vectors = vectors.into_iter();
+        let Some(first) = vectors.next().transpose()? else { return Ok(None) };
+        let mut mins = first.as_ref().to_vec();
+        let mut maxs = first.as_ref().to_vec();
+
+        for vector in vectors {
+            let vector = vector?;
+            for ((min, max), x) in mins.iter_mut().zip(maxs.iter_mut()).zip(vector.as_ref()) {
+                *min = min.min(*x);
+                *max = max.max(*x);
+            }
+        }
+
+        let range = mins.iter().zip(&maxs).map(|(min, max)| max - min).fold(0.0f32, f32::max);
+        // all the vectors are equal, any step is correct
+        let step = if range > 0.0 { range / LEVELS } else { 1.0 };
+
+        Ok(Some(Calibration { mins, step }))
+    }
+
+    /// The number of dimensions covered by this calibration.
+    pub fn dimensions(&self) -> usize {
+        self.mins.len()
+    }
+
+    /// The per-dimension offsets, i.e. the value represented by the code `0`.
+    pub fn mins(&self) -> &[f32] {
+        &self.mins
+    }
+
+    /// The distance between two consecutive codes, shared by all the dimensions.
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    /// Returns `true` if all the values of the vector are within the calibrated range, i.e.
+    /// aren't clamped once quantized.
+    pub fn covers(&self, vector: &[f32]) -> bool {
+        vector.iter().zip(&self.mins).all(|(x, min)| (*min..=min + LEVELS * self.step).contains(x))
+    }
+
+    /// Quantizes a vector into its codes. Values outside the calibrated range are clamped.
+    pub fn quantize(&self, vector: &[f32]) -> Vec<u8> {
+        vector
+            .iter()
+            .zip(&self.mins)
+            .map(|(x, min)| ((x - min) / self.step).round().clamp(0.0, LEVELS) as u8)
+            .collect()
+    }
+
+    /// Returns the values represented by the codes.
+    pub fn dequantize(&self, codes: impl IntoIterator<Item = f32>) -> Vec<f32> {
+        codes.into_iter().zip(&self.mins).map(|(code, min)| min + code * self.step).collect()
+    }
+}
+
+pub enum CalibrationCodec {}
+
+impl<'a> heed::BytesEncode<'a> for CalibrationCodec {
+    type EItem = Calibration;
+
+    fn bytes_encode(item: &'a Self::EItem) -> Result<Cow<'a, [u8]>, BoxedError> {
+        let Calibration { mins, step } = item;
+
+        let mut output = Vec::with_capacity(size_of::<f32>() * (mins.len() + 1));
+        output.extend_from_slice(&step.to_be_bytes());
+        mins.iter().for_each(|min| output.extend_from_slice(&min.to_be_bytes()));
+
+        Ok(Cow::Owned(output))
+    }
+}
+
+impl heed::BytesDecode<'_> for CalibrationCodec {
+    type DItem = Calibration;
+
+    fn bytes_decode(bytes: &'_ [u8]) -> Result<Self::DItem, BoxedError> {
+        if bytes.is_empty() || !bytes.len().is_multiple_of(size_of::<f32>()) {
+            return Err(format!("invalid calibration of {} bytes", bytes.len()).into());
+        }
+
+        let step = BigEndian::read_f32(bytes);
+        let bytes = &bytes[size_of::<f32>()..];
+        let mins = bytes.chunks_exact(size_of::<f32>()).map(BigEndian::read_f32).collect();
+
+        Ok(Calibration { mins, step })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use heed::{BytesDecode, BytesEncode};
+
+    use super::*;
+
+    #[test]
+    fn calibration_codec() {
+        let calibration = Calibration::from_vectors([[0.0, -1.0, 2.0], [1.0, 1.0, 2.0]]).unwrap();
+
+        let encoded = CalibrationCodec::bytes_encode(&calibration).unwrap();
+        let decoded = CalibrationCodec::bytes_decode(&encoded).unwrap();
+
+        assert_eq!(calibration, decoded);
+    }
+
+    #[test]
+    fn quantize_dequantize() {
+        let vectors = [[-1.0, 0.0, 10.0], [1.0, 0.5, 12.0], [0.25, 0.25, 11.0]];
+        let calibration = Calibration::from_vectors(vectors).unwrap();
+
+        assert_eq!(calibration.mins(), &[-1.0, 0.0, 10.0]);
+        assert_eq!(calibration.step(), 2.0 / 255.0);
+
+        for vector in vectors {
+            let codes = calibration.quantize(&vector);
+            let decoded = calibration.dequantize(codes.iter().map(|c| *c as f32));
+            for (x, d) in vector.iter().zip(decoded) {
+                assert!((x - d).abs() <= calibration.step() / 2.0 + f32::EPSILON * 16.0);
+            }
+        }
+
+        // out of range values are clamped
+        assert_eq!(calibration.quantize(&[-5.0, 5.0, 10.5]), vec![0, 255, 64]);
+    }
+}
@@ -0,0 +1,197 @@
+use heed::byteorder::BE;
+use heed::types::{Bytes, Unit, U32};
+use heed::{BytesDecode, RoTxn};
+use roaring::RoaringBitmap;
+
+use crate::attributes::{AttributesCodec, FieldsCodec, PostingsCodec};
+use crate::calibration::CalibrationCodec;
+use crate::external_keys::KeyIndexCodec;
+use crate::key::{KeyCodec, Prefix, PrefixCodec};
+use crate::node::{FullPrecisionCodec, Links, Node};
+use crate::update_status::UpdateStatusCodec;
+use crate::version::VersionCodec;
+use crate::{
+    BuildParamsCodec, Database, Distance, ItemId, LayerId, MetadataCodec, NodeCodec, NodeMode,
+    Result,
+};
+
+/// The result of an integrity check of an index, see [`fsck`].
+///
+/// Nothing is repaired, a report for which [`CheckReport::is_ok`] returns `false` means the
+/// index must be rebuilt or cleared.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CheckReport {
+    /// The metadata of the index are missing or undecodable, the checks relying on them are
+    /// skipped.
+    pub missing_metadata: bool,
+    /// The items stored in the index but missing from the metadata.
+    pub unindexed_items: RoaringBitmap,
+    /// The items listed in the metadata but not stored in the index.
+    pub missing_items: RoaringBitmap,
+    /// The stored items without any links in layer 0.
+    pub unlinked_items: RoaringBitmap,
+    /// The links of an item on a layer pointing to items that aren't stored in the index.
+    pub dangling_links: Vec<(ItemId, LayerId, RoaringBitmap)>,
+    /// The links stored for an item, on a layer, that isn't stored in the index.
+    pub orphan_links: Vec<(ItemId, LayerId)>,
+    /// The entry points of the graph that aren't stored in the index.
+    pub dangling_entry_points: Vec<ItemId>,
+    /// The items updated since the last build.
+    pub updated_items: RoaringBitmap,
+    /// The entries of the index that couldn't be decoded.
+    pub undecodable_nodes: Vec<UndecodableNode>,
+}
+
+impl CheckReport {
+    /// Returns `true` if no problem was found in the index.
+    pub fn is_ok(&self) -> bool {
+        let CheckReport {
+            missing_metadata,
+            unindexed_items,
+            missing_items,
+            unlinked_items,
+            dangling_links,
+            orphan_links,
+            dangling_entry_points,
+            updated_items,
+            undecodable_nodes,
+        } = self;
+
+        !missing_metadata
+            && unindexed_items.is_empty()
+            && missing_items.is_empty()
+            && unlinked_items.is_empty()
+            && dangling_links.is_empty()
+            && orphan_links.is_empty()
+            && dangling_entry_points.is_empty()
+            && updated_items.is_empty()
+            && undecodable_nodes.is_empty()
+    }
+}
+
+/// An entry of the index that couldn't be decoded, see [`CheckReport::undecodable_nodes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndecodableNode {
+    /// The raw LMDB key of the entry.
+    pub key: Vec<u8>,
+    /// Why the key or its value couldn't be decoded.
+    pub error: String,
+}
+
+/// Checks the integrity of an index and reports every inconsistency found instead of panicking.
+///
+/// Unlike [`crate::Reader::open`] it doesn't require the index to be built, which makes it usable
+/// in health checks. See also [`crate::Reader::check`].
+///
+/// # Examples
+///
+/// ```no_run
+/// # use hannoy::{distances::Euclidean, Database};
+/// # let (rtxn, database): (heed::RoTxn, Database<Euclidean>) = todo!();
+/// let report = hannoy::fsck(&rtxn, database, 0)?;
+/// if !report.is_ok() {
+///     eprintln!("index 0 is corrupted: {report:?}");
+/// }
+/// # Ok::<(), hannoy::Error>(())
+/// ```
+pub fn fsck<D: Distance>(rtxn: &RoTxn, database: Database<D>, index: u16) -> Result<CheckReport> {
+    let mut report = CheckReport::default();
+    let mut metadata = None;
+    let mut items = RoaringBitmap::new();
+    let mut linked_items = RoaringBitmap::new();
+    let mut links = Vec::new();
+
+    let iter = database
+        .remap_types::<PrefixCodec, Bytes>()
+        .prefix_iter(rtxn, &Prefix::all(index))?
+        .remap_key_type::<Bytes>();
+
+    for result in iter {
+        let (key_bytes, value) = result?;
+        let undecodable = |error: String| UndecodableNode { key: key_bytes.to_vec(), error };
+
+        let key = match decode::<KeyCodec>(key_bytes) {
+            Ok(key) => key,
+            Err(error) => {
+                report.undecodable_nodes.push(undecodable(error));
+                continue;
+            }
+        };
+
+        let item = key.node.item;
+        let decoded = match key.node.mode {
+            NodeMode::Metadata => match item {
+                0 => decode::<MetadataCodec>(value).map(|m| {
+                    metadata = Some((m.items, Vec::from_iter(m.entry_points.iter())));
+                }),
+                1 => decode::<VersionCodec>(value).map(drop),
+                2 => decode::<CalibrationCodec>(value).map(drop),
+                3 => decode::<FieldsCodec>(value).map(drop),
+                4 => decode::<U32<BE>>(value).map(drop),
+                5 => decode::<BuildParamsCodec>(value).map(drop),
+                6 => decode::<U32<BE>>(value).map(drop),
+                _ => Err(format!("unknown metadata entry {item}")),
+            },
+            NodeMode::Updated => decode::<UpdateStatusCodec>(value).map(|_| {
+                report.updated_items.insert(item);
+            }),
+            NodeMode::Item => match decode::<NodeCodec<D>>(value) {
+                Ok(Node::Item(_)) => {
+                    items.insert(item);
+                    Ok(())
+                }
+                Ok(Node::Links(_)) => Err("expected an item but found links".to_string()),
+                Err(error) => Err(error),
+            },
+            NodeMode::Links => match decode::<NodeCodec<D>>(value) {
+                Ok(Node::Links(Links { links: bitmap })) => {
+                    if key.node.layer == 0 {
+                        linked_items.insert(item);
+                    }
+                    links.push((item, key.node.layer, bitmap.into_owned()));
+                    Ok(())
+                }
+                Ok(Node::Item(_)) => Err("expected links but found an item".to_string()),
+                Err(error) => Err(error),
+            },
+            NodeMode::FullPrecision => decode::<FullPrecisionCodec>(value).map(drop),
+            NodeMode::Attributes => decode::<AttributesCodec>(value).map(drop),
+            NodeMode::Postings => decode::<PostingsCodec>(value).map(drop),
+            NodeMode::ExternalKey => Ok(()),
+            NodeMode::KeyIndex => decode::<KeyIndexCodec>(value).map(drop),
+            NodeMode::FreeId => decode::<Unit>(value).map(drop),
+        };
+
+        if let Err(error) = decoded {
+            report.undecodable_nodes.push(undecodable(error));
+        }
+    }
+
+    match metadata {
+        Some((indexed_items, entry_points)) => {
+            report.unindexed_items = &items - &indexed_items;
+            report.missing_items = &indexed_items - &items;
+            report.dangling_entry_points =
+                entry_points.into_iter().filter(|ep| !items.contains(*ep)).collect();
+        }
+        None => report.missing_metadata = true,
+    }
+
+    report.unlinked_items = &items - &linked_items;
+    for (item, layer, bitmap) in links {
+        if !items.contains(item) {
+            report.orphan_links.push((item, layer));
+        }
+        let dangling = bitmap - &items;
+        if !dangling.is_empty() {
+            report.dangling_links.push((item, layer, dangling));
+        }
+    }
+
+    Ok(report)
+}
+
+/// Decodes `bytes`, the codecs check the bounds and return an error for a malformed entry.
+fn decode<'a, C: BytesDecode<'a>>(bytes: &'a [u8]) -> Result<C::DItem, String> {
+    C::bytes_decode(bytes).map_err(|e| e.to_string())
+}
@@ -2,7 +2,7 @@ use std::fmt;
 
 use bytemuck::{Pod, Zeroable};
 
-use crate::distance::Distance;
+use crate::distance::{Cosine, Distance};
 use crate::node::Item;
 use crate::spaces::simple::dot_product_binary_quantized;
 use crate::unaligned_vector::{BinaryQuantized, UnalignedVector};
@@ -61,4 +61,8 @@ impl Distance for BinaryQuantizedCosine {
     fn norm_no_header(v: &UnalignedVector<Self::VectorCodec>) -> f32 {
         dot_product_binary_quantized(v, v).sqrt()
     }
+
+    fn full_precision_distance() -> Option<fn(&[f32], &[f32]) -> f32> {
+        Some(|p, q| Cosine::distance(&Item::from_slice(p), &Item::from_slice(q)))
+    }
 }
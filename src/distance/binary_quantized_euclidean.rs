@@ -2,7 +2,7 @@ use std::fmt;
 
 use bytemuck::{Pod, Zeroable};
 
-use crate::distance::Distance;
+use crate::distance::{Distance, Euclidean};
 use crate::node::Item;
 use crate::spaces::simple::dot_product_binary_quantized;
 use crate::unaligned_vector::{self, BinaryQuantized, UnalignedVector};
@@ -49,6 +49,10 @@ impl Distance for BinaryQuantizedEuclidean {
     fn norm_no_header(v: &UnalignedVector<Self::VectorCodec>) -> f32 {
         dot_product_binary_quantized(v, v).sqrt()
     }
+
+    fn full_precision_distance() -> Option<fn(&[f32], &[f32]) -> f32> {
+        Some(|p, q| Euclidean::distance(&Item::from_slice(p), &Item::from_slice(q)))
+    }
 }
 
 /// For the binary quantized squared euclidean distance:
@@ -2,7 +2,7 @@ use std::fmt;
 
 use bytemuck::{Pod, Zeroable};
 
-use crate::distance::Distance;
+use crate::distance::{Distance, Manhattan};
 use crate::node::Item;
 use crate::unaligned_vector::{self, BinaryQuantized, UnalignedVector};
 
@@ -53,6 +53,10 @@ impl Distance for BinaryQuantizedManhattan {
             .sum::<i32>() as f32;
         ones.sqrt()
     }
+
+    fn full_precision_distance() -> Option<fn(&[f32], &[f32]) -> f32> {
+        Some(|p, q| Manhattan::distance(&Item::from_slice(p), &Item::from_slice(q)))
+    }
 }
 
 /// For the binary quantized manhattan distance:
@@ -0,0 +1,64 @@
+use std::fmt;
+
+use bytemuck::{Pod, Zeroable};
+use half::f16;
+
+use crate::distance::Distance;
+use crate::node::Item;
+use crate::spaces::simple::dot_product_f16;
+use crate::unaligned_vector::UnalignedVector;
+
+/// The Cosine similarity is a measure of similarity between two
+/// non-zero vectors defined in an inner product space. Cosine similarity
+/// is the cosine of the angle between the vectors.
+/// /!\ The vectors are stored as `f16`, halving their size on disk at
+///     the cost of some precision.
+#[derive(Debug, Clone)]
+pub enum CosineF16 {}
+
+/// The header of `CosineF16` item nodes.
+#[repr(C)]
+#[derive(Pod, Zeroable, Clone, Copy)]
+pub struct NodeHeaderCosineF16 {
+    norm: f32,
+}
+impl fmt::Debug for NodeHeaderCosineF16 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeHeaderCosineF16").field("norm", &format!("{:.4}", self.norm)).finish()
+    }
+}
+
+impl Distance for CosineF16 {
+    type Header = NodeHeaderCosineF16;
+    type VectorCodec = f16;
+
+    fn name() -> &'static str {
+        "f16 cosine"
+    }
+
+    fn new_header(vector: &UnalignedVector<Self::VectorCodec>) -> Self::Header {
+        NodeHeaderCosineF16 { norm: Self::norm_no_header(vector) }
+    }
+
+    fn distance(p: &Item<Self>, q: &Item<Self>) -> f32 {
+        let pn = p.header.norm;
+        let qn = q.header.norm;
+        let pq = dot_product_f16(&p.vector, &q.vector);
+        let pnqn = pn * qn;
+        if pnqn > f32::EPSILON {
+            let cos = pq / pnqn;
+            let cos = cos.clamp(-1.0, 1.0);
+            // cos is [-1; 1]
+            // cos =  0. -> 0.5
+            // cos = -1. -> 1.0
+            // cos =  1. -> 0.0
+            (1.0 - cos) / 2.0
+        } else {
+            0.0
+        }
+    }
+
+    fn norm_no_header(v: &UnalignedVector<Self::VectorCodec>) -> f32 {
+        dot_product_f16(v, v).sqrt()
+    }
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+
+use bytemuck::{Pod, Zeroable};
+use heed::byteorder::BE;
+use heed::types::{DecodeIgnore, U32};
+use heed::{PutFlags, RwTxn};
+use roaring::RoaringBitmap;
+
+use crate::distance::Distance;
+use crate::internals::KeyCodec;
+use crate::node::{Item, NodeCodec};
+use crate::spaces::simple::dot_product;
+use crate::unaligned_vector::UnalignedVector;
+use crate::{Database, Key, Node, Prefix, PrefixCodec, Result};
+
+/// The dot-product distance ranks items by maximum inner product (MIPS).
+///
+/// The inner product isn't a metric, so graph navigation suffers when using it directly. Instead
+/// every item `x` is augmented with an extra dimension `sqrt(Φ² - |x|²)`, where `Φ` is the biggest
+/// norm added to the index, and queries are augmented with a zero. The squared euclidean
+/// distance in this augmented space is `Φ² + |q|² - 2 p·q`, which for a fixed query is
+/// minimized exactly when the inner product is maximized.
+///
+/// Unlike [`Cosine`](crate::distances::Cosine), the magnitude of the vectors is kept.
+///
+/// `Φ` is stored in the index and only grows. A build whose new items stay under it only sets
+/// their extra dimension, but an item going over it changes the extra dimension of every item
+/// and the whole index is relinked. Adding the items with the biggest norms first keeps the
+/// following builds incremental.
+#[derive(Debug, Clone)]
+pub enum DotProduct {}
+
+/// The header of DotProduct item nodes.
+#[repr(C)]
+#[derive(Pod, Zeroable, Clone, Copy)]
+pub struct NodeHeaderDotProduct {
+    norm: f32,
+    /// An extra dimension making all the items of the index share the same norm.
+    extra_dim: f32,
+}
+impl fmt::Debug for NodeHeaderDotProduct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeHeaderDotProduct")
+            .field("norm", &format!("{:.4}", self.norm))
+            .field("extra_dim", &format!("{:.4}", self.extra_dim))
+            .finish()
+    }
+}
+
+impl Distance for DotProduct {
+    type Header = NodeHeaderDotProduct;
+    type VectorCodec = f32;
+
+    fn name() -> &'static str {
+        "dot-product"
+    }
+
+    fn new_header(vector: &UnalignedVector<Self::VectorCodec>) -> Self::Header {
+        // The extra dimension is only known once all the items are there, see `preprocess`.
+        NodeHeaderDotProduct { norm: Self::norm_no_header(vector), extra_dim: 0.0 }
+    }
+
+    fn distance(p: &Item<Self>, q: &Item<Self>) -> f32 {
+        let p_sq = p.header.norm * p.header.norm + p.header.extra_dim * p.header.extra_dim;
+        let q_sq = q.header.norm * q.header.norm + q.header.extra_dim * q.header.extra_dim;
+        let pq = dot_product(&p.vector, &q.vector) + p.header.extra_dim * q.header.extra_dim;
+        // rounding errors may push the distance slightly below zero
+        (p_sq + q_sq - 2.0 * pq).max(0.0)
+    }
+
+    fn norm(item: &Item<Self>) -> f32 {
+        item.header.norm
+    }
+
+    fn norm_no_header(v: &UnalignedVector<Self::VectorCodec>) -> f32 {
+        dot_product(v, v).sqrt()
+    }
+
+    fn preprocess(wtxn: &mut RwTxn, database: Database<Self>, index: u16) -> Result<RoaringBitmap> {
+        let stored_max_norm = database
+            .remap_data_type::<U32<BE>>()
+            .get(wtxn, &Key::max_norm(index))?
+            .map(f32::from_bits);
+
+        // only the updated items can go over the biggest norm of the items already indexed
+        let updated = database
+            .remap_types::<PrefixCodec, DecodeIgnore>()
+            .prefix_iter(wtxn, &Prefix::updated(index))?
+            .remap_key_type::<KeyCodec>()
+            .map(|result| result.map(|(key, _)| key.node.item))
+            .collect::<heed::Result<RoaringBitmap>>()?;
+        let mut updated_max_norm = 0.0f32;
+        for item in &updated {
+            if let Some(Node::Item(item)) = database.get(wtxn, &Key::item(index, item))? {
+                updated_max_norm = updated_max_norm.max(item.header.norm);
+            }
+        }
+
+        let max_norm = match stored_max_norm {
+            Some(max_norm) if updated_max_norm <= max_norm => {
+                for item in &updated {
+                    let key = Key::item(index, item);
+                    let Some(Node::Item(item)) = database.get(wtxn, &key)? else { continue };
+                    let extra_dim = extra_dim(max_norm, item.header.norm);
+                    if item.header.extra_dim.to_bits() != extra_dim.to_bits() {
+                        let mut item = item.into_owned();
+                        item.header.extra_dim = extra_dim;
+                        database.put(wtxn, &key, &Node::Item(item))?;
+                    }
+                }
+                // the updated items are relinked anyway
+                return Ok(RoaringBitmap::new());
+            }
+            Some(_) => updated_max_norm,
+            // the index was built before the biggest norm was stored
+            None => database
+                .remap_key_type::<PrefixCodec>()
+                .prefix_iter(wtxn, &Prefix::item(index))?
+                .remap_key_type::<KeyCodec>()
+                .try_fold(0.0f32, |max, result| match result? {
+                    (_, Node::Item(item)) => Ok::<_, heed::Error>(max.max(item.header.norm)),
+                    (_, Node::Links(_)) => unreachable!("Node must not be a link"),
+                })?,
+        };
+        database.remap_data_type::<U32<BE>>().put(
+            wtxn,
+            &Key::max_norm(index),
+            &max_norm.to_bits(),
+        )?;
+
+        let mut cursor = database
+            .remap_key_type::<PrefixCodec>()
+            .prefix_iter_mut(wtxn, &Prefix::item(index))?
+            .remap_key_type::<KeyCodec>();
+
+        // when the biggest norm changes the geometry of the whole index changes with it
+        let mut changed = RoaringBitmap::new();
+        while let Some((key, node)) = cursor.next().transpose()? {
+            let Node::Item(item) = node else { unreachable!("Node must not be a link") };
+            let extra_dim = extra_dim(max_norm, item.header.norm);
+            if item.header.extra_dim.to_bits() == extra_dim.to_bits() {
+                continue;
+            }
+
+            changed.insert(key.node.item);
+            let mut item = item.into_owned();
+            item.header.extra_dim = extra_dim;
+            unsafe {
+                // safety: We do not keep a reference to the current value, we own it.
+                cursor.put_current_with_options::<NodeCodec<Self>>(
+                    PutFlags::empty(),
+                    &key,
+                    &Node::Item(item),
+                )?
+            };
+        }
+
+        Ok(changed)
+    }
+}
+
+/// The extra dimension bringing an item of this `norm` to the norm of the biggest item.
+fn extra_dim(max_norm: f32, norm: f32) -> f32 {
+    (max_norm * max_norm - norm * norm).max(0.0).sqrt()
+}
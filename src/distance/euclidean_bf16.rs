@@ -0,0 +1,54 @@
+use std::fmt;
+
+use bytemuck::{Pod, Zeroable};
+use half::bf16;
+
+use crate::distance::Distance;
+use crate::node::Item;
+use crate::spaces::simple::{dot_product_bf16, euclidean_distance_bf16};
+use crate::unaligned_vector::UnalignedVector;
+
+/// The Euclidean distance between two points in Euclidean space
+/// is the length of the line segment between them.
+///
+/// `d(p, q) = sqrt((p - q)²)`
+/// /!\ The vectors are stored as `bf16`, halving their size on disk at
+///     the cost of some precision.
+#[derive(Debug, Clone)]
+pub enum EuclideanBf16 {}
+
+/// The header of `EuclideanBf16` item nodes.
+#[repr(C)]
+#[derive(Pod, Zeroable, Clone, Copy)]
+pub struct NodeHeaderEuclideanBf16 {
+    /// An extra constant term to determine the offset of the plane
+    bias: f32,
+}
+impl fmt::Debug for NodeHeaderEuclideanBf16 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeHeaderEuclideanBf16")
+            .field("bias", &format!("{:.4}", self.bias))
+            .finish()
+    }
+}
+
+impl Distance for EuclideanBf16 {
+    type Header = NodeHeaderEuclideanBf16;
+    type VectorCodec = bf16;
+
+    fn name() -> &'static str {
+        "bf16 euclidean"
+    }
+
+    fn new_header(_vector: &UnalignedVector<Self::VectorCodec>) -> Self::Header {
+        NodeHeaderEuclideanBf16 { bias: 0.0 }
+    }
+
+    fn distance(p: &Item<Self>, q: &Item<Self>) -> f32 {
+        euclidean_distance_bf16(&p.vector, &q.vector)
+    }
+
+    fn norm_no_header(v: &UnalignedVector<Self::VectorCodec>) -> f32 {
+        dot_product_bf16(v, v).sqrt()
+    }
+}
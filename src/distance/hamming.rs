@@ -1,4 +1,5 @@
 use std::fmt;
+use std::mem::size_of;
 
 use bytemuck::{Pod, Zeroable};
 
@@ -41,6 +42,10 @@ impl Distance for Hamming {
         NodeHeaderHamming { idx: 0 }
     }
 
+    fn header_field_size() -> usize {
+        size_of::<usize>()
+    }
+
     fn distance(p: &Item<Self>, q: &Item<Self>) -> f32 {
         let dist = hamming_bitwise_fast(p.vector.as_bytes(), q.vector.as_bytes());
         dist / (p.vector.len() as f32)
@@ -1,24 +1,45 @@
 use std::fmt;
+use std::mem::size_of;
 
 pub use binary_quantized_cosine::{BinaryQuantizedCosine, NodeHeaderBinaryQuantizedCosine};
 pub use binary_quantized_euclidean::BinaryQuantizedEuclidean;
 pub use binary_quantized_manhattan::BinaryQuantizedManhattan;
 use bytemuck::{Pod, Zeroable};
 pub use cosine::{Cosine, NodeHeaderCosine};
+pub use cosine_bf16::{CosineBf16, NodeHeaderCosineBf16};
+pub use cosine_f16::{CosineF16, NodeHeaderCosineF16};
+pub use dot_product::{DotProduct, NodeHeaderDotProduct};
 pub use euclidean::{Euclidean, NodeHeaderEuclidean};
+pub use euclidean_bf16::{EuclideanBf16, NodeHeaderEuclideanBf16};
+pub use euclidean_f16::{EuclideanF16, NodeHeaderEuclideanF16};
 pub use hamming::Hamming;
+use heed::RwTxn;
 pub use manhattan::Manhattan;
+use roaring::RoaringBitmap;
+pub use scalar_quantized_cosine::{NodeHeaderScalarQuantizedCosine, ScalarQuantizedCosine};
+pub use scalar_quantized_euclidean::{
+    NodeHeaderScalarQuantizedEuclidean, ScalarQuantizedEuclidean,
+};
 
+use crate::calibration::Calibration;
 use crate::node::Item;
 use crate::unaligned_vector::{UnalignedVector, UnalignedVectorCodec};
+use crate::{Database, Result};
 
 mod binary_quantized_cosine;
 mod binary_quantized_euclidean;
 mod binary_quantized_manhattan;
 mod cosine;
+mod cosine_bf16;
+mod cosine_f16;
+mod dot_product;
 mod euclidean;
+mod euclidean_bf16;
+mod euclidean_f16;
 mod hamming;
 mod manhattan;
+mod scalar_quantized_cosine;
+mod scalar_quantized_euclidean;
 
 /// A trait used by hannoy to compute the distances,
 /// compute the split planes, and normalize user vectors.
@@ -45,4 +66,49 @@ pub trait Distance: Send + Sync + Sized + Clone + fmt::Debug + 'static {
     }
 
     fn norm_no_header(v: &UnalignedVector<Self::VectorCodec>) -> f32;
+
+    /// Whether the vectors must be quantized with a [`Calibration`] computed over the whole index
+    /// when it is built, see [`Self::encode`].
+    fn needs_calibration() -> bool {
+        false
+    }
+
+    /// Encodes a user vector into an item, quantizing it with the `calibration` of the index
+    /// when there is one.
+    fn encode<'v>(vector: &'v [f32], _calibration: Option<&Calibration>) -> Item<'v, Self> {
+        Item::from_slice(vector)
+    }
+
+    /// Decodes the vector of an item back into f32s, the opposite of [`Self::encode`].
+    fn decode(item: &Item<Self>, _calibration: Option<&Calibration>) -> Vec<f32> {
+        item.vector.to_vec()
+    }
+
+    /// The distance used to rescore the candidates found with this quantized distance against
+    /// their full-precision vectors, see [`QueryBuilder::rescore`](crate::QueryBuilder::rescore).
+    ///
+    /// Distances that already work on the full-precision vectors return `None`.
+    #[allow(clippy::type_complexity)]
+    fn full_precision_distance() -> Option<fn(&[f32], &[f32]) -> f32> {
+        None
+    }
+
+    /// The size in bytes of the fields of the [`Self::Header`], they are stored in the byte order
+    /// of the machine.
+    fn header_field_size() -> usize {
+        size_of::<f32>()
+    }
+
+    /// Called right before building the graph to let the distance update the headers of
+    /// the items, e.g. when they depend on the whole set of vectors stored in the index.
+    ///
+    /// Returns the items whose header changed, their distances changed too and they must be
+    /// relinked.
+    fn preprocess(
+        _wtxn: &mut RwTxn,
+        _database: Database<Self>,
+        _index: u16,
+    ) -> Result<RoaringBitmap> {
+        Ok(RoaringBitmap::new())
+    }
 }
@@ -0,0 +1,121 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::calibration::Calibration;
+use crate::distance::{Cosine, Distance};
+use crate::node::Item;
+use crate::spaces::simple::dot_product_scalar_quantized;
+use crate::unaligned_vector::{ScalarQuantized, UnalignedVector};
+
+/// The Cosine similarity is a measure of similarity between two
+/// non-zero vectors defined in an inner product space. Cosine similarity
+/// is the cosine of the angle between the vectors.
+/// /!\ This distance function is scalar quantized, which means every dimension is stored
+///     as one of the 256 levels defined by the [`Calibration`] of the index.
+#[derive(Debug, Clone)]
+pub enum ScalarQuantizedCosine {}
+
+/// The header of `ScalarQuantizedCosine` item nodes.
+///
+/// With `x = min + step * c`, the dot product of two items is
+/// `bias(p) + bias(q) + step² * (cp · cq)`, with `bias(p) = step * (min · cp) + |min|² / 2`.
+#[repr(C)]
+#[derive(Pod, Zeroable, Clone, Copy)]
+pub struct NodeHeaderScalarQuantizedCosine {
+    norm: f32,
+    /// The contribution of the per-dimension offsets to the dot product.
+    bias: f32,
+    /// The distance between two consecutive codes.
+    step: f32,
+}
+impl fmt::Debug for NodeHeaderScalarQuantizedCosine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeHeaderScalarQuantizedCosine")
+            .field("norm", &format!("{:.4}", self.norm))
+            .field("bias", &format!("{:.4}", self.bias))
+            .field("step", &format!("{:.4}", self.step))
+            .finish()
+    }
+}
+
+impl ScalarQuantizedCosine {
+    fn dot_product(p: &Item<Self>, q: &Item<Self>) -> f32 {
+        let step = p.header.step;
+        let pq = dot_product_scalar_quantized(&p.vector, &q.vector) as f32;
+        p.header.bias + q.header.bias + step * step * pq
+    }
+}
+
+impl Distance for ScalarQuantizedCosine {
+    type Header = NodeHeaderScalarQuantizedCosine;
+    type VectorCodec = ScalarQuantized;
+
+    fn name() -> &'static str {
+        "scalar quantized cosine"
+    }
+
+    fn new_header(vector: &UnalignedVector<Self::VectorCodec>) -> Self::Header {
+        NodeHeaderScalarQuantizedCosine { norm: Self::norm_no_header(vector), bias: 0.0, step: 1.0 }
+    }
+
+    fn distance(p: &Item<Self>, q: &Item<Self>) -> f32 {
+        let pn = p.header.norm;
+        let qn = q.header.norm;
+        let pq = Self::dot_product(p, q);
+        let pnqn = pn * qn;
+        if pnqn > f32::EPSILON {
+            let cos = pq / pnqn;
+            let cos = cos.clamp(-1.0, 1.0);
+            // cos is [-1; 1]
+            // cos =  0. -> 0.5
+            // cos = -1. -> 1.0
+            // cos =  1. -> 0.0
+            (1.0 - cos) / 2.0
+        } else {
+            0.0
+        }
+    }
+
+    fn norm(item: &Item<Self>) -> f32 {
+        item.header.norm
+    }
+
+    fn norm_no_header(v: &UnalignedVector<Self::VectorCodec>) -> f32 {
+        (dot_product_scalar_quantized(v, v) as f32).sqrt()
+    }
+
+    fn full_precision_distance() -> Option<fn(&[f32], &[f32]) -> f32> {
+        Some(|p, q| Cosine::distance(&Item::from_slice(p), &Item::from_slice(q)))
+    }
+
+    fn needs_calibration() -> bool {
+        true
+    }
+
+    fn encode<'v>(vector: &'v [f32], calibration: Option<&Calibration>) -> Item<'v, Self> {
+        let Some(calibration) = calibration else { return Item::new(vector.to_vec()) };
+
+        let codes = calibration.quantize(vector);
+        let step = calibration.step();
+        let mins = calibration.mins();
+        let min_dot_codes: f32 = mins.iter().zip(&codes).map(|(min, c)| min * *c as f32).sum();
+        let min_norm_sq: f32 = mins.iter().map(|min| min * min).sum();
+        let bias = step * min_dot_codes + min_norm_sq / 2.0;
+
+        let mut item: Item<Self> = Item {
+            header: NodeHeaderScalarQuantizedCosine { norm: 0.0, bias, step },
+            vector: Cow::Owned(codes),
+        };
+        item.header.norm = Self::dot_product(&item, &item).max(0.0).sqrt();
+        item
+    }
+
+    fn decode(item: &Item<Self>, calibration: Option<&Calibration>) -> Vec<f32> {
+        match calibration {
+            Some(calibration) => calibration.dequantize(item.vector.iter()),
+            None => item.vector.to_vec(),
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::calibration::Calibration;
+use crate::distance::{Distance, Euclidean};
+use crate::node::Item;
+use crate::spaces::simple::{dot_product_scalar_quantized, euclidean_distance_scalar_quantized};
+use crate::unaligned_vector::{ScalarQuantized, UnalignedVector};
+
+/// The Euclidean distance between two points in Euclidean space
+/// is the length of the line segment between them.
+///
+/// `d(p, q) = sqrt((p - q)²)`
+/// /!\ This distance function is scalar quantized, which means every dimension is stored
+///     as one of the 256 levels defined by the [`Calibration`] of the index.
+#[derive(Debug, Clone)]
+pub enum ScalarQuantizedEuclidean {}
+
+/// The header of `ScalarQuantizedEuclidean` item nodes.
+#[repr(C)]
+#[derive(Pod, Zeroable, Clone, Copy)]
+pub struct NodeHeaderScalarQuantizedEuclidean {
+    /// The distance between two consecutive codes.
+    step: f32,
+}
+impl fmt::Debug for NodeHeaderScalarQuantizedEuclidean {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeHeaderScalarQuantizedEuclidean")
+            .field("step", &format!("{:.4}", self.step))
+            .finish()
+    }
+}
+
+impl Distance for ScalarQuantizedEuclidean {
+    type Header = NodeHeaderScalarQuantizedEuclidean;
+    type VectorCodec = ScalarQuantized;
+
+    fn name() -> &'static str {
+        "scalar quantized euclidean"
+    }
+
+    fn new_header(_vector: &UnalignedVector<Self::VectorCodec>) -> Self::Header {
+        NodeHeaderScalarQuantizedEuclidean { step: 1.0 }
+    }
+
+    fn distance(p: &Item<Self>, q: &Item<Self>) -> f32 {
+        // the offsets cancel out, only the codes matter
+        let step = p.header.step;
+        euclidean_distance_scalar_quantized(&p.vector, &q.vector) as f32 * step * step
+    }
+
+    fn norm_no_header(v: &UnalignedVector<Self::VectorCodec>) -> f32 {
+        (dot_product_scalar_quantized(v, v) as f32).sqrt()
+    }
+
+    fn full_precision_distance() -> Option<fn(&[f32], &[f32]) -> f32> {
+        Some(|p, q| Euclidean::distance(&Item::from_slice(p), &Item::from_slice(q)))
+    }
+
+    fn needs_calibration() -> bool {
+        true
+    }
+
+    fn encode<'v>(vector: &'v [f32], calibration: Option<&Calibration>) -> Item<'v, Self> {
+        match calibration {
+            Some(calibration) => {
+                let vector = Cow::Owned(calibration.quantize(vector));
+                Item {
+                    header: NodeHeaderScalarQuantizedEuclidean { step: calibration.step() },
+                    vector,
+                }
+            }
+            None => Item::new(vector.to_vec()),
+        }
+    }
+
+    fn decode(item: &Item<Self>, calibration: Option<&Calibration>) -> Vec<f32> {
+        match calibration {
+            Some(calibration) => calibration.dequantize(item.vector.iter()),
+            None => item.vector.to_vec(),
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! A portable, single-file, representation of an index, see [`crate::Reader::dump`] and
+//! [`crate::Writer::restore`].
+//!
+//! The stream starts with a header, the magic string, the version of the format and the name of
+//! the distance. It's followed by the entries of the index, each one being a tag, the mode, item
+//! and layer of its key and the length-prefixed raw value. It ends with a tag, the number of
+//! entries and the CRC32 of everything that precedes it.
+//!
+//! Everything is little-endian, the items and full-precision vectors, stored in the byte order of
+//! the machine, are converted when dumped and restored.
+
+use std::io::{self, BufReader, Read, Write};
+use std::mem::size_of;
+
+use heed::types::Bytes;
+use heed::{BytesDecode, RoTxn, RwTxn};
+
+use crate::internals::KeyCodec;
+use crate::key::{Prefix, PrefixCodec};
+use crate::unaligned_vector::UnalignedVectorCodec;
+use crate::{Database, Distance, Error, Key, MetadataCodec, NodeId, NodeMode, Result};
+
+const MAGIC: &[u8; 8] = b"HANNOYDP";
+const DUMP_VERSION: u32 = 1;
+const ENTRY_TAG: u8 = 1;
+const END_TAG: u8 = 0;
+const NATIVE_IS_LITTLE_ENDIAN: bool = cfg!(target_endian = "little");
+
+/// The longest value of an entry, larger lengths can only come from a corrupted dump.
+const MAX_VALUE_LEN: u32 = 1 << 30;
+
+pub(crate) fn dump<D: Distance>(
+    database: Database<D>,
+    index: u16,
+    rtxn: &RoTxn,
+    output: impl Write,
+) -> Result<()> {
+    let mut output = Checksummed { inner: output, hasher: crc32fast::Hasher::new() };
+    output.write_all(MAGIC)?;
+    output.write_all(&DUMP_VERSION.to_le_bytes())?;
+    write_bytes(&mut output, D::name().as_bytes())?;
+
+    let iter = database
+        .remap_types::<PrefixCodec, Bytes>()
+        .prefix_iter(rtxn, &Prefix::all(index))?
+        .remap_key_type::<KeyCodec>();
+
+    let mut n_entries = 0u64;
+    let mut buffer = Vec::new();
+    for result in iter {
+        let (key, value) = result?;
+        // a built index doesn't have any, and they are meaningless without the previous state
+        if key.node.mode == NodeMode::Updated {
+            continue;
+        }
+        output.write_all(&[ENTRY_TAG, key.node.mode as u8])?;
+        output.write_all(&key.node.item.to_le_bytes())?;
+        output.write_all(&[key.node.layer])?;
+        let value = if NATIVE_IS_LITTLE_ENDIAN {
+            value
+        } else {
+            buffer.clear();
+            buffer.extend_from_slice(value);
+            swap_byte_order::<D>(key.node.mode, &mut buffer);
+            &buffer[..]
+        };
+        write_bytes(&mut output, value)?;
+        n_entries += 1;
+    }
+
+    output.write_all(&[END_TAG])?;
+    output.write_all(&n_entries.to_le_bytes())?;
+    let checksum = output.hasher.finalize();
+    output.inner.write_all(&checksum.to_le_bytes())?;
+    output.inner.flush()?;
+
+    Ok(())
+}
+
+/// Writes the entries of a dump in an index that must be empty.
+pub(crate) fn restore<D: Distance>(
+    database: Database<D>,
+    index: u16,
+    dimensions: usize,
+    wtxn: &mut RwTxn,
+    input: impl Read,
+) -> Result<()> {
+    let mut input = Checksummed { inner: BufReader::new(input), hasher: crc32fast::Hasher::new() };
+
+    let mut magic = [0; MAGIC.len()];
+    input.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid("not a hannoy dump"));
+    }
+    let version = u32::from_le_bytes(read_array(&mut input)?);
+    if version != DUMP_VERSION {
+        return Err(invalid(format!("unsupported version {version} of the format")));
+    }
+    let mut distance = Vec::new();
+    read_bytes(&mut input, &mut distance)?;
+    let distance = String::from_utf8_lossy(&distance);
+    if distance != D::name() {
+        return Err(Error::UnmatchingDistance {
+            expected: distance.into_owned(),
+            received: D::name(),
+        });
+    }
+
+    let database = database.remap_data_type::<Bytes>();
+    let mut value = Vec::new();
+    let mut n_entries = 0u64;
+    loop {
+        match read_array(&mut input)? {
+            [ENTRY_TAG] => (),
+            [END_TAG] => break,
+            [tag] => return Err(invalid(format!("unknown tag {tag}"))),
+        }
+        let [mode] = read_array(&mut input)?;
+        let mode = NodeMode::try_from(mode).map_err(invalid)?;
+        let item = u32::from_le_bytes(read_array(&mut input)?);
+        let [layer] = read_array(&mut input)?;
+        read_bytes(&mut input, &mut value)?;
+        if !NATIVE_IS_LITTLE_ENDIAN {
+            swap_byte_order::<D>(mode, &mut value);
+        }
+
+        let key = Key::new(index, NodeId { mode, item, layer });
+        if mode == NodeMode::Metadata && item == 0 {
+            let metadata = MetadataCodec::bytes_decode(&value)
+                .map_err(|e| invalid(format!("invalid metadata: {e}")))?;
+            if metadata.dimensions as usize != dimensions {
+                return Err(Error::InvalidVecDimension {
+                    expected: dimensions,
+                    received: metadata.dimensions as usize,
+                });
+            }
+        }
+        database.put(wtxn, &key, &value)?;
+        n_entries += 1;
+    }
+
+    let expected_entries = u64::from_le_bytes(read_array(&mut input)?);
+    let checksum = input.hasher.finalize();
+    let expected_checksum = u32::from_le_bytes(read_array(&mut input.inner)?);
+    if checksum != expected_checksum {
+        return Err(invalid("checksum mismatch, the dump is corrupted"));
+    }
+    if n_entries != expected_entries {
+        return Err(invalid(format!("expected {expected_entries} entries but got {n_entries}")));
+    }
+
+    Ok(())
+}
+
+/// Computes the checksum of the bytes going through the inner reader or writer.
+struct Checksummed<T> {
+    inner: T,
+    hasher: crc32fast::Hasher,
+}
+
+impl<W: Write> Write for Checksummed<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<R: Read> Read for Checksummed<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+/// Converts the native-endian items and full-precision vectors from or to little-endian, the
+/// other entries are already encoded with a fixed byte order.
+fn swap_byte_order<D: Distance>(mode: NodeMode, value: &mut [u8]) {
+    let (header_len, header_field, element) = match mode {
+        NodeMode::Item => {
+            (size_of::<D::Header>(), D::header_field_size(), D::VectorCodec::element_size())
+        }
+        NodeMode::FullPrecision => (0, 1, size_of::<f32>()),
+        _ => return,
+    };
+    // the items start with the tag of their node
+    let value = if mode == NodeMode::Item { value.get_mut(1..).unwrap_or_default() } else { value };
+    let (header, vector) = value.split_at_mut(header_len.min(value.len()));
+    header.chunks_exact_mut(header_field).for_each(<[u8]>::reverse);
+    vector.chunks_exact_mut(element).for_each(<[u8]>::reverse);
+}
+
+fn write_bytes(output: &mut impl Write, bytes: &[u8]) -> io::Result<()> {
+    output.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    output.write_all(bytes)
+}
+
+/// Reads a length-prefixed value, the length isn't trusted until the checksum is verified and the
+/// buffer only grows with the bytes actually read.
+fn read_bytes(input: &mut impl Read, bytes: &mut Vec<u8>) -> Result<()> {
+    let len = u32::from_le_bytes(read_array(input)?);
+    if len > MAX_VALUE_LEN {
+        return Err(invalid(format!("an entry of {len} bytes is too long")));
+    }
+    bytes.clear();
+    input.take(len.into()).read_to_end(bytes)?;
+    if bytes.len() != len as usize {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(())
+}
+
+fn read_array<const N: usize>(input: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut array = [0; N];
+    input.read_exact(&mut array)?;
+    Ok(array)
+}
+
+fn invalid(reason: impl Into<String>) -> Error {
+    Error::InvalidFileFormat { format: "dump", reason: reason.into() }
+}
+
+#[cfg(test)]
+mod test {
+    use heed::BytesEncode;
+
+    use super::*;
+    use crate::distances::{Euclidean, Hamming};
+    use crate::node::{Item, Node, NodeCodec};
+
+    fn swapped(values: impl IntoIterator<Item = f32>) -> Vec<u8> {
+        values.into_iter().flat_map(|x| x.to_bits().swap_bytes().to_ne_bytes()).collect()
+    }
+
+    #[test]
+    fn swap_byte_order_of_items() {
+        let item = Node::Item(Item::<Euclidean>::new(vec![1.0, -2.5, 3.25]));
+        let mut value = NodeCodec::bytes_encode(&item).unwrap().into_owned();
+        let original = value.clone();
+        swap_byte_order::<Euclidean>(NodeMode::Item, &mut value);
+        let bias = f32::from_ne_bytes(original[1..5].try_into().unwrap());
+        assert_eq!(value[0], original[0]);
+        assert_eq!(value[1..], swapped([bias, 1.0, -2.5, 3.25]));
+        swap_byte_order::<Euclidean>(NodeMode::Item, &mut value);
+        assert_eq!(value, original);
+
+        let item = Node::Item(Item::<Hamming>::new(vec![1.0; 64]));
+        let mut value = NodeCodec::bytes_encode(&item).unwrap().into_owned();
+        let original = value.clone();
+        swap_byte_order::<Hamming>(NodeMode::Item, &mut value);
+        let header_end = 1 + size_of::<usize>();
+        assert!(value[1..header_end].iter().all(|b| *b == 0));
+        assert_eq!(value[header_end..], u64::MAX.to_ne_bytes());
+        swap_byte_order::<Hamming>(NodeMode::Item, &mut value);
+        assert_eq!(value, original);
+
+        let mut value: Vec<u8> = [0.5f32, 7.0].iter().flat_map(|x| x.to_ne_bytes()).collect();
+        swap_byte_order::<Hamming>(NodeMode::FullPrecision, &mut value);
+        assert_eq!(value, swapped([0.5, 7.0]));
+
+        // the links are portable and left untouched
+        let mut value = vec![1, 2, 3, 4, 5];
+        swap_byte_order::<Euclidean>(NodeMode::Links, &mut value);
+        assert_eq!(value, [1, 2, 3, 4, 5]);
+    }
+}
@@ -1,6 +1,7 @@
 use std::io;
 
 use crate::key::Key;
+use crate::metadata::BuildParams;
 use crate::node_id::NodeMode;
 use crate::version::Version;
 use crate::{ItemId, LayerId};
@@ -25,6 +26,25 @@ pub enum Error {
         received: usize,
     },
 
+    /// The user is trying to insert a vector with a value that the codec of the distance can't
+    /// store, e.g. above 65504 in magnitude for the `f16` distances.
+    #[error("Invalid vector value {value}, the values must be at most {max} in magnitude")]
+    ValueOutOfRange {
+        /// The value given by the user.
+        value: f32,
+        /// The biggest magnitude the codec can store.
+        max: f32,
+    },
+
+    /// The file being imported, or the vectors being exported, don't respect the file format.
+    #[error("Invalid {format} file: {reason}")]
+    InvalidFileFormat {
+        /// The format of the file, e.g. `fvecs` or `npy`.
+        format: &'static str,
+        /// What is wrong with the file.
+        reason: String,
+    },
+
     /// An internal error returned when hannoy cannot generate internal IDs.
     #[error("Database full. Hannoy cannot generate enough internal IDs for your items")]
     DatabaseFull,
@@ -54,6 +74,37 @@ pub enum Error {
     #[error("The graph has not been built after an update on index {0}")]
     NeedBuild(u16),
 
+    /// The user is trying to incrementally build an index with a graph degree that is
+    /// not the one the index was built with. Use [`crate::HannoyBuilder::force_rebuild`] instead.
+    #[error("Invalid build parameters on index {index}. Got {received} but the graph was built with {expected}")]
+    UnmatchingBuildParams {
+        /// The index that was being built.
+        index: u16,
+        /// The parameters the graph was built with.
+        expected: BuildParams,
+        /// The parameters given by the user.
+        received: BuildParams,
+    },
+
+    /// The numbers of links per node given to the [`crate::HannoyBuilder`] are too small, `m`
+    /// must be at least 2 and `m0` at least 1.
+    #[error("Invalid build parameters, got {0} but `m` must be at least 2 and `m0` at least 1")]
+    InvalidBuildParams(BuildParams),
+
+    /// The `lambda` given to [`QueryBuilder::diversify`](crate::QueryBuilder::diversify) must
+    /// be between 0.0 and 1.0.
+    #[error("Invalid diversification lambda {0}, it must be between 0.0 and 1.0")]
+    InvalidDiversification(f32),
+
+    /// The items of an index can't have attributes in more than 256 different fields.
+    #[error("Too many attribute fields on index {0}, an index can have at most 256 of them")]
+    TooManyAttributeFields(u16),
+
+    /// The read transactions opened to search in parallel don't see the same version of the
+    /// database as the [`crate::Reader`], it must be reopened on the latest version.
+    #[error("The database has been modified since the reader was opened")]
+    OutdatedReader,
+
     /// Returned iff the `should_abort` function returned true.
     #[error("The corresponding build process has been cancelled")]
     BuildCancelled,
@@ -95,6 +146,12 @@ impl Error {
                 NodeMode::Links => "Links",
                 NodeMode::Metadata => "Metadata",
                 NodeMode::Updated => "Updated",
+                NodeMode::FullPrecision => "FullPrecision",
+                NodeMode::Attributes => "Attributes",
+                NodeMode::Postings => "Postings",
+                NodeMode::ExternalKey => "ExternalKey",
+                NodeMode::KeyIndex => "KeyIndex",
+                NodeMode::FreeId => "FreeId",
             },
             item: key.node.item,
             layer: key.node.layer,
@@ -0,0 +1,176 @@
+//! Measures the recall of an index against an exact search, to tune `ef_search` on your own data.
+//!
+//! ```no_run
+//! # use hannoy::{Reader, distances::Euclidean};
+//! # let (reader, rtxn): (Reader<Euclidean>, heed::RoTxn) = todo!();
+//! use hannoy::eval::Evaluator;
+//! use rand::rngs::StdRng;
+//! use rand::SeedableRng;
+//!
+//! let mut rng = StdRng::seed_from_u64(42);
+//! let reports = Evaluator::new(&reader).k(10).ef_searches(vec![16, 64, 256]).run(&rtxn, &mut rng)?;
+//! for report in reports {
+//!     println!("{report}");
+//! }
+//! # Ok::<(), hannoy::Error>(())
+//! ```
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use heed::RoTxn;
+use rand::seq::IteratorRandom;
+use rand::Rng;
+
+use crate::{Distance, ItemId, Reader, Result};
+
+/// The `ef_search` values evaluated by default.
+const DEFAULT_EF_SEARCHES: [usize; 6] = [10, 20, 50, 100, 200, 400];
+
+/// Runs the same sample of queries over a grid of `ef_search` values and compares the neighbours
+/// found with the exact ones.
+///
+/// The queries are vectors of items sampled from the index, and the exact neighbours are found by
+/// scanning every item. The item a query comes from is left out of both the exact and the found
+/// neighbours, it would always be found at a distance of zero. Quantized indexes are evaluated with their quantized distance, the
+/// rescoring step isn't part of the evaluation.
+pub struct Evaluator<'a, D: Distance> {
+    reader: &'a Reader<D>,
+    n_queries: usize,
+    k: usize,
+    ef_searches: Vec<usize>,
+}
+
+impl<'a, D: Distance> Evaluator<'a, D> {
+    /// Creates an evaluator of 100 queries for the 10 nearest neighbours.
+    pub fn new(reader: &'a Reader<D>) -> Self {
+        Evaluator { reader, n_queries: 100, k: 10, ef_searches: DEFAULT_EF_SEARCHES.to_vec() }
+    }
+
+    /// The number of items sampled as queries, defaults to 100.
+    pub fn n_queries(&mut self, n_queries: usize) -> &mut Self {
+        self.n_queries = n_queries;
+        self
+    }
+
+    /// The number of neighbours searched for, the `k` of recall@k, defaults to 10.
+    pub fn k(&mut self, k: usize) -> &mut Self {
+        self.k = k;
+        self
+    }
+
+    /// The `ef_search` values to evaluate, see [`crate::QueryBuilder::ef_search`].
+    pub fn ef_searches(&mut self, ef_searches: Vec<usize>) -> &mut Self {
+        self.ef_searches = ef_searches;
+        self
+    }
+
+    /// Samples the queries with `rng` and returns a report per `ef_search` value, in the same
+    /// order.
+    ///
+    /// The exact search scans the whole index for every query, it can take a while on large
+    /// indexes.
+    pub fn run<R: Rng>(&self, rtxn: &RoTxn, rng: &mut R) -> Result<Vec<EvalReport>> {
+        let sample = self.reader.item_ids().iter().choose_multiple(rng, self.n_queries);
+
+        let mut queries = Vec::with_capacity(sample.len());
+        for item in sample {
+            let Some(vector) = self.reader.item_vector(rtxn, item)? else { continue };
+            let exact = self.reader.exact_nns(rtxn, &vector, self.k + 1)?;
+            let exact = without(item, exact, self.k);
+            queries.push(Query { item, vector, exact });
+        }
+
+        self.ef_searches.iter().map(|&ef_search| self.evaluate(rtxn, ef_search, &queries)).collect()
+    }
+
+    fn evaluate(&self, rtxn: &RoTxn, ef_search: usize, queries: &[Query]) -> Result<EvalReport> {
+        let mut latencies = Vec::with_capacity(queries.len());
+        let mut recall = 0.0;
+        let mut n_distances = 0;
+
+        for Query { item, vector, exact } in queries {
+            let now = Instant::now();
+            let searched = self
+                .reader
+                .nns(self.k + 1)
+                .ef_search(ef_search)
+                .collect_stats(true)
+                .by_vector(rtxn, vector)?;
+            latencies.push(now.elapsed());
+
+            if !exact.is_empty() {
+                let nns = without(*item, searched.nns, self.k);
+                let found = exact.iter().filter(|(id, _)| nns.iter().any(|(n, _)| n == id));
+                recall += found.count() as f64 / exact.len() as f64;
+            }
+            n_distances += searched.stats.map_or(0, |stats| stats.n_distances);
+        }
+
+        latencies.sort_unstable();
+        let n_queries = queries.len().max(1) as f64;
+
+        Ok(EvalReport {
+            ef_search,
+            n_queries: queries.len(),
+            recall: recall / n_queries,
+            p50: percentile(&latencies, 50),
+            p90: percentile(&latencies, 90),
+            p99: percentile(&latencies, 99),
+            avg_distances: n_distances as f64 / n_queries,
+        })
+    }
+}
+
+/// A sampled query along with its exact nearest neighbours.
+struct Query {
+    item: ItemId,
+    vector: Vec<f32>,
+    exact: Vec<(ItemId, f32)>,
+}
+
+/// The result of evaluating an `ef_search` value, see [`Evaluator::run`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalReport {
+    /// The `ef_search` the queries were run with.
+    pub ef_search: usize,
+    /// The number of queries run.
+    pub n_queries: usize,
+    /// The average fraction of the exact `k` nearest neighbours that were found, between 0 and 1.
+    pub recall: f64,
+    /// The median latency of a query.
+    pub p50: Duration,
+    /// The 90th percentile of the latency of a query.
+    pub p90: Duration,
+    /// The 99th percentile of the latency of a query.
+    pub p99: Duration,
+    /// The average number of distances computed by a query.
+    pub avg_distances: f64,
+}
+
+impl fmt::Display for EvalReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let EvalReport { ef_search, n_queries: _, recall, p50, p90, p99, avg_distances } = self;
+        write!(
+            f,
+            "ef_search: {ef_search:>5}, recall: {recall:.4}, p50: {p50:.2?}, p90: {p90:.2?}, \
+             p99: {p99:.2?}, distances: {avg_distances:.1}"
+        )
+    }
+}
+
+/// Removes the `item` a query comes from from its `neighbours` and keeps the `k` first ones.
+fn without(item: ItemId, mut neighbours: Vec<(ItemId, f32)>, k: usize) -> Vec<(ItemId, f32)> {
+    neighbours.retain(|(id, _)| *id != item);
+    neighbours.truncate(k);
+    neighbours
+}
+
+/// Returns the nearest-rank percentile of sorted `values`.
+fn percentile(values: &[Duration], percent: usize) -> Duration {
+    if values.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (values.len() * percent).div_ceil(100).max(1);
+    values[rank - 1]
+}
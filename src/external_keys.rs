@@ -0,0 +1,171 @@
+//! The mapping between the external keys of the items, e.g. UUIDs or 64-bit ids, and their
+//! internal [`ItemId`]s, see [`crate::Writer::add_item_by_key`].
+//!
+//! The key of an item is stored under its [`NodeMode::ExternalKey`] entry. The way back goes
+//! through the [`NodeMode::KeyIndex`] entries, whose `item` is a hash of the external key and
+//! whose value lists the keys having this hash along with their id.
+//!
+//! The next id to allocate to a key is stored in the index metadata. An id is recycled once its
+//! key is deleted, it's kept in a [`NodeMode::FreeId`] entry until it's allocated again.
+
+use std::borrow::Cow;
+use std::mem::size_of;
+
+use byteorder::{BigEndian, ByteOrder};
+use heed::byteorder::BE;
+use heed::types::{Bytes, DecodeIgnore, Unit, U32};
+use heed::{BoxedError, RoTxn, RwTxn};
+
+use crate::internals::KeyCodec;
+use crate::key::{Prefix, PrefixCodec};
+use crate::{Database, Distance, Error, ItemId, Key, Result};
+
+/// Returns the id mapped to an external key.
+pub(crate) fn get_id<D: Distance>(
+    database: Database<D>,
+    index: u16,
+    rtxn: &RoTxn,
+    key: &[u8],
+) -> Result<Option<ItemId>> {
+    let entries = database
+        .remap_data_type::<KeyIndexCodec>()
+        .get(rtxn, &key_index(index, key))?
+        .unwrap_or_default();
+    Ok(entries.into_iter().find(|(k, _)| k == key).map(|(_, item)| item))
+}
+
+/// Returns the external key mapped to an item.
+pub(crate) fn get_key<D: Distance>(
+    database: Database<D>,
+    index: u16,
+    rtxn: &RoTxn,
+    item: ItemId,
+) -> Result<Option<Vec<u8>>> {
+    let keys = database.remap_data_type::<Bytes>();
+    Ok(keys.get(rtxn, &Key::external_key(index, item))?.map(<[u8]>::to_vec))
+}
+
+/// Returns a new id for a key, the ids of the deleted keys are recycled first. The ids used by
+/// the items added without a key are skipped.
+pub(crate) fn allocate<D: Distance>(
+    database: Database<D>,
+    index: u16,
+    wtxn: &mut RwTxn,
+) -> Result<ItemId> {
+    let items = database.remap_data_type::<DecodeIgnore>();
+    let free_ids = database.remap_types::<PrefixCodec, DecodeIgnore>();
+    let lowest_free_id = |rtxn: &RoTxn| -> Result<Option<ItemId>> {
+        let mut iter =
+            free_ids.prefix_iter(rtxn, &Prefix::free_ids(index))?.remap_key_type::<KeyCodec>();
+        Ok(iter.next().transpose()?.map(|(key, ())| key.node.item))
+    };
+    while let Some(item) = lowest_free_id(wtxn)? {
+        database.delete(wtxn, &Key::free_id(index, item))?;
+        // an item may have been added without a key under this id since it was freed
+        if items.get(wtxn, &Key::item(index, item))?.is_none() {
+            return Ok(item);
+        }
+    }
+
+    let next_key_id = database.remap_data_type::<U32<BE>>();
+    let mut item = next_key_id.get(wtxn, &Key::next_key_id(index))?.unwrap_or(0);
+    while items.get(wtxn, &Key::item(index, item))?.is_some() {
+        item = item.checked_add(1).ok_or(Error::DatabaseFull)?;
+    }
+    let next = item.checked_add(1).ok_or(Error::DatabaseFull)?;
+    next_key_id.put(wtxn, &Key::next_key_id(index), &next)?;
+    Ok(item)
+}
+
+/// Maps an external key to an item, the previous mappings of both are removed.
+pub(crate) fn put<D: Distance>(
+    database: Database<D>,
+    index: u16,
+    wtxn: &mut RwTxn,
+    key: &[u8],
+    item: ItemId,
+) -> Result<()> {
+    if let Some(previous) = get_id(database, index, wtxn, key)? {
+        delete(database, index, wtxn, previous)?;
+    }
+    delete(database, index, wtxn, item)?;
+    database.delete(wtxn, &Key::free_id(index, item))?;
+
+    let key_index_db = database.remap_data_type::<KeyIndexCodec>();
+    let mut entries = key_index_db.get(wtxn, &key_index(index, key))?.unwrap_or_default();
+    entries.push((key.to_vec(), item));
+    key_index_db.put(wtxn, &key_index(index, key), &entries)?;
+    database.remap_data_type::<Bytes>().put(wtxn, &Key::external_key(index, item), key)?;
+
+    Ok(())
+}
+
+/// Removes the external key of an item and frees its id, returns the key if there was one.
+pub(crate) fn delete<D: Distance>(
+    database: Database<D>,
+    index: u16,
+    wtxn: &mut RwTxn,
+    item: ItemId,
+) -> Result<Option<Vec<u8>>> {
+    let Some(key) = get_key(database, index, wtxn, item)? else { return Ok(None) };
+    database.delete(wtxn, &Key::external_key(index, item))?;
+
+    let key_index_db = database.remap_data_type::<KeyIndexCodec>();
+    let mut entries = key_index_db.get(wtxn, &key_index(index, &key))?.unwrap_or_default();
+    entries.retain(|(k, _)| *k != key);
+    if entries.is_empty() {
+        key_index_db.delete(wtxn, &key_index(index, &key))?;
+    } else {
+        key_index_db.put(wtxn, &key_index(index, &key), &entries)?;
+    }
+
+    database.remap_data_type::<Unit>().put(wtxn, &Key::free_id(index, item), &())?;
+
+    Ok(Some(key))
+}
+
+fn key_index(index: u16, key: &[u8]) -> Key {
+    Key::key_index(index, crc32fast::hash(key))
+}
+
+/// The external keys sharing a hash along with their ids.
+pub(crate) enum KeyIndexCodec {}
+
+impl<'a> heed::BytesEncode<'a> for KeyIndexCodec {
+    type EItem = Vec<(Vec<u8>, ItemId)>;
+
+    fn bytes_encode(item: &'a Self::EItem) -> Result<Cow<'a, [u8]>, BoxedError> {
+        let mut output = Vec::new();
+        output.extend_from_slice(&u32::try_from(item.len())?.to_be_bytes());
+        for (key, id) in item {
+            output.extend_from_slice(&u32::try_from(key.len())?.to_be_bytes());
+            output.extend_from_slice(key);
+            output.extend_from_slice(&id.to_be_bytes());
+        }
+        Ok(Cow::Owned(output))
+    }
+}
+
+impl heed::BytesDecode<'_> for KeyIndexCodec {
+    type DItem = Vec<(Vec<u8>, ItemId)>;
+
+    fn bytes_decode(mut bytes: &[u8]) -> Result<Self::DItem, BoxedError> {
+        let mut take = |n: usize| -> Result<&[u8], BoxedError> {
+            if bytes.len() < n {
+                return Err(format!("expected {n} bytes but only {} remain", bytes.len()).into());
+            }
+            let (head, tail) = bytes.split_at(n);
+            bytes = tail;
+            Ok(head)
+        };
+
+        let len = BigEndian::read_u32(take(size_of::<u32>())?);
+        (0..len)
+            .map(|_| {
+                let key_len = BigEndian::read_u32(take(size_of::<u32>())?);
+                let key = take(key_len as usize)?.to_vec();
+                Ok((key, BigEndian::read_u32(take(size_of::<ItemId>())?)))
+            })
+            .collect()
+    }
+}
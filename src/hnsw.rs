@@ -15,7 +15,7 @@ use rand::prelude::Distribution;
 use rand::Rng;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use roaring::RoaringBitmap;
-use tinyvec::{array_vec, ArrayVec};
+use tinyvec::{tiny_vec, TinyVec};
 use tracing::{debug, instrument};
 
 use crate::key::Key;
@@ -29,12 +29,15 @@ use crate::{Database, Distance, Error, ItemId, Result, CANCELLATION_PROBING};
 
 pub(crate) type ScoredLink = (OrderedFloat, ItemId);
 
-/// State with stack-allocated graph edges
-pub struct NodeState<const M: usize> {
-    links: ArrayVec<[ScoredLink; M]>,
+/// The number of graph edges a [`NodeState`] stores inline before spilling to the heap.
+const INLINE_LINKS: usize = 32;
+
+/// State with graph edges stored inline up to [`INLINE_LINKS`], and heap-allocated beyond
+pub struct NodeState {
+    links: TinyVec<[ScoredLink; INLINE_LINKS]>,
 }
 
-impl<const M: usize> Debug for NodeState<M> {
+impl Debug for NodeState {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // from [crate::unaligned_vector]
         struct Number(f32);
@@ -54,22 +57,26 @@ impl<const M: usize> Debug for NodeState<M> {
     }
 }
 
-pub struct HnswBuilder<'a, D, const M: usize, const M0: usize> {
+pub struct HnswBuilder<'a, D> {
     assign_probas: Vec<f32>,
+    m: usize,
+    m0: usize,
     ef_construction: usize,
     alpha: f32,
     cancel: &'a (dyn Fn() -> bool + 'a + Sync + Send),
     pub max_level: usize,
     pub entry_points: Vec<ItemId>,
-    pub layers: Vec<HashMap<ItemId, NodeState<M0>>>,
+    pub layers: Vec<HashMap<ItemId, NodeState>>,
     distance: PhantomData<D>,
 }
 
-impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0> {
+impl<'a, D: Distance> HnswBuilder<'a, D> {
     pub fn new<P: steppe::Progress>(opts: &'a BuildOption<P>) -> Self {
-        let assign_probas = Self::get_default_probas();
+        let assign_probas = Self::get_default_probas(opts.m);
         Self {
             assign_probas,
+            m: opts.m,
+            m0: opts.m0,
             ef_construction: opts.ef_construction,
             alpha: opts.alpha,
             cancel: &opts.cancel,
@@ -91,9 +98,9 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
     }
 
     /// build quantiles from an x ~ exp(1/ln(m))
-    fn get_default_probas() -> Vec<f32> {
-        let mut assign_probas = Vec::with_capacity(M);
-        let level_factor = 1.0 / (M as f32 + f32::EPSILON).ln();
+    fn get_default_probas(m: usize) -> Vec<f32> {
+        let mut assign_probas = Vec::with_capacity(m);
+        let level_factor = 1.0 / (m as f32 + f32::EPSILON).ln();
         let mut level = 0;
         loop {
             // P(L<x<L+1) = P(x<L+1) - P(x<L)
@@ -128,13 +135,12 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
         wtxn: &mut RwTxn,
         rng: &mut R,
         options: &BuildOption<P>,
-    ) -> Result<BuildStats<D>>
+        build_stats: &mut BuildStats,
+    ) -> Result<()>
     where
         R: Rng + ?Sized,
         P: steppe::Progress,
     {
-        let mut build_stats = BuildStats::new();
-
         let lmdb = FrozenReader::new(wtxn, index, database)?;
 
         // Generate a random level for each point
@@ -154,20 +160,22 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
             to_delete,
             &lmdb,
             options,
+            build_stats,
         )?;
         to_insert |= ok_eps;
 
         let level_groups: Vec<_> = levels.chunk_by(|(_, la), (_, lb)| la == lb).collect();
 
         // Insert layers L...0 multi-threaded
-        options.progress.update(HannoyBuild::BuildingTheGraph);
+        build_stats.step(&options.progress, HannoyBuild::BuildingTheGraph);
         let (item_ctr, insert_step) = AtomicInsertItemsStep::new(to_insert.len());
         options.progress.update(insert_step);
         let cancel_index = AtomicUsize::new(0);
 
         level_groups.iter().for_each(|grp| {
-            build_stats.layer_dist.insert(grp[0].1, grp.len());
+            build_stats.set_layer_len(grp[0].1, grp.len());
         });
+        let build_stats = &*build_stats;
 
         level_groups.into_iter().try_for_each(|grp| {
             grp.into_par_iter().try_for_each(|&(item_id, lvl)| {
@@ -176,7 +184,7 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
                 {
                     Err(Error::BuildCancelled)
                 } else {
-                    self.insert(item_id, lvl, &lmdb, &build_stats)?;
+                    self.insert(item_id, lvl, &lmdb, build_stats)?;
                     item_ctr.fetch_add(1, Relaxed);
                     Ok(())
                 }
@@ -184,12 +192,193 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
             Ok(()) as Result<(), Error>
         })?;
 
-        self.fill_gaps_from_deleted(&lmdb, to_delete, options)?;
+        self.fill_gaps_from_deleted(&lmdb, to_delete, options, build_stats)?;
 
         drop(lmdb);
 
+        self.write_links(database, index, wtxn, options, build_stats)
+    }
+
+    /// Rewires the poorly connected nodes of an already built graph without touching the others.
+    ///
+    /// A node is repaired on a layer when few other nodes link to it, when it can't be reached
+    /// from the entry points in layer 0, or when it is an entry point missing from that layer.
+    /// Returns the repaired items.
+    pub fn repair<P>(
+        &mut self,
+        items: &RoaringBitmap,
+        database: Database<D>,
+        index: u16,
+        wtxn: &mut RwTxn,
+        options: &BuildOption<P>,
+    ) -> Result<RoaringBitmap>
+    where
+        P: steppe::Progress,
+    {
+        let mut build_stats = BuildStats::new();
+
+        let lmdb = FrozenReader::new(wtxn, index, database)?;
+
+        build_stats.step(&options.progress, HannoyBuild::FindingTheWeakNodes);
+        let weak = self.find_weak_nodes(items, &lmdb)?;
+        let to_repair = weak.iter().fold(RoaringBitmap::new(), |acc, w| acc | w);
+        debug!("Repairing {} weakly connected items", to_repair.len());
+
+        for _ in 0..=self.max_level {
+            self.layers.push(HashMap::new());
+        }
+
+        build_stats.step(&options.progress, HannoyBuild::RepairingTheGraph);
+        let (item_ctr, insert_step) = AtomicInsertItemsStep::new(to_repair.len());
+        options.progress.update(insert_step);
+        let cancel_index = AtomicUsize::new(0);
+
+        to_repair.iter().collect::<Vec<_>>().into_par_iter().try_for_each(|item_id| {
+            if cancel_index.fetch_add(1, Relaxed).is_multiple_of(CANCELLATION_PROBING)
+                && (self.cancel)()
+            {
+                return Err(Error::BuildCancelled);
+            }
+            self.relink(item_id, &weak, &lmdb, &build_stats)?;
+            item_ctr.fetch_add(1, Relaxed);
+            Ok(())
+        })?;
+
+        // merges the new links with the ones on disk
+        self.fill_gaps_from_deleted(&lmdb, &RoaringBitmap::new(), options, &build_stats)?;
+
+        drop(lmdb);
+
+        self.write_links(database, index, wtxn, options, &build_stats)?;
+        build_stats.finish();
+        debug!("{build_stats:?}");
+
+        Ok(to_repair)
+    }
+
+    /// Returns, for each layer, the items that must be relinked on it.
+    fn find_weak_nodes(
+        &self,
+        items: &RoaringBitmap,
+        lmdb: &FrozenReader<'_, D>,
+    ) -> Result<Vec<RoaringBitmap>> {
+        let mut nodes = vec![RoaringBitmap::new(); self.max_level + 1];
+        let mut in_degrees = vec![std::collections::HashMap::<ItemId, usize>::new(); nodes.len()];
+
+        for result in lmdb.iter_links()? {
+            let ((item_id, lvl), links) = result?;
+            let Some(layer) = nodes.get_mut(lvl as usize) else { continue };
+            layer.insert(item_id);
+            for other in links.iter() {
+                *in_degrees[lvl as usize].entry(other).or_default() += 1;
+            }
+        }
+
+        // items without any links are in layer 0 too
+        nodes[0] |= items;
+
+        let mut weak: Vec<_> = nodes
+            .iter()
+            .zip(&in_degrees)
+            .enumerate()
+            .map(|(lvl, (nodes, in_degrees))| {
+                let min_in_degree = self.min_in_degree(lvl);
+                nodes
+                    .iter()
+                    .filter(|id| in_degrees.get(id).copied().unwrap_or_default() < min_in_degree)
+                    .collect::<RoaringBitmap>()
+            })
+            .collect();
+
+        // entry points must be present on every layer
+        for &ep in &self.entry_points {
+            for (lvl, nodes) in nodes.iter().enumerate() {
+                if !nodes.contains(ep) {
+                    weak[lvl].insert(ep);
+                }
+            }
+        }
+
+        // the components of layer 0 that the entry points can't reach
+        let mut reached = RoaringBitmap::from_iter(self.entry_points.iter().copied());
+        let mut to_visit = self.entry_points.clone();
+        while let Some(item_id) = to_visit.pop() {
+            let Ok(Links { links }) = lmdb.links(item_id, 0) else { continue };
+            for other in links.iter() {
+                if reached.insert(other) {
+                    to_visit.push(other);
+                }
+            }
+        }
+        weak[0] |= &nodes[0] - reached;
+
+        for w in &mut weak {
+            *w &= items;
+        }
+
+        Ok(weak)
+    }
+
+    /// Searches the neighbours of an already linked item again on the layers it is weak on, and
+    /// links it to them in both directions.
+    fn relink(
+        &self,
+        query: ItemId,
+        weak: &[RoaringBitmap],
+        lmdb: &FrozenReader<'_, D>,
+        build_stats: &BuildStats,
+    ) -> Result<()> {
+        let mut eps = Vec::from_iter(self.entry_points.clone());
+
+        let q = lmdb.item(query)?;
+
+        for lvl in (0..=self.max_level).rev() {
+            if !weak[lvl].contains(query) {
+                // Greedy search with: ef = 1
+                let neighbours = self.walk_layer(&q, &eps, lvl, 1, lmdb, build_stats)?;
+                let closest =
+                    neighbours.peek_min().map(|(_, n)| *n).expect("No neighbor was found");
+                eps = vec![closest];
+                continue;
+            }
+
+            let mut neighbours =
+                self.walk_layer(&q, &eps, lvl, self.ef_construction, lmdb, build_stats)?.into_vec();
+            neighbours.retain(|&(_, n)| n != query);
+
+            let pruned = self.robust_prune(neighbours, lvl, self.alpha, lmdb)?;
+            if pruned.is_empty() {
+                continue;
+            }
+
+            eps.clear();
+            for (dist, n) in pruned {
+                // add links in both directions
+                self.add_link(query, (dist, n), lvl, lmdb)?;
+                self.add_link(n, (dist, query), lvl, lmdb)?;
+                eps.push(n);
+
+                build_stats.incr_link_count(2);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the links of every node touched in memory to LMDB.
+    fn write_links<P>(
+        &self,
+        database: Database<D>,
+        index: u16,
+        wtxn: &mut RwTxn,
+        options: &BuildOption<P>,
+        build_stats: &BuildStats,
+    ) -> Result<()>
+    where
+        P: steppe::Progress,
+    {
         // Single-threaded write to lmdb
-        options.progress.update(HannoyBuild::WritingTheItems);
+        build_stats.step(&options.progress, HannoyBuild::WritingTheItems);
         let mut cancellation_index = 0;
 
         for (lvl, map) in self.layers.iter().enumerate() {
@@ -212,13 +401,13 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
             }
         }
 
-        Ok(build_stats)
+        Ok(())
     }
 
     /// This function resolves several nasty edge cases that can occur, namely : deleted
     /// or partially deleted entrypoints, new indexed points assigned to higher layers, ensuring
     /// entry points are present on all layers before build
-    #[instrument(level = "trace", skip(self, options, lmdb, levels))]
+    #[instrument(level = "trace", skip(self, options, lmdb, levels, build_stats))]
     fn prepare_levels_and_entry_points<P>(
         &mut self,
         levels: &mut Vec<(u32, usize)>,
@@ -226,12 +415,13 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
         to_delete: &RoaringBitmap,
         lmdb: &FrozenReader<D>,
         options: &BuildOption<P>,
+        build_stats: &BuildStats,
     ) -> Result<RoaringBitmap>
     where
         P: steppe::Progress,
     {
         debug!("Resolving entry points in (maybe incremental) build");
-        options.progress.update(HannoyBuild::ResolveGraphEntryPoints);
+        build_stats.step(&options.progress, HannoyBuild::ResolveGraphEntryPoints);
 
         let old_eps = RoaringBitmap::from_iter(self.entry_points.iter());
         let mut new_eps = &old_eps - to_delete;
@@ -293,7 +483,7 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
         query: ItemId,
         level: usize,
         lmdb: &FrozenReader<'_, D>,
-        build_stats: &BuildStats<D>,
+        build_stats: &BuildStats,
     ) -> Result<()> {
         let mut eps = Vec::from_iter(self.entry_points.clone());
 
@@ -336,12 +526,13 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
         lmdb: &FrozenReader<'_, D>,
         to_delete: &RoaringBitmap,
         options: &BuildOption<P>,
+        build_stats: &BuildStats,
     ) -> Result<()>
     where
         P: steppe::Progress,
     {
         debug!("Repairing connections to deleted items, and linking old and new graphs");
-        options.progress.update(HannoyBuild::PatchOldNewDeletedLinks);
+        build_stats.step(&options.progress, HannoyBuild::PatchOldNewDeletedLinks);
 
         let links_in_db: Vec<_> = lmdb
             .iter_links()?
@@ -374,6 +565,7 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
                 return Ok(());
             }
             let del_subset = &links & to_delete;
+            build_stats.incr_links_patched(del_subset.len() as usize);
 
             // This is safe because we resized layers above.
             let map_guard = self.layers[lvl].pin();
@@ -388,14 +580,14 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
             debug_assert!(bitmap.is_disjoint(to_delete));
 
             //  Case 1: Union of [on_disk, current_build, deleted_extension] is small enough
-            let thresh = if lvl == 0 { M0 } else { M };
+            let thresh = self.max_links(lvl);
             if (bitmap.len() as usize) + new_links.len() <= thresh {
                 // NOTE: pairwise distance is no longer relevant
                 let mut entries: Vec<_> =
                     bitmap.iter().map(|node_id| (OrderedFloat(0.0f32), node_id)).collect();
                 entries.extend(new_links);
 
-                let _ = map_guard.insert(id, NodeState { links: ArrayVec::from_iter(entries) });
+                let _ = map_guard.insert(id, NodeState { links: TinyVec::from_iter(entries) });
                 return Ok(());
             }
 
@@ -407,7 +599,7 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
                 new_links.push((OrderedFloat(dist), other));
             }
             let pruned = self.robust_prune(new_links, lvl, self.alpha, lmdb)?;
-            let _ = map_guard.insert(id, NodeState { links: ArrayVec::from_iter(pruned) });
+            let _ = map_guard.insert(id, NodeState { links: TinyVec::from_iter(pruned) });
             Ok(())
         })?;
 
@@ -419,7 +611,7 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
     fn add_in_layers_below(&self, item_id: ItemId, level: usize) {
         for level in 0..=level {
             let Some(map) = self.layers.get(level) else { break };
-            map.pin().get_or_insert(item_id, NodeState { links: array_vec![] });
+            map.pin().get_or_insert(item_id, NodeState { links: tiny_vec![] });
         }
     }
 
@@ -430,7 +622,7 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
         lmdb: &FrozenReader<'_, D>,
         item_id: ItemId,
         level: usize,
-        build_stats: &BuildStats<D>,
+        build_stats: &BuildStats,
     ) -> Result<Vec<ItemId>> {
         let mut res = Vec::new();
 
@@ -448,7 +640,7 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
             Some(node_state) => res.extend(node_state.links.iter().map(|(_, i)| *i)),
             None => {
                 // lazily add this entry so he can get updated later
-                pinned.insert(item_id, NodeState { links: array_vec![] });
+                pinned.insert(item_id, NodeState { links: tiny_vec![] });
             }
         }
 
@@ -464,7 +656,7 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
         level: usize,
         ef: usize,
         lmdb: &FrozenReader<'_, D>,
-        build_stats: &BuildStats<D>,
+        build_stats: &BuildStats,
     ) -> Result<MinMaxHeap<ScoredLink>> {
         let mut candidates = BinaryHeap::new();
         let mut res = MinMaxHeap::with_capacity(ef);
@@ -535,9 +727,9 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
         let map_guard = map.pin();
 
         // 'pure' links update function
-        let _add_link = |node_state: &NodeState<M0>| {
-            let mut links = node_state.links;
-            let cap = if level == 0 { M0 } else { M };
+        let _add_link = |node_state: &NodeState| {
+            let mut links = node_state.links.clone();
+            let cap = self.max_links(level);
 
             if links.len() < cap {
                 links.push(q);
@@ -546,15 +738,14 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
 
             let new_links = self
                 .robust_prune(links.to_vec(), level, self.alpha, lmdb)
-                .map(ArrayVec::from_iter)
-                .unwrap_or_else(|_| node_state.links);
+                .map(TinyVec::from_iter)
+                .unwrap_or_else(|_| node_state.links.clone());
 
             NodeState { links: new_links }
         };
 
-        map_guard.update_or_insert_with(p, _add_link, || NodeState {
-            links: array_vec!([ScoredLink; M0] => q),
-        });
+        map_guard
+            .update_or_insert_with(p, _add_link, || NodeState { links: TinyVec::from_iter([q]) });
 
         Ok(())
     }
@@ -569,7 +760,7 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
         alpha: f32,
         lmdb: &FrozenReader<'_, D>,
     ) -> Result<Vec<ScoredLink>> {
-        let cap = if level == 0 { M0 } else { M };
+        let cap = self.max_links(level);
         candidates.sort_by(|a, b| b.cmp(a));
         let mut selected: Vec<ScoredLink> = Vec::with_capacity(cap);
 
@@ -595,6 +786,20 @@ impl<'a, D: Distance, const M: usize, const M0: usize> HnswBuilder<'a, D, M, M0>
 
         Ok(selected)
     }
+
+    /// The in-degree under which a node of the given layer is considered weakly connected.
+    fn min_in_degree(&self, level: usize) -> usize {
+        (self.max_links(level) / 8).max(1)
+    }
+
+    /// The maximum number of links of a node on the given layer.
+    fn max_links(&self, level: usize) -> usize {
+        if level == 0 {
+            self.m0
+        } else {
+            self.m
+        }
+    }
 }
 
 #[cfg(test)]
@@ -613,8 +818,8 @@ mod tests {
     // should be like: https://www.pinecone.io/learn/series/faiss/hnsw/
     fn check_distribution_shape() {
         let mut rng = StdRng::seed_from_u64(42);
-        let build_option = BuildOption::default();
-        let mut hnsw = HnswBuilder::<Cosine, 32, 48>::new(&build_option);
+        let build_option = BuildOption { m: 32, m0: 48, ..BuildOption::default() };
+        let mut hnsw = HnswBuilder::<Cosine>::new(&build_option);
 
         let mut bins = HashMap::new();
         (0..10000).for_each(|_| {
@@ -1,15 +1,19 @@
 use heed::RoTxn;
 
+use crate::calibration::Calibration;
 use crate::distance::Distance;
 use crate::internals::KeyCodec;
-use crate::key::{Prefix, PrefixCodec};
-use crate::node::Item;
+use crate::key::{Key, Prefix, PrefixCodec};
+use crate::node::FullPrecisionCodec;
 use crate::{Database, ItemId, Node, NodeCodec, Result};
 
 // used by the reader
 pub struct ItemIter<'t, D: Distance> {
     pub inner: heed::RoPrefix<'t, KeyCodec, NodeCodec<D>>,
     dimensions: usize,
+    calibration: Option<Calibration>,
+    /// Returns the full-precision vectors of the items that have one, instead of decoding them.
+    full_precision: Option<(heed::Database<KeyCodec, FullPrecisionCodec>, &'t RoTxn<'t>)>,
 }
 
 impl<'t, D: Distance> ItemIter<'t, D> {
@@ -17,6 +21,7 @@ impl<'t, D: Distance> ItemIter<'t, D> {
         database: Database<D>,
         index: u16,
         dimensions: usize,
+        calibration: Option<Calibration>,
         rtxn: &'t RoTxn,
     ) -> heed::Result<Self> {
         Ok(ItemIter {
@@ -25,8 +30,24 @@ impl<'t, D: Distance> ItemIter<'t, D> {
                 .prefix_iter(rtxn, &Prefix::item(index))?
                 .remap_key_type::<KeyCodec>(),
             dimensions,
+            calibration,
+            full_precision: None,
         })
     }
+
+    /// Same as [`Self::new`] but returns the full-precision vectors of the items when they
+    /// are stored.
+    pub fn with_full_precision(
+        database: Database<D>,
+        index: u16,
+        dimensions: usize,
+        calibration: Option<Calibration>,
+        rtxn: &'t RoTxn,
+    ) -> heed::Result<Self> {
+        let mut iter = Self::new(database, index, dimensions, calibration, rtxn)?;
+        iter.full_precision = Some((database.remap_data_type(), rtxn));
+        Ok(iter)
+    }
 }
 
 impl<D: Distance> Iterator for ItemIter<'_, D> {
@@ -35,8 +56,16 @@ impl<D: Distance> Iterator for ItemIter<'_, D> {
     fn next(&mut self) -> Option<Self::Item> {
         match self.inner.next() {
             Some(Ok((key, node))) => match node {
-                Node::Item(Item { header: _, vector }) => {
-                    let mut vector = vector.to_vec();
+                Node::Item(item) => {
+                    if let Some((database, rtxn)) = self.full_precision {
+                        let key = Key::full_precision(key.index, key.node.item);
+                        match database.get(rtxn, &key) {
+                            Ok(Some(vector)) => return Some(Ok((key.node.item, vector.to_vec()))),
+                            Ok(None) => (),
+                            Err(e) => return Some(Err(e.into())),
+                        }
+                    }
+                    let mut vector = D::decode(&item, self.calibration.as_ref());
                     if vector.len() != self.dimensions {
                         // quantized codecs pad to 8-bytes so we truncate to recover len
                         vector.truncate(self.dimensions);
@@ -14,7 +14,18 @@ use crate::{NodeId, NodeMode};
 ///  - `Item`: we're looking at an `Item` node.
 ///  - `Links`: we're looking at the `Links` bitmap of neighbours for a node
 ///  - `Updated`: The list of items that has been updated since the last build of the database.
+///  - `FullPrecision`: The f32 vector of an item, kept to rescore the candidates of quantized indexes.
+///  - `Attributes`: The tags and numbers of an item, see [`crate::Attributes`].
+///  - `Postings`: The items having an attribute value, the `item` is a hash of the tag or the high
+///    bits of the number and the `layer` is the id of the field.
+///  - `ExternalKey`: The external key of an item added with [`crate::Writer::add_item_by_key`].
+///  - `KeyIndex`: The ids of the external keys, the `item` is a hash of the key.
+///  - `FreeId`: An id freed by a deleted external key, see [`crate::Writer::del_item_by_key`].
 ///  - `Metadata`: There is only one item at `0` that contains the header required to read the index.
+///    The version is stored at `1`, the optional scalar quantization calibration at `2`, the
+///    names of the attribute fields at `3`, the next id to allocate to an external key at `4`,
+///    the parameters the graph was built with at `5` and the biggest norm of the
+///    [`DotProduct`](crate::distances::DotProduct) items at `6`.
 #[derive(Debug, Copy, Clone)]
 pub struct Key {
     /// The prefix specified by the user.
@@ -35,6 +46,26 @@ impl Key {
         Self::new(index, NodeId::version())
     }
 
+    pub const fn calibration(index: u16) -> Self {
+        Self::new(index, NodeId::calibration())
+    }
+
+    pub const fn attribute_fields(index: u16) -> Self {
+        Self::new(index, NodeId::attribute_fields())
+    }
+
+    pub const fn next_key_id(index: u16) -> Self {
+        Self::new(index, NodeId::next_key_id())
+    }
+
+    pub const fn build_params(index: u16) -> Self {
+        Self::new(index, NodeId::build_params())
+    }
+
+    pub const fn max_norm(index: u16) -> Self {
+        Self::new(index, NodeId::max_norm())
+    }
+
     pub const fn updated(index: u16, item: u32) -> Self {
         Self::new(index, NodeId::updated(item))
     }
@@ -46,6 +77,30 @@ impl Key {
     pub const fn links(index: u16, item: u32, layer: u8) -> Self {
         Self::new(index, NodeId::links(item, layer))
     }
+
+    pub const fn full_precision(index: u16, item: u32) -> Self {
+        Self::new(index, NodeId::full_precision(item))
+    }
+
+    pub const fn attributes(index: u16, item: u32) -> Self {
+        Self::new(index, NodeId::attributes(item))
+    }
+
+    pub const fn postings(index: u16, bucket: u32, field: u8) -> Self {
+        Self::new(index, NodeId::postings(bucket, field))
+    }
+
+    pub const fn external_key(index: u16, item: u32) -> Self {
+        Self::new(index, NodeId::external_key(item))
+    }
+
+    pub const fn key_index(index: u16, hash: u32) -> Self {
+        Self::new(index, NodeId::key_index(hash))
+    }
+
+    pub const fn free_id(index: u16, item: u32) -> Self {
+        Self::new(index, NodeId::free_id(item))
+    }
 }
 
 /// The heed codec used internally to encode/decoding the internal key type.
@@ -69,6 +124,10 @@ impl heed::BytesDecode<'_> for KeyCodec {
     type DItem = Key;
 
     fn bytes_decode(bytes: &[u8]) -> Result<Self::DItem, BoxedError> {
+        if bytes.len() != size_of::<u64>() {
+            return Err(format!("invalid key of {} bytes", bytes.len()).into());
+        }
+
         let prefix = BigEndian::read_u16(bytes);
         let bytes = &bytes[size_of::<u16>()..];
         let mode = bytes[0].try_into()?;
@@ -106,6 +165,10 @@ impl Prefix {
     pub const fn updated(index: u16) -> Self {
         Self { index, mode: Some(NodeMode::Updated) }
     }
+
+    pub const fn free_ids(index: u16) -> Self {
+        Self { index, mode: Some(NodeMode::FreeId) }
+    }
 }
 
 pub enum PrefixCodec {}
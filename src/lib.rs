@@ -14,7 +14,7 @@
 //! # Examples
 //!
 //! Open an LMDB database, store some vectors in it and query the nearest item from some query vector. This is the most
-//! trivial way to use hannoy and it's fairly easy. Just do not forget to [`HannoyBuilder::build<M0,M>`] and [`heed::RwTxn::commit`]
+//! trivial way to use hannoy and it's fairly easy. Just do not forget to [`HannoyBuilder::build`] and [`heed::RwTxn::commit`]
 //! when you are done inserting your items.
 //!
 //! ```rust
@@ -46,7 +46,7 @@
 //!     let mut rng = StdRng::seed_from_u64(42);
 //!
 //!     let mut builder = writer.builder(&mut rng);
-//!     builder.ef_construction(100).build::<16,32>(&mut wtxn)?;
+//!     builder.ef_construction(100).m(16).m0(32).build(&mut wtxn)?;
 //!     wtxn.commit()?;
 //!
 //!     // search hnsw using a new lmdb read transaction
@@ -69,12 +69,18 @@
 )]
 #![warn(clippy::todo)]
 
+mod attributes;
+mod calibration;
+mod check;
 mod distance;
+mod dump;
 mod error;
+mod external_keys;
 mod hnsw;
 mod item_iter;
 mod key;
 mod metadata;
+mod multi_reader;
 mod node;
 mod node_id;
 mod parallel;
@@ -93,23 +99,35 @@ mod tests;
 mod ordered_float;
 mod unaligned_vector;
 
+pub mod eval;
 #[cfg(feature = "python")]
 pub mod python;
+pub mod vecs;
 
+pub use attributes::{AttributeValue, Attributes, Filter};
+pub use check::{fsck, CheckReport, UndecodableNode};
 pub use distance::Distance;
 pub use error::Error;
 use key::{Key, Prefix, PrefixCodec};
-use metadata::{Metadata, MetadataCodec};
+pub use metadata::{list_indexes, BuildParams};
+use metadata::{BuildParamsCodec, Metadata, MetadataCodec};
+pub use multi_reader::{MultiQueryBuilder, MultiReader};
 use node::{Node, NodeCodec};
 use node_id::{NodeId, NodeMode};
-pub use reader::{QueryBuilder, Reader, Searched};
+pub use progress::HannoyBuild;
+pub use reader::{NnsIter, QueryBuilder, Reader, Searched};
 pub use roaring::RoaringBitmapCodec;
+pub use stats::{BuildStats, GraphStats, LayerStats, SearchStats};
 pub use writer::{HannoyBuilder, Writer};
 
 /// The set of types used by the [`Distance`] trait.
 pub mod internals {
+    pub use crate::calibration::Calibration;
     pub use crate::distance::{
-        NodeHeaderBinaryQuantizedCosine, NodeHeaderCosine, NodeHeaderEuclidean,
+        NodeHeaderBinaryQuantizedCosine, NodeHeaderCosine, NodeHeaderCosineBf16,
+        NodeHeaderCosineF16, NodeHeaderDotProduct, NodeHeaderEuclidean, NodeHeaderEuclideanBf16,
+        NodeHeaderEuclideanF16, NodeHeaderScalarQuantizedCosine,
+        NodeHeaderScalarQuantizedEuclidean,
     };
     pub use crate::key::KeyCodec;
     pub use crate::node::{Item, NodeCodec};
@@ -120,7 +138,8 @@ pub mod internals {
 pub mod distances {
     pub use crate::distance::{
         BinaryQuantizedCosine, BinaryQuantizedEuclidean, BinaryQuantizedManhattan, Cosine,
-        Euclidean, Hamming, Manhattan,
+        CosineBf16, CosineF16, DotProduct, Euclidean, EuclideanBf16, EuclideanF16, Hamming,
+        Manhattan, ScalarQuantizedCosine, ScalarQuantizedEuclidean,
     };
 }
 
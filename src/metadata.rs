@@ -1,12 +1,15 @@
 use std::borrow::Cow;
 use std::ffi::CStr;
+use std::fmt;
 use std::mem::size_of;
 
 use ::roaring::RoaringBitmap;
 use byteorder::{BigEndian, ByteOrder};
-use heed::BoxedError;
+use heed::types::DecodeIgnore;
+use heed::{BoxedError, RoTxn};
 
 use crate::node::ItemIds;
+use crate::{Database, Distance, Key};
 
 #[derive(Debug)]
 pub struct Metadata<'a> {
@@ -17,6 +20,36 @@ pub struct Metadata<'a> {
     pub max_level: u8,
 }
 
+/// The parameters used to build the graph of an index.
+///
+/// They are stored under their own key next to the index metadata and can be retrieved with
+/// [`crate::Reader::build_params`]. Indexes built by older versions of hannoy do not have any.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BuildParams {
+    /// The maximum number of links per node in the layers above the first one.
+    pub m: usize,
+    /// The maximum number of links per node in the first layer.
+    pub m0: usize,
+    /// The size of the candidate list used while inserting items.
+    pub ef_construction: usize,
+    /// The pruning factor used when selecting the links of a node.
+    pub alpha: f32,
+}
+
+impl BuildParams {
+    /// Returns `true` if both parameters produce links of the same degree.
+    pub(crate) fn same_degree(&self, other: &BuildParams) -> bool {
+        self.m == other.m && self.m0 == other.m0
+    }
+}
+
+impl fmt::Display for BuildParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let BuildParams { m, m0, ef_construction, alpha } = self;
+        write!(f, "m = {m}, m0 = {m0}, ef_construction = {ef_construction}, alpha = {alpha}")
+    }
+}
+
 pub enum MetadataCodec {}
 
 impl<'a> heed::BytesEncode<'a> for MetadataCodec {
@@ -48,15 +81,12 @@ impl<'a> heed::BytesEncode<'a> for MetadataCodec {
 impl<'a> heed::BytesDecode<'a> for MetadataCodec {
     type DItem = Metadata<'a>;
 
-    fn bytes_decode(bytes: &'a [u8]) -> Result<Self::DItem, BoxedError> {
+    fn bytes_decode(mut bytes: &'a [u8]) -> Result<Self::DItem, BoxedError> {
         let distance = CStr::from_bytes_until_nul(bytes)?.to_str()?;
-        let bytes = &bytes[distance.len() + 1..];
-        let dimensions = BigEndian::read_u32(bytes);
-        let bytes = &bytes[size_of::<u32>()..];
-        let items_size = BigEndian::read_u32(bytes) as usize;
-        let bytes = &bytes[size_of::<u32>()..];
-        let items = RoaringBitmap::deserialize_from(&bytes[..items_size])?;
-        let bytes = &bytes[items_size..];
+        take(&mut bytes, distance.len() + 1)?;
+        let dimensions = BigEndian::read_u32(take(&mut bytes, size_of::<u32>())?);
+        let items_size = BigEndian::read_u32(take(&mut bytes, size_of::<u32>())?) as usize;
+        let items = RoaringBitmap::deserialize_from(take(&mut bytes, items_size)?)?;
 
         let entry_points;
         let max_level;
@@ -72,6 +102,82 @@ impl<'a> heed::BytesDecode<'a> for MetadataCodec {
     }
 }
 
+/// Splits the first `n` bytes off `bytes`.
+fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Result<&'a [u8], BoxedError> {
+    if bytes.len() < n {
+        return Err(format!("expected {n} bytes but only {} remain", bytes.len()).into());
+    }
+    let (head, tail) = bytes.split_at(n);
+    *bytes = tail;
+    Ok(head)
+}
+
+pub enum BuildParamsCodec {}
+
+impl heed::BytesEncode<'_> for BuildParamsCodec {
+    type EItem = BuildParams;
+
+    fn bytes_encode(item: &Self::EItem) -> Result<Cow<'_, [u8]>, BoxedError> {
+        let BuildParams { m, m0, ef_construction, alpha } = item;
+
+        let mut output = Vec::with_capacity(4 * size_of::<u32>());
+        output.extend_from_slice(&u32::try_from(*m)?.to_be_bytes());
+        output.extend_from_slice(&u32::try_from(*m0)?.to_be_bytes());
+        output.extend_from_slice(&u32::try_from(*ef_construction)?.to_be_bytes());
+        output.extend_from_slice(&alpha.to_be_bytes());
+
+        Ok(Cow::Owned(output))
+    }
+}
+
+impl heed::BytesDecode<'_> for BuildParamsCodec {
+    type DItem = BuildParams;
+
+    fn bytes_decode(bytes: &[u8]) -> Result<Self::DItem, BoxedError> {
+        if bytes.len() != 4 * size_of::<u32>() {
+            return Err(format!("invalid build parameters of {} bytes", bytes.len()).into());
+        }
+
+        let m = BigEndian::read_u32(bytes) as usize;
+        let m0 = BigEndian::read_u32(&bytes[size_of::<u32>()..]) as usize;
+        let ef_construction = BigEndian::read_u32(&bytes[2 * size_of::<u32>()..]) as usize;
+        let alpha = BigEndian::read_f32(&bytes[3 * size_of::<u32>()..]);
+
+        Ok(BuildParams { m, m0, ef_construction, alpha })
+    }
+}
+
+/// Returns the indexes stored in `database` along with the name of their distance, see
+/// [`Distance::name`].
+///
+/// The distance a database is opened with doesn't matter, which makes it possible to find the one
+/// to open a [`crate::Reader`] with. Indexes that were never built don't have any metadata and
+/// aren't listed.
+pub fn list_indexes<D: Distance>(
+    rtxn: &RoTxn,
+    database: Database<D>,
+) -> crate::Result<Vec<(u16, String)>> {
+    let database = database.remap_data_type::<MetadataCodec>();
+    let mut indexes = Vec::new();
+
+    // The metadata key is the first key of an index, we can jump from one index to the next
+    let mut next = Some(0);
+    while let Some(index) = next {
+        let Some((key, _)) = database
+            .remap_data_type::<DecodeIgnore>()
+            .get_greater_than_or_equal_to(rtxn, &Key::metadata(index))?
+        else {
+            break;
+        };
+        if let Some(metadata) = database.get(rtxn, &Key::metadata(key.index))? {
+            indexes.push((key.index, metadata.distance.to_string()));
+        }
+        next = key.index.checked_add(1);
+    }
+
+    Ok(indexes)
+}
+
 #[cfg(test)]
 mod test {
     use heed::{BytesDecode, BytesEncode};
@@ -80,21 +186,39 @@ mod test {
 
     #[test]
     fn metadata_codec() {
-        let metadata = Metadata {
-            dimensions: 12,
-            items: RoaringBitmap::from_sorted_iter(0..100).unwrap(),
-            entry_points: ItemIds::from_slice(&[1, 2, 3, 4]),
-            max_level: 42,
-            distance: "tamo",
-        };
+        for entry_points in [&[][..], &[7], &[1, 2, 3, 4]] {
+            let metadata = Metadata {
+                dimensions: 12,
+                items: RoaringBitmap::from_sorted_iter(0..100).unwrap(),
+                entry_points: ItemIds::from_slice(entry_points),
+                max_level: 42,
+                distance: "tamo",
+            };
+
+            let encoded = MetadataCodec::bytes_encode(&metadata).unwrap();
+            let decoded = MetadataCodec::bytes_decode(&encoded).unwrap();
+
+            assert_eq!(metadata.dimensions, decoded.dimensions);
+            assert_eq!(metadata.items, decoded.items);
+            assert_eq!(metadata.entry_points.raw_bytes(), decoded.entry_points.raw_bytes());
+            assert_eq!(metadata.distance, decoded.distance);
+            assert_eq!(metadata.max_level, decoded.max_level);
+
+            // truncated metadata must not make the decoder panic
+            for len in 0..encoded.len() {
+                let _ = MetadataCodec::bytes_decode(&encoded[..len]);
+            }
+        }
+    }
+
+    #[test]
+    fn build_params_codec() {
+        let params = BuildParams { m: 16, m0: 32, ef_construction: 100, alpha: 1.2 };
 
-        let encoded = MetadataCodec::bytes_encode(&metadata).unwrap();
-        let decoded = MetadataCodec::bytes_decode(&encoded).unwrap();
+        let encoded = BuildParamsCodec::bytes_encode(&params).unwrap();
+        let decoded = BuildParamsCodec::bytes_decode(&encoded).unwrap();
 
-        assert_eq!(metadata.dimensions, decoded.dimensions);
-        assert_eq!(metadata.items, decoded.items);
-        assert_eq!(metadata.entry_points.raw_bytes(), decoded.entry_points.raw_bytes());
-        assert_eq!(metadata.distance, decoded.distance);
-        assert_eq!(metadata.max_level, decoded.max_level);
+        assert_eq!(params, decoded);
+        assert!(BuildParamsCodec::bytes_decode(&encoded[1..]).is_err());
     }
 }
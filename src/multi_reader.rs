@@ -0,0 +1,172 @@
+use heed::{Env, RoTxn, WithoutTls};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::ordered_float::OrderedFloat;
+use crate::{Database, Distance, Error, Filter, ItemId, Reader, Result};
+
+/// Searches several indexes of the same database at once and merges their nearest neighbours
+/// into a single top-k, e.g. one index per tenant.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use hannoy::{distances::Euclidean, Database, MultiReader};
+/// # let (database, rtxn): (Database<Euclidean>, heed::RoTxn) = todo!();
+/// let reader = MultiReader::open(&rtxn, [0, 1, 2], database)?;
+/// for (index, item, distance) in reader.nns(20).by_vector(&rtxn, &[1.25854, -0.75598, 0.58524])? {
+///     println!("item {item} of index {index} is at {distance}");
+/// }
+/// # Ok::<(), hannoy::Error>(())
+/// ```
+pub struct MultiReader<D: Distance> {
+    readers: Vec<Reader<D>>,
+}
+
+impl<D: Distance> MultiReader<D> {
+    /// Opens a [`Reader`] on each of the `indexes`, they must all have the same dimensions.
+    pub fn open(
+        rtxn: &RoTxn,
+        indexes: impl IntoIterator<Item = u16>,
+        database: Database<D>,
+    ) -> Result<MultiReader<D>> {
+        let readers = indexes
+            .into_iter()
+            .map(|index| Reader::open(rtxn, index, database))
+            .collect::<Result<_>>()?;
+        MultiReader::from_readers(readers)
+    }
+
+    /// Groups readers already opened, they must all have the same dimensions.
+    pub fn from_readers(readers: Vec<Reader<D>>) -> Result<MultiReader<D>> {
+        if let Some(first) = readers.first() {
+            let expected = first.dimensions();
+            if let Some(reader) = readers.iter().find(|r| r.dimensions() != expected) {
+                return Err(Error::InvalidVecDimension { expected, received: reader.dimensions() });
+            }
+        }
+        Ok(MultiReader { readers })
+    }
+
+    /// Returns the readers of the indexes, in the order they were given.
+    pub fn readers(&self) -> &[Reader<D>] {
+        &self.readers
+    }
+
+    /// Returns the number of dimensions of the vectors of the indexes, or `None` without any.
+    pub fn dimensions(&self) -> Option<usize> {
+        self.readers.first().map(Reader::dimensions)
+    }
+
+    /// Return a [`MultiQueryBuilder`] that lets you configure and execute a search request on
+    /// all the indexes.
+    ///
+    /// You must provide the number of items you want to receive, in total.
+    pub fn nns(&self, count: usize) -> MultiQueryBuilder<'_, D> {
+        MultiQueryBuilder { reader: self, count, ef: None, rescore: None, filter: None }
+    }
+}
+
+/// Options used to make a query against a [`MultiReader`].
+pub struct MultiQueryBuilder<'a, D: Distance> {
+    reader: &'a MultiReader<D>,
+    count: usize,
+    ef: Option<usize>,
+    rescore: Option<usize>,
+    filter: Option<&'a Filter>,
+}
+
+impl<'a, D: Distance> MultiQueryBuilder<'a, D> {
+    /// Specify the beam width of each search, see [`crate::QueryBuilder::ef_search`].
+    pub fn ef_search(&mut self, ef: usize) -> &mut Self {
+        self.ef = Some(ef);
+        self
+    }
+
+    /// Rescore the candidates of each index, see [`crate::QueryBuilder::rescore`].
+    pub fn rescore(&mut self, factor: usize) -> &mut Self {
+        self.rescore = Some(factor);
+        self
+    }
+
+    /// Only returns the items matching the filter in their index, see
+    /// [`crate::QueryBuilder::filter`].
+    pub fn filter(&mut self, filter: &'a Filter) -> &mut Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Returns the `count` items closest to `vector` among all the indexes, along with the
+    /// index they come from, in increasing distance.
+    ///
+    /// The indexes are searched one after the other, see [`Self::par_by_vector`].
+    pub fn by_vector(&self, rtxn: &RoTxn, vector: &[f32]) -> Result<Vec<(u16, ItemId, f32)>> {
+        let mut nns = Vec::new();
+        for reader in &self.reader.readers {
+            nns.extend(self.search(reader, rtxn, vector)?);
+        }
+        Ok(self.merge(nns))
+    }
+
+    /// Same as [`Self::by_vector`] but searches the indexes in parallel, on the rayon thread
+    /// pool, with a read transaction per thread.
+    ///
+    /// The transactions are opened on `env` and must see the same version of the database as
+    /// `rtxn`, or [`Error::OutdatedReader`] is returned.
+    pub fn par_by_vector(
+        &self,
+        env: &Env<WithoutTls>,
+        rtxn: &RoTxn,
+        vector: &[f32],
+    ) -> Result<Vec<(u16, ItemId, f32)>> {
+        let snapshot = rtxn.id();
+
+        let results: Vec<_> = self
+            .reader
+            .readers
+            .par_iter()
+            .map_init(
+                || None,
+                |worker, reader| {
+                    if worker.is_none() {
+                        let rtxn = env.read_txn()?;
+                        if rtxn.id() != snapshot {
+                            return Err(Error::OutdatedReader);
+                        }
+                        *worker = Some(rtxn);
+                    }
+                    self.search(reader, worker.as_ref().unwrap(), vector)
+                },
+            )
+            .collect::<Result<_>>()?;
+
+        Ok(self.merge(results.into_iter().flatten().collect()))
+    }
+
+    fn search(
+        &self,
+        reader: &Reader<D>,
+        rtxn: &RoTxn,
+        vector: &[f32],
+    ) -> Result<impl Iterator<Item = (u16, ItemId, f32)>> {
+        let mut query = reader.nns(self.count);
+        if let Some(ef) = self.ef {
+            query.ef_search(ef);
+        }
+        if let Some(factor) = self.rescore {
+            query.rescore(factor);
+        }
+        if let Some(filter) = self.filter {
+            query.filter(filter);
+        }
+        let index = reader.index();
+        let searched = query.by_vector(rtxn, vector)?;
+        Ok(searched.nns.into_iter().map(move |(item, distance)| (index, item, distance)))
+    }
+
+    /// Keeps the `count` closest neighbours, the ties are broken by index and item.
+    fn merge(&self, mut nns: Vec<(u16, ItemId, f32)>) -> Vec<(u16, ItemId, f32)> {
+        nns.sort_unstable_by_key(|&(index, item, distance)| (OrderedFloat(distance), index, item));
+        nns.truncate(self.count);
+        nns
+    }
+}
@@ -75,6 +75,15 @@ impl<D: Distance> Item<'_, D> {
     }
 }
 
+impl<'a, D: Distance> Item<'a, D> {
+    /// Builds a new item from a slice of f32, without allocating if the codec allows it.
+    pub fn from_slice(slice: &'a [f32]) -> Self {
+        let vector = UnalignedVector::from_slice(slice);
+        let header = D::new_header(&vector);
+        Self { header, vector }
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Links<'a> {
     pub links: Cow<'a, RoaringBitmap>,
@@ -153,6 +162,9 @@ impl<'a, D: Distance> BytesDecode<'a> for NodeCodec<D> {
     fn bytes_decode(bytes: &'a [u8]) -> Result<Self::DItem, BoxedError> {
         match bytes {
             [NODE_TAG, bytes @ ..] => {
+                if bytes.len() < size_of::<D::Header>() {
+                    return Err(format!("invalid item header of {} bytes", bytes.len()).into());
+                }
                 let (header_bytes, remaining) = bytes.split_at(size_of::<D::Header>());
                 let header = pod_read_unaligned(header_bytes);
                 let vector = UnalignedVector::<D::VectorCodec>::from_bytes(remaining)?;
@@ -161,7 +173,7 @@ impl<'a, D: Distance> BytesDecode<'a> for NodeCodec<D> {
             }
             [LINKS_TAG, bytes @ ..] => {
                 let links: Cow<'_, RoaringBitmap> =
-                    Cow::Owned(RoaringBitmap::deserialize_from(bytes).unwrap());
+                    Cow::Owned(RoaringBitmap::deserialize_from(bytes)?);
                 Ok(Node::Links(Links { links }))
             }
 
@@ -173,6 +185,25 @@ impl<'a, D: Distance> BytesDecode<'a> for NodeCodec<D> {
     }
 }
 
+/// The codec used to store the full-precision vectors kept next to the quantized items.
+pub enum FullPrecisionCodec {}
+
+impl<'a> BytesEncode<'a> for FullPrecisionCodec {
+    type EItem = UnalignedVector<f32>;
+
+    fn bytes_encode(item: &'a Self::EItem) -> Result<Cow<'a, [u8]>, BoxedError> {
+        Ok(Cow::Borrowed(item.as_bytes()))
+    }
+}
+
+impl<'a> BytesDecode<'a> for FullPrecisionCodec {
+    type DItem = Cow<'a, UnalignedVector<f32>>;
+
+    fn bytes_decode(bytes: &'a [u8]) -> Result<Self::DItem, BoxedError> {
+        Ok(UnalignedVector::from_bytes(bytes)?)
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub struct InvalidNodeDecoding {
     unknown_tag: Option<u8>,
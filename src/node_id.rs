@@ -9,7 +9,10 @@ use crate::{ItemId, LayerId};
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(u8)]
 pub enum NodeMode {
-    /// Stores the metadata under the `ItemId` 0
+    /// Stores the metadata under the `ItemId` 0, the version under 1, the
+    /// scalar quantization calibration under 2, the attribute fields under 3, the next id to
+    /// allocate to an external key under 4, the build parameters under 5 and the biggest norm
+    /// of the dot-product items under 6.
     Metadata = 0,
     /// Stores the list of all the `ItemId` that have been updated.
     /// We only stores `Unit` values under the keys.
@@ -18,6 +21,18 @@ pub enum NodeMode {
     Links = 2,
     /// The original vectors are stored under this id in `Item` structures.
     Item = 3,
+    /// The full-precision vectors kept to rescore the candidates of quantized indexes.
+    FullPrecision = 4,
+    /// The attributes of the items, the tags and numbers the filters are evaluated on.
+    Attributes = 5,
+    /// The items having each attribute value, the `layer` is the id of the field.
+    Postings = 6,
+    /// The external key of the items that were added with one.
+    ExternalKey = 7,
+    /// The ids of the external keys, the `item` is a hash of the key.
+    KeyIndex = 8,
+    /// The ids freed by the deleted external keys, to be allocated to the next ones.
+    FreeId = 9,
 }
 
 impl TryFrom<u8> for NodeMode {
@@ -29,6 +44,12 @@ impl TryFrom<u8> for NodeMode {
             v if v == NodeMode::Links as u8 => Ok(NodeMode::Links),
             v if v == NodeMode::Updated as u8 => Ok(NodeMode::Updated),
             v if v == NodeMode::Metadata as u8 => Ok(NodeMode::Metadata),
+            v if v == NodeMode::FullPrecision as u8 => Ok(NodeMode::FullPrecision),
+            v if v == NodeMode::Attributes as u8 => Ok(NodeMode::Attributes),
+            v if v == NodeMode::Postings as u8 => Ok(NodeMode::Postings),
+            v if v == NodeMode::ExternalKey as u8 => Ok(NodeMode::ExternalKey),
+            v if v == NodeMode::KeyIndex as u8 => Ok(NodeMode::KeyIndex),
+            v if v == NodeMode::FreeId as u8 => Ok(NodeMode::FreeId),
             v => Err(format!("Could not convert {v} as a `NodeMode`.")),
         }
     }
@@ -60,6 +81,26 @@ impl NodeId {
         Self { mode: NodeMode::Metadata, item: 1, layer: 0 }
     }
 
+    pub const fn calibration() -> Self {
+        Self { mode: NodeMode::Metadata, item: 2, layer: 0 }
+    }
+
+    pub const fn attribute_fields() -> Self {
+        Self { mode: NodeMode::Metadata, item: 3, layer: 0 }
+    }
+
+    pub const fn next_key_id() -> Self {
+        Self { mode: NodeMode::Metadata, item: 4, layer: 0 }
+    }
+
+    pub const fn build_params() -> Self {
+        Self { mode: NodeMode::Metadata, item: 5, layer: 0 }
+    }
+
+    pub const fn max_norm() -> Self {
+        Self { mode: NodeMode::Metadata, item: 6, layer: 0 }
+    }
+
     pub const fn updated(item: u32) -> Self {
         Self { mode: NodeMode::Updated, item, layer: 0 }
     }
@@ -72,6 +113,30 @@ impl NodeId {
         Self { mode: NodeMode::Item, item, layer: 0 }
     }
 
+    pub const fn full_precision(item: u32) -> Self {
+        Self { mode: NodeMode::FullPrecision, item, layer: 0 }
+    }
+
+    pub const fn attributes(item: u32) -> Self {
+        Self { mode: NodeMode::Attributes, item, layer: 0 }
+    }
+
+    pub const fn postings(bucket: u32, field: u8) -> Self {
+        Self { mode: NodeMode::Postings, item: bucket, layer: field }
+    }
+
+    pub const fn external_key(item: u32) -> Self {
+        Self { mode: NodeMode::ExternalKey, item, layer: 0 }
+    }
+
+    pub const fn key_index(hash: u32) -> Self {
+        Self { mode: NodeMode::KeyIndex, item: hash, layer: 0 }
+    }
+
+    pub const fn free_id(item: u32) -> Self {
+        Self { mode: NodeMode::FreeId, item, layer: 0 }
+    }
+
     /// Return the underlying `ItemId` if it is an item.
     /// Panic otherwise.
     #[track_caller]
@@ -1,15 +1,63 @@
-use steppe::{make_atomic_progress, make_enum_progress};
+use std::borrow::Cow;
 
-make_enum_progress! {
-    pub enum HannoyBuild {
-        DeletingTheLinks,
-        RetrieveTheUpdatedItems,
-        ResolveGraphEntryPoints,
-        BuildingTheGraph,
-        PatchOldNewDeletedLinks,
-        WritingTheItems,
-        WriteTheMetadata,
-        ConvertingArroyToHannoy,
+use steppe::{make_atomic_progress, Step};
+
+/// The steps of a build reported to its [`steppe::Progress`], and timed in the
+/// [`BuildStats`](crate::BuildStats).
+///
+/// It is written by hand rather than with `steppe::make_enum_progress!`, which can't document the
+/// variants.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::enum_variant_names)]
+pub enum HannoyBuild {
+    /// Deleting the links of the removed items, or of all the items before a full rebuild.
+    DeletingTheLinks,
+    /// Collecting and resetting the items added, updated or removed since the last build.
+    RetrieveTheUpdatedItems,
+    /// Choosing the entry points of the graph among the old and new items.
+    ResolveGraphEntryPoints,
+    /// Linking the inserted items into the graph.
+    BuildingTheGraph,
+    /// Relinking the neighbours of the removed items and merging the new links with the old ones.
+    PatchOldNewDeletedLinks,
+    /// Writing the links of the graph to the database.
+    WritingTheItems,
+    /// Writing the metadata of the index.
+    WriteTheMetadata,
+    /// Converting an index written by arroy.
+    ConvertingArroyToHannoy,
+    /// Looking for the nodes with too few incoming links, see
+    /// [`HannoyBuilder::repair`](crate::HannoyBuilder::repair).
+    FindingTheWeakNodes,
+    /// Searching the neighbours of the weak nodes again.
+    RepairingTheGraph,
+}
+
+impl Step for HannoyBuild {
+    fn name(&self) -> Cow<'static, str> {
+        let name = match self {
+            HannoyBuild::DeletingTheLinks => "deleting the links",
+            HannoyBuild::RetrieveTheUpdatedItems => "retrieve the updated items",
+            HannoyBuild::ResolveGraphEntryPoints => "resolve graph entry points",
+            HannoyBuild::BuildingTheGraph => "building the graph",
+            HannoyBuild::PatchOldNewDeletedLinks => "patch old new deleted links",
+            HannoyBuild::WritingTheItems => "writing the items",
+            HannoyBuild::WriteTheMetadata => "write the metadata",
+            HannoyBuild::ConvertingArroyToHannoy => "converting arroy to hannoy",
+            HannoyBuild::FindingTheWeakNodes => "finding the weak nodes",
+            HannoyBuild::RepairingTheGraph => "repairing the graph",
+        };
+        Cow::Borrowed(name)
+    }
+
+    fn current(&self) -> u64 {
+        *self as u64
+    }
+
+    fn total(&self) -> u64 {
+        // the repair is the last step
+        HannoyBuild::RepairingTheGraph as u64 + 1
     }
 }
 
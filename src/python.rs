@@ -3,6 +3,7 @@ use std::path::PathBuf;
 use std::sync::LazyLock;
 
 use heed::{RoTxn, RwTxn, WithoutTls};
+use numpy::PyReadonlyArray2;
 use once_cell::sync::OnceCell;
 use parking_lot::{MappedMutexGuard, Mutex, MutexGuard};
 use pyo3::exceptions::{PyIOError, PyRuntimeError};
@@ -29,6 +30,8 @@ pub(super) enum PyDistance {
     Euclidean,
     #[pyo3(name = "MANHATTAN")]
     Manhattan,
+    #[pyo3(name = "DOT_PRODUCT")]
+    DotProduct,
     #[pyo3(name = "BQ_COSINE")]
     BqCosine,
     #[pyo3(name = "BQ_EUCLIDEAN")]
@@ -47,6 +50,7 @@ impl PyDistance {
             PyDistance::Cosine => "cosine".into(),
             PyDistance::Euclidean => "euclidean".into(),
             PyDistance::Manhattan => "manhattan".into(),
+            PyDistance::DotProduct => "dot_product".into(),
             PyDistance::BqCosine => "bq_cosine".into(),
             PyDistance::BqEuclidean => "bq_euclidean".into(),
             PyDistance::BqManhattan => "bq_manhattan".into(),
@@ -59,6 +63,7 @@ enum DynDatabase {
     Cosine(Database<distance::Cosine>),
     Euclidean(Database<distance::Euclidean>),
     Manhattan(Database<distance::Manhattan>),
+    DotProduct(Database<distance::DotProduct>),
     BqCosine(Database<distance::BinaryQuantizedCosine>),
     BqEuclidean(Database<distance::BinaryQuantizedEuclidean>),
     BqManhattan(Database<distance::BinaryQuantizedManhattan>),
@@ -75,6 +80,7 @@ impl DynDatabase {
             PyDistance::Cosine => Ok(DynDatabase::Cosine(env.create_database(wtxn, name)?)),
             PyDistance::Euclidean => Ok(DynDatabase::Euclidean(env.create_database(wtxn, name)?)),
             PyDistance::Manhattan => Ok(DynDatabase::Manhattan(env.create_database(wtxn, name)?)),
+            PyDistance::DotProduct => Ok(DynDatabase::DotProduct(env.create_database(wtxn, name)?)),
             PyDistance::BqCosine => Ok(DynDatabase::BqCosine(env.create_database(wtxn, name)?)),
             PyDistance::BqEuclidean => {
                 Ok(DynDatabase::BqEuclidean(env.create_database(wtxn, name)?))
@@ -131,6 +137,10 @@ impl PyDatabase {
                 dyn_writer: DynWriter::Manhattan(Writer::new(db, index, dimensions)),
                 opts,
             },
+            DynDatabase::DotProduct(db) => PyWriter {
+                dyn_writer: DynWriter::DotProduct(Writer::new(db, index, dimensions)),
+                opts,
+            },
             DynDatabase::BqCosine(db) => PyWriter {
                 dyn_writer: DynWriter::BqCosine(Writer::new(db, index, dimensions)),
                 opts,
@@ -171,6 +181,11 @@ impl PyDatabase {
                 let dyn_reader = DynReader::Manhattan(reader);
                 PyReader { dyn_reader, rtxn }
             }
+            DynDatabase::DotProduct(database) => {
+                let reader = Reader::open(&rtxn, index, database).map_err(h2py_err)?;
+                let dyn_reader = DynReader::DotProduct(reader);
+                PyReader { dyn_reader, rtxn }
+            }
             DynDatabase::BqCosine(database) => {
                 let reader = Reader::open(&rtxn, index, database).map_err(h2py_err)?;
                 let dyn_reader = DynReader::BqCosine(reader);
@@ -220,6 +235,7 @@ enum DynWriter {
     Cosine(Writer<distance::Cosine>),
     Euclidean(Writer<distance::Euclidean>),
     Manhattan(Writer<distance::Manhattan>),
+    DotProduct(Writer<distance::DotProduct>),
     BqCosine(Writer<distance::BinaryQuantizedCosine>),
     BqEuclidean(Writer<distance::BinaryQuantizedEuclidean>),
     BqManhattan(Writer<distance::BinaryQuantizedManhattan>),
@@ -263,28 +279,22 @@ impl PyWriter {
 
         let BuildOptions { ef, m, m0 } = self.opts;
 
-        // a helper macro to auto generating some matches
-        macro_rules! match_table {
-            ($w:expr => $(($M:literal, $M0:literal)),* $(,)?) => {
-                match (m, m0) {
-                    $(
-                        ($M, $M0) => $w.builder(&mut rng).ef_construction(ef).build::<$M, $M0>(&mut wtxn),
-                    )*
-                    _ => panic!("not supported: m = {}, m0 = {}", m, m0),
-                }.map_err(h2py_err)?
-            };
-        }
-        // the real macro
         macro_rules! hnsw_build {
-            ($w:expr) => {{
-                match_table! {$w => (4, 8), (8, 16), (12, 24), (16, 32), (24, 48), (32, 64)}
-            }};
+            ($w:expr) => {
+                $w.builder(&mut rng)
+                    .ef_construction(ef)
+                    .m(m)
+                    .m0(m0)
+                    .build(&mut wtxn)
+                    .map_err(h2py_err)?
+            };
         }
 
         match &self.dyn_writer {
             DynWriter::Cosine(writer) => hnsw_build!(writer),
             DynWriter::Euclidean(writer) => hnsw_build!(writer),
             DynWriter::Manhattan(writer) => hnsw_build!(writer),
+            DynWriter::DotProduct(writer) => hnsw_build!(writer),
             DynWriter::BqCosine(writer) => hnsw_build!(writer),
             DynWriter::BqEuclidean(writer) => hnsw_build!(writer),
             DynWriter::BqManhattan(writer) => hnsw_build!(writer),
@@ -326,6 +336,9 @@ impl PyWriter {
             DynWriter::Manhattan(writer) => {
                 writer.add_item(&mut wtxn, item, &vector).map_err(h2py_err)?
             }
+            DynWriter::DotProduct(writer) => {
+                writer.add_item(&mut wtxn, item, &vector).map_err(h2py_err)?
+            }
             DynWriter::BqCosine(writer) => {
                 writer.add_item(&mut wtxn, item, &vector).map_err(h2py_err)?
             }
@@ -347,6 +360,7 @@ enum DynReader {
     Cosine(Reader<distance::Cosine>),
     Euclidean(Reader<distance::Euclidean>),
     Manhattan(Reader<distance::Manhattan>),
+    DotProduct(Reader<distance::DotProduct>),
     BqCosine(Reader<distance::BinaryQuantizedCosine>),
     BqEuclidean(Reader<distance::BinaryQuantizedEuclidean>),
     BqManhattan(Reader<distance::BinaryQuantizedManhattan>),
@@ -388,6 +402,7 @@ impl PyReader {
             DynReader::Cosine(reader) => hnsw_search!(reader, &query)?,
             DynReader::Euclidean(reader) => hnsw_search!(reader, &query)?,
             DynReader::Manhattan(reader) => hnsw_search!(reader, &query)?,
+            DynReader::DotProduct(reader) => hnsw_search!(reader, &query)?,
             DynReader::BqCosine(reader) => hnsw_search!(reader, &query)?,
             DynReader::BqEuclidean(reader) => hnsw_search!(reader, &query)?,
             DynReader::BqManhattan(reader) => hnsw_search!(reader, &query)?,
@@ -395,6 +410,44 @@ impl PyReader {
         };
         Ok(found.into_nns())
     }
+
+    /// Retrieve similar items from the db for every row of a 2-D array of queries, searching them
+    /// in parallel.
+    ///
+    /// Every thread searches with its own read transaction, they are all opened before the
+    /// search and must see the same version of the database as this reader. A runtime error is
+    /// raised, without searching anything, if a write was committed since the reader was
+    /// opened: open a new reader to see it.
+    #[pyo3(signature = (queries, n=10, ef_search=200))]
+    fn by_vecs(
+        &self,
+        queries: PyReadonlyArray2<f32>,
+        n: usize,
+        ef_search: usize,
+    ) -> PyResult<Vec<Vec<(ItemId, f32)>>> {
+        let env = ENV.get().ok_or_else(|| PyRuntimeError::new_err("No environment"))?;
+        let rtxn = &self.rtxn;
+        let queries: Vec<Vec<f32>> =
+            queries.as_array().rows().into_iter().map(|row| row.to_vec()).collect();
+
+        macro_rules! hnsw_search {
+            ($read:expr, $q:expr) => {
+                $read.nns(n).ef_search(ef_search).by_vectors(env, rtxn, $q).map_err(h2py_err)
+            };
+        }
+
+        let found = match &self.dyn_reader {
+            DynReader::Cosine(reader) => hnsw_search!(reader, &queries)?,
+            DynReader::Euclidean(reader) => hnsw_search!(reader, &queries)?,
+            DynReader::Manhattan(reader) => hnsw_search!(reader, &queries)?,
+            DynReader::DotProduct(reader) => hnsw_search!(reader, &queries)?,
+            DynReader::BqCosine(reader) => hnsw_search!(reader, &queries)?,
+            DynReader::BqEuclidean(reader) => hnsw_search!(reader, &queries)?,
+            DynReader::BqManhattan(reader) => hnsw_search!(reader, &queries)?,
+            DynReader::Hamming(reader) => hnsw_search!(reader, &queries)?,
+        };
+        Ok(found.into_iter().map(|searched| searched.into_nns()).collect())
+    }
 }
 
 fn h2py_err<E: Into<crate::error::Error>>(e: E) -> PyErr {
@@ -1,27 +1,47 @@
+use std::borrow::Cow;
 use std::cmp::Reverse;
 use std::collections::BinaryHeap;
+use std::io::Write;
 use std::marker;
 use std::num::NonZeroUsize;
+use std::sync::Arc;
 
 use heed::types::DecodeIgnore;
-use heed::RoTxn;
+use heed::{Env, RoTxn, WithoutTls};
 use min_max_heap::MinMaxHeap;
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use rayon::slice::ParallelSlice;
 use roaring::RoaringBitmap;
 
+use crate::attributes;
+use crate::calibration::{Calibration, CalibrationCodec};
 use crate::distance::Distance;
+use crate::dump;
+use crate::external_keys;
 use crate::hnsw::ScoredLink;
 use crate::internals::KeyCodec;
 use crate::item_iter::ItemIter;
 use crate::metadata::Metadata;
-use crate::node::{Item, Links};
+use crate::node::{FullPrecisionCodec, Item, Links};
 use crate::ordered_float::OrderedFloat;
-use crate::unaligned_vector::UnalignedVector;
+use crate::stats::{DisjointSets, GraphStats, LayerStats, SearchStats};
+use crate::vecs::{write_npy_header, VecsFormat};
 use crate::version::{Version, VersionCodec};
-use crate::{Database, Error, ItemId, Key, MetadataCodec, Node, Prefix, PrefixCodec, Result};
+use crate::{
+    Attributes, BuildParams, BuildParamsCodec, CheckReport, Database, Error, Filter, ItemId, Key,
+    MetadataCodec, Node, NodeCodec, Prefix, PrefixCodec, Result,
+};
 
 /// A good default value for the `ef` parameter.
 const DEFAULT_EF_SEARCH: usize = 100;
 
+/// A predicate on the items a search can return, see [`QueryBuilder::filter_fn`].
+type FilterFn<'a> = dyn Fn(ItemId) -> bool + Sync + Send + 'a;
+
+/// The number of candidates spread over the candidates that are added to the entry points of a
+/// filtered traversal.
+const FILTERED_TRAVERSAL_SEEDS: u64 = 16;
+
 #[cfg(not(windows))]
 const READER_AVAILABLE_MEMORY: &str = "HANNOY_READER_PREFETCH_MEMORY";
 
@@ -38,11 +58,19 @@ pub struct Searched {
     pub nns: Vec<(ItemId, f32)>,
     /// A bool indicating whether or not the search terminated early
     pub did_cancel: bool,
+    /// The work done by the search, only collected when asked with
+    /// [`QueryBuilder::collect_stats`]
+    pub stats: Option<SearchStats>,
 }
 
 impl Searched {
     pub(crate) fn new(nns: Vec<(ItemId, f32)>, did_cancel: bool) -> Self {
-        Searched { nns, did_cancel }
+        Searched { nns, did_cancel, stats: None }
+    }
+
+    /// Returns the work done by the search, if it was collected
+    pub fn stats(&self) -> Option<&SearchStats> {
+        self.stats.as_ref()
     }
 
     /// Indicates if the search terminated early
@@ -60,10 +88,16 @@ impl Searched {
 pub struct QueryBuilder<'a, D: Distance> {
     reader: &'a Reader<D>,
     candidates: Option<&'a RoaringBitmap>,
+    filter: Option<&'a Filter>,
+    filter_fn: Option<Arc<FilterFn<'a>>>,
     count: usize,
     ef: usize,
     linear_below: usize,
     linear_below_ratio: f32,
+    rescore: Option<usize>,
+    diversify: Option<(f32, usize)>,
+    collect_stats: bool,
+    filtered_traversal: bool,
 }
 
 impl<'a, D: Distance> QueryBuilder<'a, D> {
@@ -79,8 +113,22 @@ impl<'a, D: Distance> QueryBuilder<'a, D> {
     /// reader.nns(20).by_item(&rtxn, 5);
     /// ```
     pub fn by_item(&self, rtxn: &RoTxn, item: ItemId) -> Result<Option<Searched>> {
-        self.reader.nns_by_item(rtxn, item, self, || false).map(|res| match res {
-            Some(Completion::Done(items)) => Some(Searched::new(items, false)),
+        if self.filter.is_some() {
+            return self.with_filter(rtxn, |query| query.by_item(rtxn, item));
+        }
+
+        let opt = self.oversampled();
+        let mut scratch = Scratch::default();
+        let res = self.reader.nns_by_item(
+            rtxn,
+            item,
+            opt.as_ref().unwrap_or(self),
+            &mut scratch,
+            || false,
+        )?;
+        let res = self.rescored_by_item(rtxn, item, res)?;
+        res.map(|res| self.diversified(rtxn, res)).transpose().map(|res| match res {
+            Some(Completion::Done(items)) => Some(self.searched(items, false, &scratch)),
             Some(Completion::Cancelled(_)) => {
                 unreachable!("cancellation only possible using by_item_with_cancellation")
             }
@@ -88,6 +136,28 @@ impl<'a, D: Distance> QueryBuilder<'a, D> {
         })
     }
 
+    /// Returns the closests items from the item identified by an external key, see
+    /// [`crate::Writer::add_item_by_key`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{Reader, distances::Euclidean};
+    /// # let (reader, rtxn): (Reader<Euclidean>, heed::RoTxn) = todo!();
+    /// if let Some(searched) = reader.nns(20).by_key(&rtxn, b"my-document")? {
+    ///     for (key, distance) in reader.external_keys(&rtxn, &searched.nns)? {
+    ///         println!("{:?} is at {distance}", key.map(String::from_utf8));
+    ///     }
+    /// }
+    /// # Ok::<(), hannoy::Error>(())
+    /// ```
+    pub fn by_key(&self, rtxn: &RoTxn, key: &[u8]) -> Result<Option<Searched>> {
+        match self.reader.item_id(rtxn, key)? {
+            Some(item) => self.by_item(rtxn, item),
+            None => Ok(None),
+        }
+    }
+
     /// Returns as many nearest neighbours to the query as possible before `cancel_fn` evaluates to
     /// true, and indicates whether or not search terminated early.
     ///
@@ -102,7 +172,7 @@ impl<'a, D: Distance> QueryBuilder<'a, D> {
     ///
     /// let later = Instant::now().checked_add(Duration::from_secs(1)).unwrap();
     /// let cancel_fn = || Instant::now() > later;
-    /// let Searched{ nns, did_cancel } = reader.nns(20).by_item_with_cancellation(&rtxn, 5, cancel_fn)?.unwrap();
+    /// let Searched{ nns, did_cancel, .. } = reader.nns(20).by_item_with_cancellation(&rtxn, 5, cancel_fn)?.unwrap();
     /// # Ok::<(), hannoy::Error>(())
     /// ```
     pub fn by_item_with_cancellation(
@@ -111,9 +181,26 @@ impl<'a, D: Distance> QueryBuilder<'a, D> {
         item: ItemId,
         cancel_fn: impl Fn() -> bool,
     ) -> Result<Option<Searched>> {
-        self.reader.nns_by_item(rtxn, item, self, cancel_fn).map(|res| match res {
-            Some(Completion::Done(done)) => Some(Searched::new(done, false)),
-            Some(Completion::Cancelled(cancelled)) => Some(Searched::new(cancelled, true)),
+        if self.filter.is_some() {
+            return self
+                .with_filter(rtxn, |query| query.by_item_with_cancellation(rtxn, item, cancel_fn));
+        }
+
+        let opt = self.oversampled();
+        let mut scratch = Scratch::default();
+        let res = self.reader.nns_by_item(
+            rtxn,
+            item,
+            opt.as_ref().unwrap_or(self),
+            &mut scratch,
+            cancel_fn,
+        )?;
+        let res = self.rescored_by_item(rtxn, item, res)?;
+        res.map(|res| self.diversified(rtxn, res)).transpose().map(|res| match res {
+            Some(Completion::Done(done)) => Some(self.searched(done, false, &scratch)),
+            Some(Completion::Cancelled(cancelled)) => {
+                Some(self.searched(cancelled, true, &scratch))
+            }
             None => None,
         })
     }
@@ -129,7 +216,99 @@ impl<'a, D: Distance> QueryBuilder<'a, D> {
     /// # let (reader, rtxn): (Reader<Euclidean>, heed::RoTxn) = todo!();
     /// reader.nns(20).by_vector(&rtxn, &[1.25854, -0.75598, 0.58524]);
     /// ```
-    pub fn by_vector(&self, rtxn: &RoTxn, vector: &'a [f32]) -> Result<Searched> {
+    pub fn by_vector(&self, rtxn: &RoTxn, vector: &[f32]) -> Result<Searched> {
+        if self.filter.is_some() {
+            return self.with_filter(rtxn, |query| query.by_vector(rtxn, vector));
+        }
+
+        self.search_vector(rtxn, vector, &mut Scratch::default())
+    }
+
+    /// Returns the closest items of every vector in `vectors`, in the same order, running the
+    /// searches in parallel on the rayon thread pool.
+    ///
+    /// Read transactions can't be shared between threads so one is opened on `env` for every
+    /// thread of the pool, which is why the environment must be opened
+    /// [`WithoutTls`](heed::WithoutTls). They are all opened before the batch starts and must see
+    /// the same version of the database as `rtxn`, the transaction the [`Reader`] was opened
+    /// with: [`Error::OutdatedReader`] is returned, without running any query, if a write
+    /// transaction committed since then. A write committing while the batch runs doesn't affect
+    /// it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{Reader, distances::Euclidean};
+    /// # use heed::{Env, RoTxn, WithoutTls};
+    /// # let (env, reader, rtxn): (Env<WithoutTls>, Reader<Euclidean>, RoTxn<WithoutTls>) = todo!();
+    /// let queries = vec![[1.25854, -0.75598, 0.58524], [-0.54291, 0.16733, 0.80234]];
+    /// let searched = reader.nns(20).by_vectors(&env, &rtxn, &queries)?;
+    /// assert_eq!(searched.len(), queries.len());
+    /// # Ok::<(), hannoy::Error>(())
+    /// ```
+    pub fn by_vectors<V>(
+        &self,
+        env: &Env<WithoutTls>,
+        rtxn: &RoTxn,
+        vectors: &[V],
+    ) -> Result<Vec<Searched>>
+    where
+        V: AsRef<[f32]> + Sync,
+    {
+        if self.filter.is_some() {
+            return self.with_filter(rtxn, |query| query.by_vectors(env, rtxn, vectors));
+        }
+
+        let n_workers = rayon::current_num_threads().min(vectors.len()).max(1);
+        let mut workers = (0..n_workers)
+            .map(|_| {
+                let worker_rtxn = env.read_txn()?;
+                if worker_rtxn.id() != rtxn.id() {
+                    return Err(Error::OutdatedReader);
+                }
+                Ok((worker_rtxn, Scratch::default()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // every worker searches a contiguous chunk of the queries with its own transaction
+        let chunk_size = vectors.len().div_ceil(n_workers).max(1);
+        let searched = vectors
+            .par_chunks(chunk_size)
+            .zip(workers.par_iter_mut())
+            .map(|(vectors, (rtxn, scratch))| {
+                vectors
+                    .iter()
+                    .map(|vector| self.search_vector(rtxn, vector.as_ref(), scratch))
+                    .collect()
+            })
+            .collect::<Result<Vec<Vec<_>>>>()?;
+
+        Ok(searched.into_iter().flatten().collect())
+    }
+
+    /// Returns a lazy iterator over the items closest to the provided `vector`, in increasing
+    /// distance.
+    ///
+    /// The `count` of the query is ignored, pull neighbours until you have enough of them. Every
+    /// call to `next` resumes the search where the previous one stopped, using a beam of `ef`
+    /// neighbours. Quantized indexes are searched with their quantized distance,
+    /// [`Self::rescore`] is ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{Reader, distances::Euclidean};
+    /// # let (reader, rtxn): (Reader<Euclidean>, heed::RoTxn) = todo!();
+    /// let mut iter = reader.nns(0).iter_by_vector(&rtxn, &[1.25854, -0.75598, 0.58524])?;
+    /// let first_page: Vec<_> = iter.by_ref().take(20).collect::<Result<_, _>>()?;
+    /// let second_page: Vec<_> = iter.take(20).collect::<Result<_, _>>()?;
+    /// # Ok::<(), hannoy::Error>(())
+    /// ```
+    pub fn iter_by_vector<'t>(
+        &self,
+        rtxn: &'t RoTxn,
+        vector: &'a [f32],
+    ) -> Result<NnsIter<'a, 't, D>> {
         if vector.len() != self.reader.dimensions() {
             return Err(Error::InvalidVecDimension {
                 expected: self.reader.dimensions(),
@@ -137,14 +316,63 @@ impl<'a, D: Distance> QueryBuilder<'a, D> {
             });
         }
 
-        let vector = UnalignedVector::from_slice(vector);
-        let item = Item { header: D::new_header(&vector), vector };
+        let candidates = match self.filter {
+            Some(filter) => Some(Cow::Owned(self.filtered_candidates(rtxn, filter)?)),
+            None => self.candidates.map(Cow::Borrowed),
+        };
+        let opt = QueryBuilder {
+            candidates: candidates.as_deref(),
+            filter: None,
+            filter_fn: None,
+            ..*self
+        };
 
-        let cancel_fn = || false;
-        let neighbours =
-            self.reader.nns_by_vec(rtxn, &item, self, cancel_fn).map(|res| res.into_inner())?;
+        let mut iter = NnsIter {
+            reader: self.reader,
+            rtxn,
+            query: D::encode(vector, self.reader.calibration.as_ref()),
+            candidates: None,
+            filter_fn: self.filter_fn.clone(),
+            ef: self.ef.max(1),
+            frontier: BinaryHeap::new(),
+            beam: MinMaxHeap::with_capacity(self.ef),
+            spill: BinaryHeap::new(),
+            path: RoaringBitmap::new(),
+        };
+
+        // If we will never find any candidates, return an empty iterator
+        let item_ids = self.reader.item_ids();
+        if item_ids.is_empty() || opt.candidates.is_some_and(|c| item_ids.is_disjoint(c)) {
+            return Ok(iter);
+        }
+
+        // If the number of candidates is less than a given threshold, rank them all at once
+        if let Some(candidates) = opt.candidates.filter(|_| self.reader.should_linear_scan(&opt)) {
+            let count = candidates.len() as usize;
+            let found = self
+                .reader
+                .brute_force_search(
+                    &iter.query,
+                    rtxn,
+                    candidates,
+                    self.filter_fn.as_deref(),
+                    count,
+                    &mut SearchStats::default(),
+                    || false,
+                )?
+                .into_inner();
+            iter.spill.extend(found.into_iter().map(|(i, d)| Reverse((OrderedFloat(d), i))));
+            return Ok(iter);
+        }
 
-        Ok(Searched::new(neighbours, false))
+        drop(opt);
+        iter.candidates = candidates;
+        let eps = self.reader.descend_to_layer_zero(&iter.query, rtxn, &mut Scratch::default())?;
+        for ep in eps {
+            iter.discover(ep)?;
+        }
+
+        Ok(iter)
     }
 
     /// Returns as many nearest neighbours to the query as possible before `cancel_fn` evaluates to
@@ -161,15 +389,21 @@ impl<'a, D: Distance> QueryBuilder<'a, D> {
     ///
     /// let later = Instant::now().checked_add(Duration::from_secs(1)).unwrap();
     /// let cancel_fn = || Instant::now() > later;
-    /// let Searched{ nns, did_cancel } = reader.nns(20).by_vector_with_cancellation(&rtxn, &[1.25854, -0.75598, 0.58524], cancel_fn)?;
+    /// let Searched{ nns, did_cancel, .. } = reader.nns(20).by_vector_with_cancellation(&rtxn, &[1.25854, -0.75598, 0.58524], cancel_fn)?;
     /// # Ok::<(), hannoy::Error>(())
     /// ```
     pub fn by_vector_with_cancellation(
         &self,
         rtxn: &RoTxn,
-        vector: &'a [f32],
+        vector: &[f32],
         cancel_fn: impl Fn() -> bool,
     ) -> Result<Searched> {
+        if self.filter.is_some() {
+            return self.with_filter(rtxn, |query| {
+                query.by_vector_with_cancellation(rtxn, vector, cancel_fn)
+            });
+        }
+
         if vector.len() != self.reader.dimensions() {
             return Err(Error::InvalidVecDimension {
                 expected: self.reader.dimensions(),
@@ -177,14 +411,87 @@ impl<'a, D: Distance> QueryBuilder<'a, D> {
             });
         }
 
-        let vector = UnalignedVector::from_slice(vector);
-        let item = Item { header: D::new_header(&vector), vector };
+        let item = D::encode(vector, self.reader.calibration.as_ref());
+
+        let opt = self.oversampled();
+        let mut scratch = Scratch::default();
+        let nns = self.reader.nns_by_vec(
+            rtxn,
+            &item,
+            opt.as_ref().unwrap_or(self),
+            &mut scratch,
+            cancel_fn,
+        )?;
+        let nns = self.rescored(rtxn, vector, nns)?;
+        match self.diversified(rtxn, nns)? {
+            Completion::Done(done) => Ok(self.searched(done, false, &scratch)),
+            Completion::Cancelled(cancelled) => Ok(self.searched(cancelled, true, &scratch)),
+        }
+    }
+
+    /// Returns every item within `max_distance` of `item`, not including the item itself, sorted
+    /// by distance.
+    ///
+    /// See also [`Self::by_vector_within`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{Reader, distances::Euclidean};
+    /// # let (reader, rtxn): (Reader<Euclidean>, heed::RoTxn) = todo!();
+    /// reader.nns(0).by_item_within(&rtxn, 5, 0.1);
+    /// ```
+    pub fn by_item_within(
+        &self,
+        rtxn: &RoTxn,
+        item: ItemId,
+        max_distance: f32,
+    ) -> Result<Option<Searched>> {
+        if self.filter.is_some() {
+            return self.with_filter(rtxn, |query| query.by_item_within(rtxn, item, max_distance));
+        }
+
+        let found = self.reader.nns_within_item(rtxn, item, self, max_distance)?;
+        Ok(found.map(|found| Searched::new(found, false)))
+    }
+
+    /// Returns every item within `max_distance` of the provided `vector`, sorted by distance.
+    ///
+    /// Unlike [`Self::by_vector`] the number of neighbours returned isn't bounded by the `count`
+    /// of the query, which is ignored. Instead the search beam starts with `ef` neighbours and
+    /// keeps widening as long as it finds items within the radius. The `max_distance` is
+    /// inclusive and expressed in the same unit as the distances returned by the other searches.
+    /// Quantized indexes are searched with their quantized distance, [`Self::rescore`] is ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{Reader, distances::Euclidean};
+    /// # let (reader, rtxn): (Reader<Euclidean>, heed::RoTxn) = todo!();
+    /// reader.nns(0).by_vector_within(&rtxn, &[1.25854, -0.75598, 0.58524], 0.1);
+    /// ```
+    pub fn by_vector_within(
+        &self,
+        rtxn: &RoTxn,
+        vector: &[f32],
+        max_distance: f32,
+    ) -> Result<Searched> {
+        if self.filter.is_some() {
+            return self
+                .with_filter(rtxn, |query| query.by_vector_within(rtxn, vector, max_distance));
+        }
 
-        let nns = self.reader.nns_by_vec(rtxn, &item, self, cancel_fn)?;
-        match nns {
-            Completion::Done(done) => Ok(Searched::new(done, false)),
-            Completion::Cancelled(cancelled) => Ok(Searched::new(cancelled, true)),
+        if vector.len() != self.reader.dimensions() {
+            return Err(Error::InvalidVecDimension {
+                expected: self.reader.dimensions(),
+                received: vector.len(),
+            });
         }
+
+        let item = D::encode(vector, self.reader.calibration.as_ref());
+        let found = self.reader.nns_within_vec(rtxn, &item, self, max_distance)?;
+
+        Ok(Searched::new(found, false))
     }
 
     /// Specify a subset of candidates to inspect. Filters out everything else.
@@ -202,6 +509,51 @@ impl<'a, D: Distance> QueryBuilder<'a, D> {
         self
     }
 
+    /// Specify a predicate the returned items must satisfy, on top of the
+    /// [candidates](Self::candidates). Filters out every item for which it returns `false`.
+    ///
+    /// Unlike the candidates it's evaluated lazily, on the items the search comes across, which
+    /// suits the checks that are cheap per item but expensive to turn into a bitmap of every id,
+    /// e.g. an access check against an in-memory cache. It can't make the search switch to a
+    /// linear scan, prefer the candidates when the items are few.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{Reader, distances::Euclidean};
+    /// # let (reader, rtxn): (Reader<Euclidean>, heed::RoTxn) = todo!();
+    /// use std::collections::HashSet;
+    ///
+    /// let banned: HashSet<u32> = HashSet::from([2, 7, 8]);
+    /// reader.nns(20).filter_fn(|item| !banned.contains(&item)).by_item(&rtxn, 6);
+    /// ```
+    pub fn filter_fn(
+        &mut self,
+        filter_fn: impl Fn(ItemId) -> bool + Sync + Send + 'a,
+    ) -> &mut Self {
+        self.filter_fn = Some(Arc::new(filter_fn));
+        self
+    }
+
+    /// Only returns the items whose [attributes](crate::Writer::set_attributes) match the
+    /// `filter`. It's resolved into a bitmap through the postings of the attributes, at the start
+    /// of every search, and intersected with the [candidates](Self::candidates).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{Reader, distances::Euclidean};
+    /// # let (reader, rtxn): (Reader<Euclidean>, heed::RoTxn) = todo!();
+    /// use hannoy::Filter;
+    ///
+    /// let filter = Filter::tag("color", "red").and(Filter::range("price", ..20.0));
+    /// reader.nns(20).filter(&filter).by_item(&rtxn, 6);
+    /// ```
+    pub fn filter(&mut self, filter: &'a Filter) -> &mut Self {
+        self.filter = Some(filter);
+        self
+    }
+
     /// Specify a search buffer size from which the closest elements are returned. Increasing this
     /// value improves the search relevancy but increases latency as more neighbours need to be
     /// searched.
@@ -258,6 +610,329 @@ impl<'a, D: Distance> QueryBuilder<'a, D> {
         self.linear_below_ratio = ratio;
         self
     }
+
+    /// Searches `factor` times more neighbours than requested with the quantized distance of
+    /// the index, then re-ranks them against their full-precision vectors and only keeps the
+    /// closest ones. The full-precision vectors must have been stored with
+    /// [`Writer::set_full_precision`](crate::Writer::set_full_precision).
+    ///
+    /// The distances returned are the full-precision ones, the items stored without their
+    /// full-precision vector keep their quantized distance. Distances that aren't quantized
+    /// ignore this option, see [`Distance::full_precision_distance`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{Reader, distances::BinaryQuantizedCosine};
+    /// # let (reader, rtxn): (Reader<BinaryQuantizedCosine>, heed::RoTxn) = todo!();
+    /// reader.nns(20).rescore(4).by_vector(&rtxn, &[1.25854, -0.75598, 0.58524]);
+    /// ```
+    pub fn rescore(&mut self, factor: usize) -> &mut Self {
+        self.rescore = Some(factor.max(1));
+        self
+    }
+
+    /// Retrieves the `fetch_k` nearest neighbours and then greedily selects `count` of them with
+    /// [Maximal Marginal Relevance], so that the results aren't near-duplicates of each other.
+    ///
+    /// Each step picks the neighbour maximizing `lambda * relevance + (1 - lambda) * novelty`,
+    /// the relevance being the opposite of its distance to the query and the novelty its distance
+    /// to the closest neighbour already picked, computed with [`Distance::distance`] between the
+    /// stored items. Both are computed on the full-precision vectors when the results are
+    /// [rescored](Self::rescore), so that they stay on the same scale. A `lambda` of `1.0`
+    /// returns the nearest neighbours as usual, a lower one trades relevance for diversity. The
+    /// results come in the order they were picked. The search returns
+    /// [`Error::InvalidDiversification`] if `lambda` isn't between 0.0 and 1.0.
+    ///
+    /// It applies after [`Self::rescore`], but is ignored by [`Self::iter_by_vector`] and the
+    /// searches within a distance.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{Reader, distances::Cosine};
+    /// # let (reader, rtxn): (Reader<Cosine>, heed::RoTxn) = todo!();
+    /// reader.nns(5).diversify(0.5, 50).by_vector(&rtxn, &[1.25854, -0.75598, 0.58524]);
+    /// ```
+    ///
+    /// [Maximal Marginal Relevance]: https://www.cs.cmu.edu/~jgc/publication/The_Use_MMR_Diversity_Based_LTMIR_1998.pdf
+    pub fn diversify(&mut self, lambda: f32, fetch_k: usize) -> &mut Self {
+        self.diversify = Some((lambda, fetch_k));
+        self
+    }
+
+    /// Specify whether the returned [`Searched`] carries the [`SearchStats`] of the query, e.g.
+    /// how many distances were computed or whether a linear scan was used.
+    ///
+    /// Only the searches for the nearest neighbours of an item or a vector collect them, the
+    /// rescoring and diversification steps aren't counted. Defaults to `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{Reader, distances::Euclidean};
+    /// # let (reader, rtxn): (Reader<Euclidean>, heed::RoTxn) = todo!();
+    /// let searched = reader.nns(20).collect_stats(true).by_vector(&rtxn, &[1.25854, -0.75598, 0.58524])?;
+    /// let stats = searched.stats().unwrap();
+    /// println!("{} distances computed", stats.n_distances);
+    /// # Ok::<(), hannoy::Error>(())
+    /// ```
+    pub fn collect_stats(&mut self, enabled: bool) -> &mut Self {
+        self.collect_stats = enabled;
+        self
+    }
+
+    /// Specify whether the graph walk stays within the [candidates](Self::candidates) and the
+    /// items accepted by the [`Self::filter_fn`], in the style of [ACORN-1]. Defaults to `false`.
+    ///
+    /// By default the items that aren't candidates are visited like the others and only left
+    /// out of the results, which wastes most of the beam once the candidates are a small part of
+    /// the index, e.g. under 10%, and makes the recall collapse. With this option their distance
+    /// isn't computed, the search goes through their links to the candidates behind them
+    /// instead, and a few items spread over the candidates are added to the entry points so that
+    /// the groups of candidates that aren't linked to the others are searched too.
+    ///
+    /// It's worth enabling when the candidates are selective but too many to be scanned linearly,
+    /// see [`Self::linear_below`]. It's ignored by [`Self::iter_by_vector`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{Reader, distances::Euclidean};
+    /// # let (reader, rtxn): (Reader<Euclidean>, heed::RoTxn) = todo!();
+    /// let candidates = roaring::RoaringBitmap::from_iter((0..1_000_000).step_by(50));
+    /// reader.nns(20).candidates(&candidates).filtered_traversal(true).by_item(&rtxn, 6);
+    /// ```
+    ///
+    /// [ACORN-1]: https://arxiv.org/abs/2403.04871
+    pub fn filtered_traversal(&mut self, enabled: bool) -> &mut Self {
+        self.filtered_traversal = enabled;
+        self
+    }
+
+    /// Runs [`Self::by_vector`] reusing the `scratch` buffers of the current thread.
+    fn search_vector(
+        &self,
+        rtxn: &RoTxn,
+        vector: &[f32],
+        scratch: &mut Scratch,
+    ) -> Result<Searched> {
+        if vector.len() != self.reader.dimensions() {
+            return Err(Error::InvalidVecDimension {
+                expected: self.reader.dimensions(),
+                received: vector.len(),
+            });
+        }
+
+        let item = D::encode(vector, self.reader.calibration.as_ref());
+        scratch.stats = SearchStats::default();
+
+        let cancel_fn = || false;
+        let opt = self.oversampled();
+        let neighbours = self.reader.nns_by_vec(
+            rtxn,
+            &item,
+            opt.as_ref().unwrap_or(self),
+            scratch,
+            cancel_fn,
+        )?;
+        let neighbours = self.rescored(rtxn, vector, neighbours)?;
+        let neighbours = self.diversified(rtxn, neighbours)?.into_inner();
+
+        Ok(self.searched(neighbours, false, scratch))
+    }
+
+    /// Runs `search` with the candidates matching the filter instead of the filter.
+    fn with_filter<T>(
+        &self,
+        rtxn: &RoTxn,
+        search: impl FnOnce(&QueryBuilder<'_, D>) -> Result<T>,
+    ) -> Result<T> {
+        match self.filter {
+            Some(filter) => {
+                let candidates = self.filtered_candidates(rtxn, filter)?;
+                let query = QueryBuilder {
+                    candidates: Some(&candidates),
+                    filter: None,
+                    filter_fn: self.filter_fn.clone(),
+                    ..*self
+                };
+                search(&query)
+            }
+            None => search(self),
+        }
+    }
+
+    /// Returns the candidates that match the `filter`.
+    fn filtered_candidates(&self, rtxn: &RoTxn, filter: &Filter) -> Result<RoaringBitmap> {
+        let mut candidates = self.reader.filter(rtxn, filter)?;
+        if let Some(c) = self.candidates {
+            candidates &= c;
+        }
+        Ok(candidates)
+    }
+
+    /// Wraps the neighbours found, along with the stats of the search if they were asked for.
+    fn searched(&self, nns: Vec<(ItemId, f32)>, did_cancel: bool, scratch: &Scratch) -> Searched {
+        Searched { nns, did_cancel, stats: self.collect_stats.then_some(scratch.stats) }
+    }
+
+    /// Returns the options to search the candidates with when they will be rescored or
+    /// diversified.
+    fn oversampled(&self) -> Option<QueryBuilder<'a, D>> {
+        let factor = self.rescore.filter(|_| D::full_precision_distance().is_some());
+        if factor.is_none() && self.diversify.is_none() {
+            return None;
+        }
+        let count = self.count.saturating_mul(factor.unwrap_or(1)).max(self.fetched());
+
+        Some(QueryBuilder {
+            reader: self.reader,
+            candidates: self.candidates,
+            filter: self.filter,
+            filter_fn: self.filter_fn.clone(),
+            count,
+            ef: self.ef.max(count),
+            linear_below: self.linear_below,
+            linear_below_ratio: self.linear_below_ratio,
+            rescore: None,
+            diversify: None,
+            collect_stats: self.collect_stats,
+            filtered_traversal: self.filtered_traversal,
+        })
+    }
+
+    /// Re-ranks the candidates found with [`Self::oversampled`] against the full-precision `query`.
+    fn rescored(
+        &self,
+        rtxn: &RoTxn,
+        query: &[f32],
+        found: Completion<Vec<(ItemId, f32)>>,
+    ) -> Result<Completion<Vec<(ItemId, f32)>>> {
+        let Some(distance) = D::full_precision_distance().filter(|_| self.rescore.is_some()) else {
+            return Ok(found);
+        };
+
+        let rescore = |candidates: Vec<(ItemId, f32)>| -> Result<Vec<(ItemId, f32)>> {
+            let mut rescored = candidates
+                .into_iter()
+                .map(|(item, quantized)| {
+                    // items stored without their full-precision vector keep the quantized distance
+                    match self.reader.full_precision_vector(rtxn, item)? {
+                        Some(vector) => Ok((item, distance(query, &vector))),
+                        None => Ok((item, quantized)),
+                    }
+                })
+                .collect::<Result<Vec<_>>>()?;
+            rescored.sort_unstable_by_key(|&(item, distance)| (OrderedFloat(distance), item));
+            rescored.truncate(self.fetched());
+            Ok(rescored)
+        };
+
+        Ok(match found {
+            Completion::Done(done) => Completion::Done(rescore(done)?),
+            Completion::Cancelled(cancelled) => Completion::Cancelled(rescore(cancelled)?),
+        })
+    }
+
+    /// Returns the number of neighbours kept before the diversification picks `count` of them.
+    fn fetched(&self) -> usize {
+        self.diversify.map_or(self.count, |(_, fetch_k)| fetch_k.max(self.count))
+    }
+
+    /// Greedily picks `count` of the neighbours `found` with Maximal Marginal Relevance, see
+    /// [`Self::diversify`].
+    fn diversified(
+        &self,
+        rtxn: &RoTxn,
+        found: Completion<Vec<(ItemId, f32)>>,
+    ) -> Result<Completion<Vec<(ItemId, f32)>>> {
+        let Some((lambda, _)) = self.diversify else { return Ok(found) };
+        // NaN isn't contained in the range either
+        if !(0.0..=1.0).contains(&lambda) {
+            return Err(Error::InvalidDiversification(lambda));
+        }
+        let Reader { database, index, .. } = *self.reader;
+
+        let diversify = |candidates: Vec<(ItemId, f32)>| -> Result<Vec<(ItemId, f32)>> {
+            let items = candidates
+                .iter()
+                .map(|&(item, _)| {
+                    get_item(database, index, rtxn, item)?
+                        .ok_or_else(|| Error::missing_key(Key::item(index, item)))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            // the relevance is a full-precision distance once rescored, the novelty must be too
+            let rescored = D::full_precision_distance().filter(|_| self.rescore.is_some());
+            let full_precision = match rescored {
+                Some(distance) => {
+                    let vectors = candidates
+                        .iter()
+                        .map(|&(item, _)| self.reader.full_precision_vector(rtxn, item))
+                        .collect::<Result<Vec<_>>>()?;
+                    Some((distance, vectors))
+                }
+                None => None,
+            };
+            let distance = |a: usize, b: usize| match &full_precision {
+                Some((distance, vectors)) => match (&vectors[a], &vectors[b]) {
+                    (Some(a), Some(b)) => distance(a, b),
+                    _ => D::distance(&items[a], &items[b]),
+                },
+                None => D::distance(&items[a], &items[b]),
+            };
+
+            // the distance of each remaining candidate to the closest one already picked
+            let mut novelty = vec![0.0; candidates.len()];
+            let mut remaining: Vec<usize> = (0..candidates.len()).collect();
+            let mut picked = Vec::with_capacity(self.count.min(candidates.len()));
+            while picked.len() < self.count && !remaining.is_empty() {
+                let score = |i: usize| lambda * -candidates[i].1 + (1.0 - lambda) * novelty[i];
+                // the ties go to the closest candidate
+                let (pos, &best) = remaining
+                    .iter()
+                    .enumerate()
+                    .max_by(|(_, &a), (_, &b)| score(a).total_cmp(&score(b)).then(b.cmp(&a)))
+                    .unwrap();
+                remaining.swap_remove(pos);
+                picked.push(candidates[best]);
+
+                for &i in &remaining {
+                    let distance = distance(i, best);
+                    novelty[i] =
+                        if picked.len() == 1 { distance } else { novelty[i].min(distance) };
+                }
+            }
+            Ok(picked)
+        };
+
+        Ok(match found {
+            Completion::Done(done) => Completion::Done(diversify(done)?),
+            Completion::Cancelled(cancelled) => Completion::Cancelled(diversify(cancelled)?),
+        })
+    }
+
+    /// Same as [`Self::rescored`] using the full-precision vector of `item` as the query.
+    #[allow(clippy::type_complexity)]
+    fn rescored_by_item(
+        &self,
+        rtxn: &RoTxn,
+        item: ItemId,
+        found: Option<Completion<Vec<(ItemId, f32)>>>,
+    ) -> Result<Option<Completion<Vec<(ItemId, f32)>>>> {
+        let Some(found) = found else { return Ok(None) };
+        if self.rescore.is_none() || D::full_precision_distance().is_none() {
+            return Ok(Some(found));
+        }
+
+        match self.reader.full_precision_vector(rtxn, item)? {
+            Some(query) => self.rescored(rtxn, &query, found).map(Some),
+            None => Ok(Some(found.map(|mut found| {
+                found.truncate(self.fetched());
+                found
+            }))),
+        }
+    }
 }
 
 enum Completion<T> {
@@ -279,11 +954,36 @@ impl<T> Completion<T> {
     }
 }
 
+/// The buffers of a search, kept around to be reused by the next searches of a same thread.
+#[derive(Default)]
+struct Scratch {
+    /// The items already visited in the current layer.
+    path: RoaringBitmap,
+    /// The search frontier, closest items first.
+    queue: BinaryHeap<(Reverse<OrderedFloat>, ItemId)>,
+    /// The neighbours of the item being expanded that weren't visited yet.
+    neighbours: Vec<ItemId>,
+    /// The work done by the current search.
+    stats: SearchStats,
+}
+
+impl Scratch {
+    /// Forgets the items visited by the previous search, keeping the allocations.
+    fn clear(&mut self) {
+        self.path.clear();
+        self.queue.clear();
+        self.neighbours.clear();
+    }
+}
+
 struct Visitor<'a> {
     pub eps: Vec<ItemId>,
     pub level: usize,
     pub ef: usize,
     pub candidates: Option<&'a RoaringBitmap>,
+    pub radius: Option<f32>,
+    pub two_hop: bool,
+    pub filter_fn: Option<&'a FilterFn<'a>>,
 }
 impl<'a> Visitor<'a> {
     pub fn new(
@@ -292,38 +992,55 @@ impl<'a> Visitor<'a> {
         ef: usize,
         candidates: Option<&'a RoaringBitmap>,
     ) -> Self {
-        Self { eps, level, ef, candidates }
+        Self { eps, level, ef, candidates, radius: None, two_hop: false, filter_fn: None }
     }
 
-    /// Iteratively traverse a given level of the HNSW graph, updating the search path history.
+    /// Returns `true` if the item can be part of the results.
+    fn admits(&self, item: ItemId) -> bool {
+        self.candidates.is_none_or(|c| c.contains(item)) && self.filter_fn.is_none_or(|f| f(item))
+    }
+
+    /// Iteratively traverse a given level of the HNSW graph, updating the search path history in
+    /// the `scratch` buffers.
     /// Returns a Min-Max heap of size ef nearest neighbours to the query in that layer.
+    ///
+    /// When a `radius` is set the beam is widened past `ef` to keep every neighbour within the
+    /// radius, and the traversal only stops once the frontier is further than the radius.
+    ///
+    /// When `two_hop` is set the neighbours that can't be part of the results are replaced by
+    /// their own neighbours that can, so that only the entry points can be outside the results.
     #[allow(clippy::too_many_arguments)]
     pub fn visit<D: Distance>(
         &self,
         query: &Item<D>,
         reader: &Reader<D>,
         rtxn: &RoTxn,
-        path: &mut RoaringBitmap,
+        scratch: &mut Scratch,
         cancel_fn: &impl Fn() -> bool,
     ) -> Result<Completion<MinMaxHeap<ScoredLink>>> {
         use Completion::*;
 
-        let mut search_queue = BinaryHeap::new();
+        let Scratch { path, queue: search_queue, neighbours, stats } = scratch;
+        search_queue.clear();
         let mut res = MinMaxHeap::with_capacity(self.ef);
 
         // Register all entry points as visited and populate candidates
         for &ep in &self.eps[..] {
             let ve = get_item(reader.database, reader.index, rtxn, ep)?.unwrap();
             let dist = D::distance(query, &ve);
+            stats.n_lmdb_lookups += 1;
+            stats.n_distances += 1;
 
             search_queue.push((Reverse(OrderedFloat(dist)), ep));
             path.insert(ep);
 
-            if self.candidates.is_none_or(|c| c.contains(ep)) {
+            if self.admits(ep) {
                 res.push((OrderedFloat(dist), ep));
             }
         }
 
+        let within_radius = |dist: f32| self.radius.is_some_and(|radius| dist <= radius);
+
         // Stop occurs either once we've done at least ef searches and notice no improvements, or
         // when we've exhausted the search queue.
         while let Some(&(Reverse(OrderedFloat(f)), _)) = search_queue.peek() {
@@ -331,33 +1048,60 @@ impl<'a> Visitor<'a> {
                 return Ok(Cancelled(res));
             }
             let f_max = res.peek_max().map(|&(OrderedFloat(d), _)| d).unwrap_or(f32::MAX);
-            if f > f_max {
+            if f > f_max && !within_radius(f) {
                 break;
             }
             let (_, c) = search_queue.pop().unwrap();
+            stats.n_visited += 1;
 
             let Links { links } = get_links(rtxn, reader.database, reader.index, c, self.level)?
                 .expect("Links must exist");
+            stats.n_lmdb_lookups += 1;
 
+            neighbours.clear();
             for point in links.iter() {
                 if !path.insert(point) {
                     continue;
                 }
+                if self.two_hop && !self.admits(point) {
+                    let Some(Links { links: hops }) =
+                        get_links(rtxn, reader.database, reader.index, point, self.level)?
+                    else {
+                        continue;
+                    };
+                    stats.n_lmdb_lookups += 1;
+                    stats.n_two_hops += 1;
+                    neighbours
+                        .extend(hops.iter().filter(|&hop| self.admits(hop) && path.insert(hop)));
+                } else {
+                    neighbours.push(point);
+                }
+            }
+
+            for &point in neighbours.iter() {
                 let dist = D::distance(
                     query,
                     &get_item(reader.database, reader.index, rtxn, point)?.unwrap(),
                 );
+                stats.n_lmdb_lookups += 1;
+                stats.n_distances += 1;
 
                 // The search queue can take points that aren't included in the (optional)
-                // candidates bitmap, but the final result must *not* include them.
-                if res.len() < self.ef || dist < f_max {
+                // candidates bitmap or filtered out, but the final result must *not* include them.
+                if res.len() < self.ef || dist < f_max || within_radius(dist) {
                     search_queue.push((Reverse(OrderedFloat(dist)), point));
-                    if let Some(c) = self.candidates {
-                        if !c.contains(point) {
-                            continue;
-                        }
+                    if !self.admits(point) {
+                        continue;
                     }
-                    if res.len() == self.ef {
+                    if self.radius.is_some() && res.len() >= self.ef {
+                        // only evict the neighbours that are out of the radius
+                        res.push((OrderedFloat(dist), point));
+                        while res.len() > self.ef
+                            && res.peek_max().is_some_and(|&(OrderedFloat(d), _)| !within_radius(d))
+                        {
+                            res.pop_max();
+                        }
+                    } else if res.len() == self.ef {
                         let _ = res.push_pop_max((OrderedFloat(dist), point));
                     } else {
                         res.push((OrderedFloat(dist), point));
@@ -369,6 +1113,95 @@ impl<'a> Visitor<'a> {
     }
 }
 
+/// A lazy iterator over the neighbours of a query, closest first, see
+/// [`QueryBuilder::iter_by_vector`].
+///
+/// The search state is kept between the calls to [`Iterator::next`] so the neighbours already
+/// returned are never searched again.
+pub struct NnsIter<'a, 't, D: Distance> {
+    reader: &'a Reader<D>,
+    rtxn: &'t RoTxn<'t>,
+    query: Item<'a, D>,
+    candidates: Option<Cow<'a, RoaringBitmap>>,
+    filter_fn: Option<Arc<FilterFn<'a>>>,
+    ef: usize,
+    /// The items discovered but not expanded yet, closest first.
+    frontier: BinaryHeap<(Reverse<OrderedFloat>, ItemId)>,
+    /// The `ef` closest candidates discovered but not returned yet.
+    beam: MinMaxHeap<ScoredLink>,
+    /// The candidates that were pushed out of the beam, all further than the ones in the beam.
+    spill: BinaryHeap<Reverse<ScoredLink>>,
+    /// The items already discovered.
+    path: RoaringBitmap,
+}
+
+impl<D: Distance> NnsIter<'_, '_, D> {
+    /// Computes the distance of a newly discovered item and registers it to be expanded later.
+    fn discover(&mut self, item: ItemId) -> Result<()> {
+        if !self.path.insert(item) {
+            return Ok(());
+        }
+
+        let Some(node) = get_item(self.reader.database, self.reader.index, self.rtxn, item)? else {
+            return Ok(());
+        };
+        let dist = OrderedFloat(D::distance(&self.query, &node));
+        self.frontier.push((Reverse(dist), item));
+
+        // Like in the search, items that aren't candidates can be traversed but never returned.
+        if self.candidates.as_ref().is_none_or(|c| c.contains(item))
+            && self.filter_fn.as_ref().is_none_or(|f| f(item))
+        {
+            if self.beam.len() < self.ef {
+                self.beam.push((dist, item));
+            } else {
+                let furthest = self.beam.push_pop_max((dist, item));
+                self.spill.push(Reverse(furthest));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Discovers all the neighbours of `item` in layer 0.
+    fn expand(&mut self, item: ItemId) -> Result<()> {
+        let Links { links } =
+            get_links(self.rtxn, self.reader.database, self.reader.index, item, 0)?
+                .ok_or_else(|| Error::missing_key(Key::links(self.reader.index, item, 0)))?;
+        for neighbour in links.iter() {
+            self.discover(neighbour)?;
+        }
+        Ok(())
+    }
+}
+
+impl<D: Distance> Iterator for NnsIter<'_, '_, D> {
+    type Item = Result<(ItemId, f32)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Same stop condition as the search: once the beam is full, items further than all of
+        // its neighbours can't bring a closer one.
+        while let Some(&(Reverse(OrderedFloat(f)), item)) = self.frontier.peek() {
+            let f_max = self.beam.peek_max().map(|&(OrderedFloat(d), _)| d).unwrap_or(f32::MAX);
+            if self.beam.len() >= self.ef && f > f_max {
+                break;
+            }
+            self.frontier.pop();
+            if let Err(e) = self.expand(item) {
+                return Some(Err(e));
+            }
+        }
+
+        let (OrderedFloat(dist), item) =
+            self.beam.pop_min().or_else(|| self.spill.pop().map(|Reverse(next)| next))?;
+        if let Some(Reverse(next)) = self.spill.pop() {
+            self.beam.push(next);
+        }
+
+        Some(Ok((item, dist)))
+    }
+}
+
 /// A reader over the hannoy hnsw graph
 #[derive(Debug)]
 pub struct Reader<D: Distance> {
@@ -379,6 +1212,8 @@ pub struct Reader<D: Distance> {
     dimensions: usize,
     items: RoaringBitmap,
     version: Version,
+    build_params: Option<BuildParams>,
+    calibration: Option<Calibration>,
     _marker: marker::PhantomData<D>,
 }
 
@@ -415,6 +1250,14 @@ impl<D: Distance> Reader<D> {
             return Err(Error::NeedBuild(index));
         }
 
+        let build_params =
+            database.remap_data_type::<BuildParamsCodec>().get(rtxn, &Key::build_params(index))?;
+        let calibration = if D::needs_calibration() {
+            database.remap_data_type::<CalibrationCodec>().get(rtxn, &Key::calibration(index))?
+        } else {
+            None
+        };
+
         // Hint to the kernel that we'll probably need some vectors in RAM.
         Self::prefetch_graph(rtxn, &database, index, &metadata)?;
 
@@ -426,6 +1269,8 @@ impl<D: Distance> Reader<D> {
             dimensions: metadata.dimensions.try_into().unwrap(),
             items: metadata.items,
             version,
+            build_params,
+            calibration,
             _marker: marker::PhantomData,
         })
     }
@@ -493,7 +1338,11 @@ impl<D: Distance> Reader<D> {
         // Load links and vectors for layers > 0.
         let mut added = RoaringBitmap::new();
         for lvl in (1..=metadata.max_level).rev() {
-            for result in database.remap_data_type::<Bytes>().iter(rtxn)? {
+            let links = database
+                .remap_types::<PrefixCodec, Bytes>()
+                .prefix_iter(rtxn, &Prefix::links(index))?
+                .remap_key_type::<KeyCodec>();
+            for result in links {
                 if available_memory < largest_alloc.load(Ordering::Relaxed) {
                     return Ok(());
                 }
@@ -572,6 +1421,13 @@ impl<D: Distance> Reader<D> {
         self.version
     }
 
+    /// Returns the parameters the graph was built with.
+    ///
+    /// Returns `None` if the index was built with a version of hannoy that didn't persist them.
+    pub fn build_params(&self) -> Option<BuildParams> {
+        self.build_params
+    }
+
     /// Returns the number of nodes in the index. Useful to run an exhaustive search.
     pub fn n_nodes(&self, rtxn: &RoTxn) -> Result<Option<NonZeroUsize>> {
         Ok(NonZeroUsize::new(self.database.len(rtxn)? as usize))
@@ -580,17 +1436,58 @@ impl<D: Distance> Reader<D> {
     /// Returns the vector for item `i` that was previously added.
     pub fn item_vector(&self, rtxn: &RoTxn, item_id: ItemId) -> Result<Option<Vec<f32>>> {
         Ok(get_item(self.database, self.index, rtxn, item_id)?.map(|item| {
-            let mut vec = item.vector.to_vec();
+            let mut vec = D::decode(&item, self.calibration.as_ref());
             vec.truncate(self.dimensions());
             vec
         }))
     }
 
+    /// Returns the full-precision vector of item `i` if it was stored to rescore the search
+    /// results, see [`Writer::set_full_precision`](crate::Writer::set_full_precision).
+    pub fn full_precision_vector(&self, rtxn: &RoTxn, item_id: ItemId) -> Result<Option<Vec<f32>>> {
+        Ok(self
+            .database
+            .remap_data_type::<FullPrecisionCodec>()
+            .get(rtxn, &Key::full_precision(self.index, item_id))?
+            .map(|vector| vector.to_vec()))
+    }
+
     /// Returns `true` if the index is empty.
     pub fn is_empty(&self, rtxn: &RoTxn) -> Result<bool> {
         self.iter(rtxn).map(|mut iter| iter.next().is_none())
     }
 
+    /// Returns the attributes of an item, see [`crate::Writer::set_attributes`].
+    pub fn attributes(&self, rtxn: &RoTxn, item_id: ItemId) -> Result<Option<Attributes>> {
+        attributes::get(self.database, self.index, rtxn, item_id)
+    }
+
+    /// Returns the items of the index matching the `filter`, see [`QueryBuilder::filter`].
+    pub fn filter(&self, rtxn: &RoTxn, filter: &Filter) -> Result<RoaringBitmap> {
+        attributes::resolve(self.database, self.index, rtxn, self.item_ids(), filter)
+    }
+
+    /// Returns the id of the item identified by an external key, see
+    /// [`crate::Writer::add_item_by_key`].
+    pub fn item_id(&self, rtxn: &RoTxn, key: &[u8]) -> Result<Option<ItemId>> {
+        external_keys::get_id(self.database, self.index, rtxn, key)
+    }
+
+    /// Returns the external key of an item, if it was added with one.
+    pub fn external_key(&self, rtxn: &RoTxn, item_id: ItemId) -> Result<Option<Vec<u8>>> {
+        external_keys::get_key(self.database, self.index, rtxn, item_id)
+    }
+
+    /// Replaces the ids of the neighbours found by a search with their external keys, `None` for
+    /// the items added without one.
+    pub fn external_keys(
+        &self,
+        rtxn: &RoTxn,
+        nns: &[(ItemId, f32)],
+    ) -> Result<Vec<(Option<Vec<u8>>, f32)>> {
+        nns.iter().map(|&(item, distance)| Ok((self.external_key(rtxn, item)?, distance))).collect()
+    }
+
     /// Returns `true` if the database contains the given item.
     pub fn contains_item(&self, rtxn: &RoTxn, item_id: ItemId) -> Result<bool> {
         self.database
@@ -602,7 +1499,122 @@ impl<D: Distance> Reader<D> {
 
     /// Returns an iterator over the items vector.
     pub fn iter<'t>(&self, rtxn: &'t RoTxn) -> Result<ItemIter<'t, D>> {
-        ItemIter::new(self.database, self.index, self.dimensions, rtxn).map_err(Into::into)
+        let calibration = self.calibration.clone();
+        ItemIter::new(self.database, self.index, self.dimensions, calibration, rtxn)
+            .map_err(Into::into)
+    }
+
+    /// Writes the vectors of the index to an `.fvecs` file and returns the number of items
+    /// written.
+    ///
+    /// The vectors are written in the order of the item ids. The ids themselves are written to
+    /// the optional id-mapping file, one per line, see the [`vecs`](crate::vecs) module.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{distances::Euclidean, Reader};
+    /// # let (reader, rtxn): (Reader<Euclidean>, heed::RoTxn) = todo!();
+    /// use std::fs::File;
+    /// use std::io::BufWriter;
+    ///
+    /// let vectors = BufWriter::new(File::create("items.fvecs")?);
+    /// let mut ids = BufWriter::new(File::create("items.ids")?);
+    /// reader.export_fvecs(&rtxn, vectors, Some(&mut ids))?;
+    /// # Ok::<(), hannoy::Error>(())
+    /// ```
+    pub fn export_fvecs(
+        &self,
+        rtxn: &RoTxn,
+        output: impl Write,
+        ids: Option<&mut dyn Write>,
+    ) -> Result<u64> {
+        self.export(rtxn, output, ids, |output, vector| {
+            VecsFormat::Fvecs.write_vector(output, vector)
+        })
+    }
+
+    /// Same as [`Self::export_fvecs`] for a `.bvecs` file, the components of the vectors
+    /// must be integers between 0 and 255.
+    pub fn export_bvecs(
+        &self,
+        rtxn: &RoTxn,
+        output: impl Write,
+        ids: Option<&mut dyn Write>,
+    ) -> Result<u64> {
+        self.export(rtxn, output, ids, |output, vector| {
+            VecsFormat::Bvecs.write_vector(output, vector)
+        })
+    }
+
+    /// Same as [`Self::export_fvecs`] for an `.ivecs` file, the components of the vectors
+    /// must be integers that fit in an `i32`.
+    pub fn export_ivecs(
+        &self,
+        rtxn: &RoTxn,
+        output: impl Write,
+        ids: Option<&mut dyn Write>,
+    ) -> Result<u64> {
+        self.export(rtxn, output, ids, |output, vector| {
+            VecsFormat::Ivecs.write_vector(output, vector)
+        })
+    }
+
+    /// Same as [`Self::export_fvecs`] for a `.npy` file storing a two-dimensional array of
+    /// `f32`s, one vector per row.
+    pub fn export_npy(
+        &self,
+        rtxn: &RoTxn,
+        mut output: impl Write,
+        ids: Option<&mut dyn Write>,
+    ) -> Result<u64> {
+        write_npy_header(&mut output, self.n_items() as usize, self.dimensions)?;
+        self.export(rtxn, output, ids, |output, vector| {
+            vector.iter().try_for_each(|x| output.write_all(&x.to_le_bytes()))?;
+            Ok(())
+        })
+    }
+
+    fn export<W: Write>(
+        &self,
+        rtxn: &RoTxn,
+        mut output: W,
+        mut ids: Option<&mut dyn Write>,
+        mut write_vector: impl FnMut(&mut W, &[f32]) -> Result<()>,
+    ) -> Result<u64> {
+        let mut n_items = 0;
+        for result in self.iter(rtxn)? {
+            let (item, vector) = result?;
+            write_vector(&mut output, &vector)?;
+            if let Some(ids) = &mut ids {
+                writeln!(ids, "{item}")?;
+            }
+            n_items += 1;
+        }
+
+        output.flush()?;
+        if let Some(ids) = ids {
+            ids.flush()?;
+        }
+        Ok(n_items)
+    }
+
+    /// Writes the whole index, its metadata, items and graph, to a single checksummed stream that
+    /// doesn't depend on the LMDB page format, see [`Writer::restore`](crate::Writer::restore).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{distances::Euclidean, Reader};
+    /// # let (reader, rtxn): (Reader<Euclidean>, heed::RoTxn) = todo!();
+    /// use std::fs::File;
+    /// use std::io::BufWriter;
+    ///
+    /// reader.dump(&rtxn, BufWriter::new(File::create("index.dump")?))?;
+    /// # Ok::<(), hannoy::Error>(())
+    /// ```
+    pub fn dump(&self, rtxn: &RoTxn, output: impl Write) -> Result<()> {
+        dump::dump(self.database, self.index, rtxn, output)
     }
 
     /// Return a [`QueryBuilder`] that lets you configure and execute a search request.
@@ -612,10 +1624,16 @@ impl<D: Distance> Reader<D> {
         QueryBuilder {
             reader: self,
             candidates: None,
+            filter: None,
+            filter_fn: None,
             count,
             ef: DEFAULT_EF_SEARCH,
             linear_below: DEFAULT_LINEAR_SCAN_THRESHOLD,
             linear_below_ratio: DEFAULT_LINEAR_SCAN_THRESHOLD_RATIO,
+            rescore: None,
+            diversify: None,
+            collect_stats: false,
+            filtered_traversal: false,
         }
     }
 
@@ -644,6 +1662,7 @@ impl<D: Distance> Reader<D> {
         rtxn: &RoTxn,
         query: &Item<D>,
         opt: &QueryBuilder<D>,
+        scratch: &mut Scratch,
         cancel_fn: impl Fn() -> bool,
     ) -> Result<Completion<Vec<(ItemId, f32)>>> {
         use Completion::*;
@@ -657,24 +1676,46 @@ impl<D: Distance> Reader<D> {
 
         // If the number of candidates is less than a given threshold, perform linear search
         if let Some(candidates) = opt.candidates.filter(|_| self.should_linear_scan(opt)) {
-            return self.brute_force_search(query, rtxn, candidates, opt.count, cancel_fn);
+            let stats = &mut scratch.stats;
+            let filter_fn = opt.filter_fn.as_deref();
+            return self.brute_force_search(
+                query, rtxn, candidates, filter_fn, opt.count, stats, cancel_fn,
+            );
         }
 
         // exhaustive search
-        self.hnsw_search(query, rtxn, opt, cancel_fn)
+        self.hnsw_search(query, rtxn, opt, scratch, cancel_fn)
+    }
+
+    /// Returns the exact `count` nearest neighbours of `vector` by scanning every item.
+    pub(crate) fn exact_nns(
+        &self,
+        rtxn: &RoTxn,
+        vector: &[f32],
+        count: usize,
+    ) -> Result<Vec<(ItemId, f32)>> {
+        let query = D::encode(vector, self.calibration.as_ref());
+        let mut stats = SearchStats::default();
+        self.brute_force_search(&query, rtxn, self.item_ids(), None, count, &mut stats, || false)
+            .map(Completion::into_inner)
     }
 
     /// Directly retrieves items in the candidate list and ranks them by distance to the query.
+    #[allow(clippy::too_many_arguments)]
     fn brute_force_search(
         &self,
         query: &Item<D>,
         rtxn: &RoTxn,
         candidates: &RoaringBitmap,
+        filter_fn: Option<&FilterFn>,
         count: usize,
+        stats: &mut SearchStats,
         cancel_fn: impl Fn() -> bool,
     ) -> Result<Completion<Vec<(ItemId, f32)>>> {
         use Completion::*;
 
+        stats.linear_scan = true;
+
         // We set the capacity to the maximum number of
         // candidates we can return as it should be small enough.
         let mut item_distances = BinaryHeap::<(OrderedFloat, _)>::with_capacity(count);
@@ -685,11 +1726,16 @@ impl<D: Distance> Reader<D> {
                 cancelled = true;
                 break;
             }
+            if filter_fn.is_some_and(|f| !f(item_id)) {
+                continue;
+            }
 
-            let Some(vector) = self.item_vector(rtxn, item_id)? else { continue };
-            let vector = UnalignedVector::from_vec(vector);
-            let item = Item { header: D::new_header(&vector), vector };
+            stats.n_lmdb_lookups += 1;
+            let Some(item) = get_item(self.database, self.index, rtxn, item_id)? else {
+                continue;
+            };
             let distance = D::distance(&item, query);
+            stats.n_distances += 1;
 
             // We make sure we maintain the number of items
             // in the heap at a maximum of count elements.
@@ -724,27 +1770,20 @@ impl<D: Distance> Reader<D> {
         query: &Item<D>,
         rtxn: &RoTxn,
         opt: &QueryBuilder<D>,
+        scratch: &mut Scratch,
         cancel_fn: impl Fn() -> bool,
     ) -> Result<Completion<Vec<(ItemId, f32)>>> {
         use Completion::*;
 
         let cancel_fn = &cancel_fn;
-        let mut visitor = Visitor::new(self.entry_points.clone(), self.max_level, 1, None);
-
-        let mut path = RoaringBitmap::new();
-        for _ in (1..=self.max_level).rev() {
-            let neighbours = visitor.visit(query, self, rtxn, &mut path, &|| false)?.into_inner();
-            let closest = neighbours.peek_min().map(|(_, n)| n).expect("No neighbor was found");
-
-            visitor.eps = vec![*closest];
-            visitor.level -= 1;
-        }
+        scratch.clear();
+        let eps = self.descend_to_layer_zero(query, rtxn, scratch)?;
+        let eps = self.seeded(eps, opt);
+        let mut visitor = Visitor::new(eps, 0, opt.ef.max(opt.count), opt.candidates);
+        visitor.two_hop = opt.filtered_traversal;
+        visitor.filter_fn = opt.filter_fn.as_deref();
         // clear visited set as we only care about level 0
-        path.clear();
-        debug_assert!(visitor.level == 0);
-
-        visitor.ef = opt.ef.max(opt.count);
-        visitor.candidates = opt.candidates;
+        scratch.path.clear();
 
         macro_rules! return_if_cancelled {
             ($completion: expr) => {
@@ -764,11 +1803,12 @@ impl<D: Distance> Reader<D> {
         }
 
         let mut neighbours =
-            return_if_cancelled!(visitor.visit(query, self, rtxn, &mut path, cancel_fn)?);
+            return_if_cancelled!(visitor.visit(query, self, rtxn, scratch, cancel_fn)?);
 
         // If we still don't have enough nns (e.g. search encountered cyclic subgraphs) then do exhaustive
         // search over remaining unseen items.
         if neighbours.len() < opt.count {
+            scratch.stats.exhaustive_fallback = true;
             let mut cursor = self
                 .database
                 .remap_types::<PrefixCodec, DecodeIgnore>()
@@ -777,7 +1817,7 @@ impl<D: Distance> Reader<D> {
 
             while let Some((key, _)) = cursor.next().transpose()? {
                 let id = key.node.item;
-                if path.contains(id) {
+                if scratch.path.contains(id) {
                     continue;
                 }
 
@@ -785,9 +1825,9 @@ impl<D: Distance> Reader<D> {
                 visitor.ef = opt.ef.saturating_sub(neighbours.len());
 
                 let more_nns =
-                    return_if_cancelled!(visitor.visit(query, self, rtxn, &mut path, cancel_fn)?);
+                    return_if_cancelled!(visitor.visit(query, self, rtxn, scratch, cancel_fn)?);
 
-                neighbours.extend(more_nns.into_iter());
+                neighbours.extend(more_nns);
                 if neighbours.len() >= opt.ef {
                     break;
                 }
@@ -799,6 +1839,162 @@ impl<D: Distance> Reader<D> {
         Ok(Done(found))
     }
 
+    /// Adds a few items spread over the candidates to the entry points of a filtered traversal,
+    /// see [`QueryBuilder::filtered_traversal`].
+    fn seeded(&self, mut eps: Vec<ItemId>, opt: &QueryBuilder<D>) -> Vec<ItemId> {
+        let Some(candidates) = opt.candidates.filter(|_| opt.filtered_traversal) else {
+            return eps;
+        };
+
+        let candidates = candidates & self.item_ids();
+        let n_seeds = candidates.len().min(FILTERED_TRAVERSAL_SEEDS);
+        for i in 0..n_seeds {
+            let seed = candidates.select((i * candidates.len() / n_seeds) as u32);
+            let admitted = |seed: &ItemId| opt.filter_fn.as_ref().is_none_or(|f| f(*seed));
+            if let Some(seed) = seed.filter(|seed| !eps.contains(seed) && admitted(seed)) {
+                eps.push(seed);
+            }
+        }
+        eps
+    }
+
+    /// Greedily walks down the layers above layer 0 and returns the closest item to the query
+    /// found on the way, which is where the search of layer 0 starts from.
+    fn descend_to_layer_zero(
+        &self,
+        query: &Item<D>,
+        rtxn: &RoTxn,
+        scratch: &mut Scratch,
+    ) -> Result<Vec<ItemId>> {
+        let mut visitor = Visitor::new(self.entry_points.clone(), self.max_level, 1, None);
+
+        for _ in (1..=self.max_level).rev() {
+            let neighbours = visitor.visit(query, self, rtxn, scratch, &|| false)?.into_inner();
+            let closest = neighbours.peek_min().map(|(_, n)| n).expect("No neighbor was found");
+
+            visitor.eps = vec![*closest];
+            visitor.level -= 1;
+        }
+        debug_assert!(visitor.level == 0);
+
+        Ok(visitor.eps)
+    }
+
+    /// Returns every item within `max_distance` of the query, see
+    /// [`QueryBuilder::by_vector_within`].
+    fn nns_within_vec(
+        &self,
+        rtxn: &RoTxn,
+        query: &Item<D>,
+        opt: &QueryBuilder<D>,
+        max_distance: f32,
+    ) -> Result<Vec<(ItemId, f32)>> {
+        let item_ids = self.item_ids();
+
+        // If we will never find any candidates, return an empty vector
+        if item_ids.is_empty() || opt.candidates.is_some_and(|c| item_ids.is_disjoint(c)) {
+            return Ok(Vec::new());
+        }
+
+        // If the number of candidates is less than a given threshold, perform linear search
+        if let Some(candidates) = opt.candidates.filter(|_| self.should_linear_scan(opt)) {
+            let filter_fn = opt.filter_fn.as_deref();
+            return self.brute_force_within(query, rtxn, candidates, filter_fn, max_distance);
+        }
+
+        let mut scratch = Scratch::default();
+        let eps = self.descend_to_layer_zero(query, rtxn, &mut scratch)?;
+        let eps = self.seeded(eps, opt);
+        scratch.path.clear();
+
+        let mut visitor = Visitor::new(eps, 0, opt.ef, opt.candidates);
+        visitor.radius = Some(max_distance);
+        visitor.two_hop = opt.filtered_traversal;
+        visitor.filter_fn = opt.filter_fn.as_deref();
+        let neighbours = visitor.visit(query, self, rtxn, &mut scratch, &|| false)?.into_inner();
+        Ok(Self::take_within(neighbours, max_distance))
+    }
+
+    /// Returns every item within `max_distance` of the item, not including the item itself, see
+    /// [`QueryBuilder::by_item_within`].
+    fn nns_within_item(
+        &self,
+        rtxn: &RoTxn,
+        item: ItemId,
+        opt: &QueryBuilder<D>,
+        max_distance: f32,
+    ) -> Result<Option<Vec<(ItemId, f32)>>> {
+        let item_ids = self.item_ids();
+
+        // If we will never find any candidates, return none
+        if item_ids.is_empty() || opt.candidates.is_some_and(|c| item_ids.is_disjoint(c)) {
+            return Ok(None);
+        }
+
+        let Some(vector) = self.item_vector(rtxn, item)? else { return Ok(None) };
+        let query = D::encode(&vector, self.calibration.as_ref());
+
+        let mut candidates = opt.candidates.unwrap_or(item_ids).clone();
+        candidates.remove(item);
+
+        // If the number of candidates is less than a given threshold, perform linear search
+        if self.should_linear_scan(opt) {
+            let filter_fn = opt.filter_fn.as_deref();
+            return self
+                .brute_force_within(&query, rtxn, &candidates, filter_fn, max_distance)
+                .map(Some);
+        }
+
+        let mut visitor = Visitor::new(vec![item], 0, opt.ef, Some(&candidates));
+        visitor.radius = Some(max_distance);
+        visitor.two_hop = opt.filtered_traversal;
+        visitor.filter_fn = opt.filter_fn.as_deref();
+
+        let mut scratch = Scratch::default();
+        let neighbours = visitor.visit(&query, self, rtxn, &mut scratch, &|| false)?.into_inner();
+        Ok(Some(Self::take_within(neighbours, max_distance)))
+    }
+
+    /// Directly retrieves items in the candidate list and keeps the ones within `max_distance`.
+    fn brute_force_within(
+        &self,
+        query: &Item<D>,
+        rtxn: &RoTxn,
+        candidates: &RoaringBitmap,
+        filter_fn: Option<&FilterFn>,
+        max_distance: f32,
+    ) -> Result<Vec<(ItemId, f32)>> {
+        let mut found = Vec::new();
+        for item_id in candidates {
+            if filter_fn.is_some_and(|f| !f(item_id)) {
+                continue;
+            }
+            let Some(item) = get_item(self.database, self.index, rtxn, item_id)? else {
+                continue;
+            };
+            let distance = D::distance(&item, query);
+            if distance <= max_distance {
+                found.push((item_id, distance));
+            }
+        }
+
+        found.sort_unstable_by_key(|&(item, distance)| (OrderedFloat(distance), item));
+        Ok(found)
+    }
+
+    /// Drains the neighbours found by a [`Visitor`] in ascending order until they get further
+    /// than `max_distance`.
+    fn take_within(
+        mut neighbours: MinMaxHeap<ScoredLink>,
+        max_distance: f32,
+    ) -> Vec<(ItemId, f32)> {
+        neighbours
+            .drain_asc()
+            .take_while(|&(OrderedFloat(f), _)| f <= max_distance)
+            .map(|(OrderedFloat(f), i)| (i, f))
+            .collect()
+    }
+
     /// Returns the nearest points to the item id, not including the point itself.
     ///
     /// Nearly identical behaviour to `Reader.nns_by_vec` except we only search layer 0 and use the
@@ -811,6 +2007,7 @@ impl<D: Distance> Reader<D> {
         rtxn: &RoTxn,
         item: ItemId,
         opt: &QueryBuilder<D>,
+        scratch: &mut Scratch,
         cancel_fn: impl Fn() -> bool,
     ) -> Result<Option<Completion<Vec<(ItemId, f32)>>>> {
         use Completion::*;
@@ -824,22 +2021,29 @@ impl<D: Distance> Reader<D> {
         }
 
         let Some(vector) = self.item_vector(rtxn, item)? else { return Ok(None) };
-        let vector = UnalignedVector::from_vec(vector);
-        let query = Item { header: D::new_header(&vector), vector };
+        let query = D::encode(&vector, self.calibration.as_ref());
+        scratch.stats.n_lmdb_lookups += 1;
 
         // If the number of candidates is less than a given threshold, perform linear search
         if let Some(candidates) = opt.candidates.filter(|_| self.should_linear_scan(opt)) {
-            let nns = self.brute_force_search(&query, rtxn, candidates, opt.count, cancel_fn)?;
+            let stats = &mut scratch.stats;
+            let filter_fn = opt.filter_fn.as_deref();
+            let nns = self.brute_force_search(
+                &query, rtxn, candidates, filter_fn, opt.count, stats, cancel_fn,
+            )?;
             return Ok(Some(nns));
         }
 
         // Search over all items except `item`
+        scratch.clear();
         let ef = opt.ef.max(opt.count);
-        let mut path = RoaringBitmap::new();
         let mut candidates = opt.candidates.unwrap_or_else(|| self.item_ids()).clone();
         candidates.remove(item);
 
-        let mut visitor = Visitor::new(vec![item], 0, ef, Some(&candidates));
+        let eps = self.seeded(vec![item], opt);
+        let mut visitor = Visitor::new(eps, 0, ef, Some(&candidates));
+        visitor.two_hop = opt.filtered_traversal;
+        visitor.filter_fn = opt.filter_fn.as_deref();
 
         macro_rules! return_if_cancelled {
             ($completion: expr) => {
@@ -858,11 +2062,12 @@ impl<D: Distance> Reader<D> {
             };
         }
         let mut neighbours =
-            return_if_cancelled!(visitor.visit(&query, self, rtxn, &mut path, cancel_fn)?);
+            return_if_cancelled!(visitor.visit(&query, self, rtxn, scratch, cancel_fn)?);
 
         // If we still don't have enough nns (e.g. search encountered cyclic subgraphs) then do exhaustive
         // search over remaining unseen items.
         if neighbours.len() < opt.count {
+            scratch.stats.exhaustive_fallback = true;
             let mut cursor = self
                 .database
                 .remap_types::<PrefixCodec, DecodeIgnore>()
@@ -871,7 +2076,7 @@ impl<D: Distance> Reader<D> {
 
             while let Some((key, _)) = cursor.next().transpose()? {
                 let id = key.node.item;
-                if path.contains(id) {
+                if scratch.path.contains(id) {
                     continue;
                 }
 
@@ -880,8 +2085,8 @@ impl<D: Distance> Reader<D> {
                 visitor.ef = opt.count - neighbours.len();
 
                 let more_nns =
-                    return_if_cancelled!(visitor.visit(&query, self, rtxn, &mut path, cancel_fn)?);
-                neighbours.extend(more_nns.into_iter());
+                    return_if_cancelled!(visitor.visit(&query, self, rtxn, scratch, cancel_fn)?);
+                neighbours.extend(more_nns);
                 if neighbours.len() >= opt.count {
                     break;
                 }
@@ -893,6 +2098,69 @@ impl<D: Distance> Reader<D> {
         Ok(Some(Done(found)))
     }
 
+    /// Checks the integrity of the index without panicking, see [`crate::fsck`].
+    pub fn check(&self, rtxn: &RoTxn) -> Result<CheckReport> {
+        crate::fsck(rtxn, self.database, self.index)
+    }
+
+    /// Returns statistics about the shape of the graph: the degree distributions of each layer,
+    /// the connectivity of layer 0 and the size of the links.
+    ///
+    /// This reads every link of the index and is as expensive as a full scan of the graph.
+    pub fn graph_stats(&self, rtxn: &RoTxn) -> Result<GraphStats> {
+        let mut layers = vec![LayerStats::default(); self.max_level + 1];
+        let mut nodes = vec![RoaringBitmap::new(); layers.len()];
+        let mut in_degrees = vec![hashbrown::HashMap::<ItemId, usize>::new(); layers.len()];
+        let mut components = DisjointSets::new(self.items.len() as usize);
+        let (mut n_links, mut links_size) = (0usize, 0usize);
+
+        for result in self
+            .database
+            .remap_types::<PrefixCodec, NodeCodec<D>>()
+            .prefix_iter(rtxn, &Prefix::links(self.index))?
+            .remap_key_type::<KeyCodec>()
+        {
+            let (key, node) = result?;
+            let Some(Links { links }) = node.links() else { continue };
+            let (item_id, lvl) = (key.node.item, key.node.layer as usize);
+
+            if layers.len() <= lvl {
+                layers.resize_with(lvl + 1, Default::default);
+                nodes.resize_with(lvl + 1, Default::default);
+                in_degrees.resize_with(lvl + 1, Default::default);
+            }
+
+            n_links += 1;
+            links_size += links.serialized_size();
+            nodes[lvl].insert(item_id);
+            layers[lvl].record_out_degree(links.len() as usize);
+
+            for other in links.iter() {
+                *in_degrees[lvl].entry(other).or_default() += 1;
+
+                if lvl == 0 && self.items.contains(item_id) && self.items.contains(other) {
+                    let a = self.items.rank(item_id) - 1;
+                    let b = self.items.rank(other) - 1;
+                    components.union(a as u32, b as u32);
+                }
+            }
+        }
+
+        for (layer, (nodes, in_degrees)) in layers.iter_mut().zip(nodes.iter().zip(&in_degrees)) {
+            layer.n_nodes = nodes.len();
+            for item_id in nodes {
+                layer.record_in_degree(in_degrees.get(&item_id).copied().unwrap_or_default());
+            }
+        }
+
+        Ok(GraphStats {
+            layers,
+            layer0_components: components.count(),
+            avg_links_size: if n_links == 0 { 0.0 } else { links_size as f64 / n_links as f64 },
+            entry_points: self.entry_points.clone(),
+        })
+    }
+
     /// NOTE: a [`crate::Reader`] can't be opened unless updates are commited through a build !
     /// Verify that the whole reader is correctly formed:
     /// - All items are linked.
@@ -4,7 +4,11 @@ use super::simple_avx::*;
 use super::simple_neon::*;
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 use super::simple_sse::*;
-use crate::unaligned_vector::{BinaryQuantized, UnalignedVector};
+use half::{bf16, f16};
+
+use crate::unaligned_vector::{
+    BinaryQuantized, ScalarQuantized, UnalignedVector, UnalignedVectorCodec,
+};
 
 #[cfg(target_arch = "x86_64")]
 const MIN_DIM_SIZE_AVX: usize = 32;
@@ -82,6 +86,95 @@ pub fn dot_product_non_optimized(u: &UnalignedVector<f32>, v: &UnalignedVector<f
     u.iter().zip(v.iter()).map(|(a, b)| a * b).sum()
 }
 
+pub fn euclidean_distance_f16(u: &UnalignedVector<f16>, v: &UnalignedVector<f16>) -> f32 {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx")
+            && is_x86_feature_detected!("fma")
+            && is_x86_feature_detected!("f16c")
+            && u.len() >= MIN_DIM_SIZE_AVX
+        {
+            return unsafe { euclid_similarity_f16_avx(u, v) };
+        }
+    }
+
+    euclidean_distance_half_non_optimized(u, v)
+}
+
+pub fn dot_product_f16(u: &UnalignedVector<f16>, v: &UnalignedVector<f16>) -> f32 {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx")
+            && is_x86_feature_detected!("fma")
+            && is_x86_feature_detected!("f16c")
+            && u.len() >= MIN_DIM_SIZE_AVX
+        {
+            return unsafe { dot_similarity_f16_avx(u, v) };
+        }
+    }
+
+    dot_product_half_non_optimized(u, v)
+}
+
+pub fn euclidean_distance_bf16(u: &UnalignedVector<bf16>, v: &UnalignedVector<bf16>) -> f32 {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2")
+            && is_x86_feature_detected!("fma")
+            && u.len() >= MIN_DIM_SIZE_AVX
+        {
+            return unsafe { euclid_similarity_bf16_avx(u, v) };
+        }
+    }
+
+    euclidean_distance_half_non_optimized(u, v)
+}
+
+pub fn dot_product_bf16(u: &UnalignedVector<bf16>, v: &UnalignedVector<bf16>) -> f32 {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2")
+            && is_x86_feature_detected!("fma")
+            && u.len() >= MIN_DIM_SIZE_AVX
+        {
+            return unsafe { dot_similarity_bf16_avx(u, v) };
+        }
+    }
+
+    dot_product_half_non_optimized(u, v)
+}
+
+/// The scalar fallback for the half-precision codecs, the elements are widened to f32 one by one.
+pub fn euclidean_distance_half_non_optimized<C: UnalignedVectorCodec>(
+    u: &UnalignedVector<C>,
+    v: &UnalignedVector<C>,
+) -> f32 {
+    u.iter().zip(v.iter()).map(|(u, v)| (u - v) * (u - v)).sum()
+}
+
+pub fn dot_product_half_non_optimized<C: UnalignedVectorCodec>(
+    u: &UnalignedVector<C>,
+    v: &UnalignedVector<C>,
+) -> f32 {
+    u.iter().zip(v.iter()).map(|(a, b)| a * b).sum()
+}
+
+/// The dot product of two scalar quantized vectors, computed on their integer codes.
+pub fn dot_product_scalar_quantized(
+    u: &UnalignedVector<ScalarQuantized>,
+    v: &UnalignedVector<ScalarQuantized>,
+) -> u32 {
+    u.as_bytes().iter().zip(v.as_bytes()).map(|(u, v)| *u as u32 * *v as u32).sum()
+}
+
+/// The squared euclidean distance of two scalar quantized vectors, computed on their integer codes.
+pub fn euclidean_distance_scalar_quantized(
+    u: &UnalignedVector<ScalarQuantized>,
+    v: &UnalignedVector<ScalarQuantized>,
+) -> u32 {
+    u.as_bytes().iter().zip(v.as_bytes()).map(|(u, v)| u.abs_diff(*v) as u32).map(|d| d * d).sum()
+}
+
 /// For the binary quantized dot product:
 /// 1. We need to multiply two scalars, in our case the only allowed values are -1 and 1:
 /// ```text
@@ -1,6 +1,8 @@
 use std::arch::x86_64::*;
 use std::ptr::read_unaligned;
 
+use half::{bf16, f16};
+
 use crate::unaligned_vector::UnalignedVector;
 
 #[target_feature(enable = "avx")]
@@ -109,6 +111,123 @@ pub(crate) unsafe fn dot_similarity_avx(
     result
 }
 
+/// Generates the dot product and squared euclidean distance kernels of a half-precision codec.
+/// The vectors are read 8 elements at a time and widened to f32 with `$load`.
+macro_rules! half_similarities_avx {
+    ($codec:ty, $load:ident, $euclid:ident, $dot:ident, $($feature:literal),+) => {
+        $(#[target_feature(enable = $feature)])+
+        pub(crate) unsafe fn $euclid(
+            v1: &UnalignedVector<$codec>,
+            v2: &UnalignedVector<$codec>,
+        ) -> f32 {
+            let n = v1.len();
+            let m = n - (n % 32);
+            let mut ptr1 = v1.as_ptr() as *const u16;
+            let mut ptr2 = v2.as_ptr() as *const u16;
+            let mut sum256_1: __m256 = _mm256_setzero_ps();
+            let mut sum256_2: __m256 = _mm256_setzero_ps();
+            let mut sum256_3: __m256 = _mm256_setzero_ps();
+            let mut sum256_4: __m256 = _mm256_setzero_ps();
+            let mut i: usize = 0;
+            while i < m {
+                let sub256_1: __m256 = _mm256_sub_ps($load(ptr1), $load(ptr2));
+                sum256_1 = _mm256_fmadd_ps(sub256_1, sub256_1, sum256_1);
+
+                let sub256_2: __m256 = _mm256_sub_ps($load(ptr1.add(8)), $load(ptr2.add(8)));
+                sum256_2 = _mm256_fmadd_ps(sub256_2, sub256_2, sum256_2);
+
+                let sub256_3: __m256 = _mm256_sub_ps($load(ptr1.add(16)), $load(ptr2.add(16)));
+                sum256_3 = _mm256_fmadd_ps(sub256_3, sub256_3, sum256_3);
+
+                let sub256_4: __m256 = _mm256_sub_ps($load(ptr1.add(24)), $load(ptr2.add(24)));
+                sum256_4 = _mm256_fmadd_ps(sub256_4, sub256_4, sum256_4);
+
+                ptr1 = ptr1.add(32);
+                ptr2 = ptr2.add(32);
+                i += 32;
+            }
+
+            let mut result = hsum256_ps_avx(sum256_1)
+                + hsum256_ps_avx(sum256_2)
+                + hsum256_ps_avx(sum256_3)
+                + hsum256_ps_avx(sum256_4);
+            for i in 0..n - m {
+                let a = <$codec>::from_bits(read_unaligned(ptr1.add(i))).to_f32();
+                let b = <$codec>::from_bits(read_unaligned(ptr2.add(i))).to_f32();
+                result += (a - b).powi(2);
+            }
+            result
+        }
+
+        $(#[target_feature(enable = $feature)])+
+        pub(crate) unsafe fn $dot(v1: &UnalignedVector<$codec>, v2: &UnalignedVector<$codec>) -> f32 {
+            let n = v1.len();
+            let m = n - (n % 32);
+            let mut ptr1 = v1.as_ptr() as *const u16;
+            let mut ptr2 = v2.as_ptr() as *const u16;
+            let mut sum256_1: __m256 = _mm256_setzero_ps();
+            let mut sum256_2: __m256 = _mm256_setzero_ps();
+            let mut sum256_3: __m256 = _mm256_setzero_ps();
+            let mut sum256_4: __m256 = _mm256_setzero_ps();
+            let mut i: usize = 0;
+            while i < m {
+                sum256_1 = _mm256_fmadd_ps($load(ptr1), $load(ptr2), sum256_1);
+                sum256_2 = _mm256_fmadd_ps($load(ptr1.add(8)), $load(ptr2.add(8)), sum256_2);
+                sum256_3 = _mm256_fmadd_ps($load(ptr1.add(16)), $load(ptr2.add(16)), sum256_3);
+                sum256_4 = _mm256_fmadd_ps($load(ptr1.add(24)), $load(ptr2.add(24)), sum256_4);
+
+                ptr1 = ptr1.add(32);
+                ptr2 = ptr2.add(32);
+                i += 32;
+            }
+
+            let mut result = hsum256_ps_avx(sum256_1)
+                + hsum256_ps_avx(sum256_2)
+                + hsum256_ps_avx(sum256_3)
+                + hsum256_ps_avx(sum256_4);
+            for i in 0..n - m {
+                let a = <$codec>::from_bits(read_unaligned(ptr1.add(i))).to_f32();
+                let b = <$codec>::from_bits(read_unaligned(ptr2.add(i))).to_f32();
+                result += a * b;
+            }
+            result
+        }
+    };
+}
+
+/// Loads 8 unaligned f16 and converts them to f32.
+#[target_feature(enable = "avx")]
+#[target_feature(enable = "f16c")]
+unsafe fn load_f16_avx(ptr: *const u16) -> __m256 {
+    _mm256_cvtph_ps(_mm_loadu_si128(ptr as *const __m128i))
+}
+
+/// Loads 8 unaligned bf16 and converts them to f32, a bf16 is the upper half of an f32.
+#[target_feature(enable = "avx2")]
+unsafe fn load_bf16_avx(ptr: *const u16) -> __m256 {
+    let widened = _mm256_cvtepu16_epi32(_mm_loadu_si128(ptr as *const __m128i));
+    _mm256_castsi256_ps(_mm256_slli_epi32(widened, 16))
+}
+
+half_similarities_avx!(
+    f16,
+    load_f16_avx,
+    euclid_similarity_f16_avx,
+    dot_similarity_f16_avx,
+    "avx",
+    "fma",
+    "f16c"
+);
+half_similarities_avx!(
+    bf16,
+    load_bf16_avx,
+    euclid_similarity_bf16_avx,
+    dot_similarity_bf16_avx,
+    "avx",
+    "avx2",
+    "fma"
+);
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -150,4 +269,46 @@ mod tests {
             println!("avx test skipped");
         }
     }
+
+    #[test]
+    fn test_spaces_half_avx() {
+        use super::*;
+        use crate::spaces::simple::*;
+
+        let v1: Vec<f32> = (0..71).map(|i| (i as f32 * 0.37).sin() * 4.0).collect();
+        let v2: Vec<f32> = (0..71).map(|i| (i as f32 * 0.11).cos() * 3.0).collect();
+
+        if is_x86_feature_detected!("avx")
+            && is_x86_feature_detected!("fma")
+            && is_x86_feature_detected!("f16c")
+        {
+            let v1 = UnalignedVector::<f16>::from_slice(&v1[..]);
+            let v2 = UnalignedVector::<f16>::from_slice(&v2[..]);
+
+            let euclid_simd = unsafe { euclid_similarity_f16_avx(&v1, &v2) };
+            let euclid = euclidean_distance_half_non_optimized(&v1, &v2);
+            approx::assert_relative_eq!(euclid_simd, euclid, max_relative = 1e-5);
+
+            let dot_simd = unsafe { dot_similarity_f16_avx(&v1, &v2) };
+            let dot = dot_product_half_non_optimized(&v1, &v2);
+            approx::assert_relative_eq!(dot_simd, dot, max_relative = 1e-5);
+        } else {
+            println!("f16c test skipped");
+        }
+
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+            let v1 = UnalignedVector::<bf16>::from_slice(&v1[..]);
+            let v2 = UnalignedVector::<bf16>::from_slice(&v2[..]);
+
+            let euclid_simd = unsafe { euclid_similarity_bf16_avx(&v1, &v2) };
+            let euclid = euclidean_distance_half_non_optimized(&v1, &v2);
+            approx::assert_relative_eq!(euclid_simd, euclid, max_relative = 1e-5);
+
+            let dot_simd = unsafe { dot_similarity_bf16_avx(&v1, &v2) };
+            let dot = dot_product_half_non_optimized(&v1, &v2);
+            approx::assert_relative_eq!(dot_simd, dot, max_relative = 1e-5);
+        } else {
+            println!("avx2 test skipped");
+        }
+    }
 }
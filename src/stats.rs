@@ -1,38 +1,210 @@
-use std::marker::PhantomData;
+use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-use hashbrown::HashMap;
+use crate::progress::HannoyBuild;
+use crate::ItemId;
 
-use crate::Distance;
-
-// TODO: ignore the phantom
+/// Statistics gathered while building an index, returned by [`crate::HannoyBuilder::build`].
 #[derive(Debug)]
-pub(crate) struct BuildStats<D> {
+pub struct BuildStats {
     /// a counter to see how many times `HnswBuilder.add_link` is invoked
-    pub n_links_added: AtomicUsize,
+    n_links_added: AtomicUsize,
     /// a counter tracking how many times we hit lmdb
-    pub lmdb_hits: AtomicUsize,
+    lmdb_hits: AtomicUsize,
+    /// a counter of the links to deleted items replaced in `fill_gaps_from_deleted`
+    n_links_patched: AtomicUsize,
     /// number of elements per layer
-    pub layer_dist: HashMap<usize, usize>,
-
-    _phantom: PhantomData<D>,
+    layer_dist: BTreeMap<usize, usize>,
+    n_items_inserted: u64,
+    n_items_deleted: u64,
+    /// the phases started so far, with the moment they started at
+    started: Mutex<Vec<(HannoyBuild, Instant)>>,
+    phases: Vec<(HannoyBuild, Duration)>,
 }
 
-impl<D: Distance> BuildStats<D> {
-    pub fn new() -> BuildStats<D> {
+impl BuildStats {
+    pub(crate) fn new() -> BuildStats {
         BuildStats {
             n_links_added: AtomicUsize::new(0),
             lmdb_hits: AtomicUsize::new(0),
-            layer_dist: HashMap::default(),
-            _phantom: PhantomData,
+            n_links_patched: AtomicUsize::new(0),
+            layer_dist: BTreeMap::new(),
+            n_items_inserted: 0,
+            n_items_deleted: 0,
+            started: Mutex::new(Vec::new()),
+            phases: Vec::new(),
         }
     }
 
-    pub fn incr_link_count(&self, val: usize) {
+    pub(crate) fn incr_link_count(&self, val: usize) {
         self.n_links_added.fetch_add(val, Ordering::Relaxed);
     }
 
-    pub fn incr_lmdb_hits(&self) {
+    pub(crate) fn incr_lmdb_hits(&self) {
         self.lmdb_hits.fetch_add(1, Ordering::Relaxed);
     }
+
+    pub(crate) fn incr_links_patched(&self, val: usize) {
+        self.n_links_patched.fetch_add(val, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_layer_len(&mut self, layer: usize, len: usize) {
+        self.layer_dist.insert(layer, len);
+    }
+
+    pub(crate) fn set_updated_items(&mut self, inserted: u64, deleted: u64) {
+        self.n_items_inserted = inserted;
+        self.n_items_deleted = deleted;
+    }
+
+    /// Reports a new phase of the build to the progress and starts timing it.
+    pub(crate) fn step<P: steppe::Progress>(&self, progress: &P, phase: HannoyBuild) {
+        progress.update(phase);
+        self.started.lock().unwrap().push((phase, Instant::now()));
+    }
+
+    /// Stops timing the current phase, must be called once the build is done.
+    pub(crate) fn finish(&mut self) {
+        let started = std::mem::take(self.started.get_mut().unwrap());
+        let ends = started.iter().skip(1).map(|(_, start)| *start).chain(Some(Instant::now()));
+        self.phases
+            .extend(started.iter().zip(ends).map(|(&(phase, start), end)| (phase, end - start)));
+    }
+
+    /// The number of links added to the graph, in both directions.
+    pub fn n_links_added(&self) -> usize {
+        self.n_links_added.load(Ordering::Relaxed)
+    }
+
+    /// The number of times the links of a node were read from LMDB while walking the graph.
+    pub fn lmdb_hits(&self) -> usize {
+        self.lmdb_hits.load(Ordering::Relaxed)
+    }
+
+    /// The number of links to deleted items that were replaced by links to their neighbours.
+    pub fn n_links_patched(&self) -> usize {
+        self.n_links_patched.load(Ordering::Relaxed)
+    }
+
+    /// The number of items inserted on each layer, indexed by the highest layer they're on.
+    pub fn layer_dist(&self) -> &BTreeMap<usize, usize> {
+        &self.layer_dist
+    }
+
+    /// The number of items added or updated by the build.
+    pub fn n_items_inserted(&self) -> u64 {
+        self.n_items_inserted
+    }
+
+    /// The number of items removed by the build.
+    pub fn n_items_deleted(&self) -> u64 {
+        self.n_items_deleted
+    }
+
+    /// The wall-time spent in each phase of the build, in the order they ran.
+    pub fn phases(&self) -> &[(HannoyBuild, Duration)] {
+        &self.phases
+    }
+
+    /// The wall-time of the whole build.
+    pub fn duration(&self) -> Duration {
+        self.phases.iter().map(|(_, duration)| *duration).sum()
+    }
+}
+
+/// Statistics about the shape of a built graph, see [`crate::Reader::graph_stats`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GraphStats {
+    /// The statistics of every layer, starting with layer 0.
+    pub layers: Vec<LayerStats>,
+    /// The number of connected components in layer 0, ignoring the direction of the links.
+    ///
+    /// A healthy graph has a single one.
+    pub layer0_components: u64,
+    /// The average size, in bytes, of the serialized link bitmaps.
+    pub avg_links_size: f64,
+    /// The entry points of the graph.
+    pub entry_points: Vec<ItemId>,
+}
+
+/// Statistics about a single layer of the graph, see [`GraphStats`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LayerStats {
+    /// The number of nodes with links on this layer.
+    pub n_nodes: u64,
+    /// The number of nodes with a given number of outgoing links, indexed by that number.
+    pub out_degrees: Vec<u64>,
+    /// The number of nodes with a given number of incoming links, indexed by that number.
+    pub in_degrees: Vec<u64>,
+}
+
+impl LayerStats {
+    pub(crate) fn record_out_degree(&mut self, degree: usize) {
+        record(&mut self.out_degrees, degree);
+    }
+
+    pub(crate) fn record_in_degree(&mut self, degree: usize) {
+        record(&mut self.in_degrees, degree);
+    }
+}
+
+fn record(histogram: &mut Vec<u64>, degree: usize) {
+    if histogram.len() <= degree {
+        histogram.resize(degree + 1, 0);
+    }
+    histogram[degree] += 1;
+}
+
+/// A union-find over `0..len`, used to count the connected components of a layer.
+pub(crate) struct DisjointSets {
+    parents: Vec<u32>,
+}
+
+impl DisjointSets {
+    pub fn new(len: usize) -> DisjointSets {
+        DisjointSets { parents: (0..len as u32).collect() }
+    }
+
+    fn find(&mut self, mut x: u32) -> u32 {
+        while self.parents[x as usize] != x {
+            // path halving
+            let grandparent = self.parents[self.parents[x as usize] as usize];
+            self.parents[x as usize] = grandparent;
+            x = grandparent;
+        }
+        x
+    }
+
+    pub fn union(&mut self, a: u32, b: u32) {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra != rb {
+            self.parents[ra as usize] = rb;
+        }
+    }
+
+    /// The number of disjoint sets.
+    pub fn count(&self) -> u64 {
+        self.parents.iter().enumerate().filter(|&(i, &p)| i as u32 == p).count() as u64
+    }
+}
+
+/// Counters of the work done by a single query, see [`crate::QueryBuilder::collect_stats`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SearchStats {
+    /// The number of distances computed between the query and an item.
+    pub n_distances: usize,
+    /// The number of nodes whose links were explored while walking the graph.
+    pub n_visited: usize,
+    /// The number of items and links read from LMDB.
+    pub n_lmdb_lookups: usize,
+    /// The number of items that weren't candidates and whose links were explored to reach the
+    /// candidates behind them, see [`crate::QueryBuilder::filtered_traversal`].
+    pub n_two_hops: usize,
+    /// Whether the candidates were few enough to be scanned linearly instead of walking the graph.
+    pub linear_scan: bool,
+    /// Whether the graph walk found too few neighbours and every item not visited yet had to be
+    /// searched from.
+    pub exhaustive_fallback: bool,
 }
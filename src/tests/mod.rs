@@ -1,7 +1,8 @@
 use std::fmt;
 use std::ops::Range;
 
-use heed::types::LazyDecode;
+use heed::byteorder::BE;
+use heed::types::{Bytes, LazyDecode, U32};
 use heed::{Env, EnvOpenOptions, WithTls};
 use rand::distributions::Uniform;
 use rand::rngs::StdRng;
@@ -11,8 +12,14 @@ use tracing_subscriber::fmt::layer;
 use tracing_subscriber::prelude::*;
 use tracing_subscriber::EnvFilter;
 
+use crate::attributes::{AttributesCodec, FieldsCodec, PostingsCodec};
+use crate::calibration::CalibrationCodec;
+use crate::external_keys::KeyIndexCodec;
+use crate::node::FullPrecisionCodec;
 use crate::version::VersionCodec;
-use crate::{Database, Distance, MetadataCodec, NodeCodec, NodeMode, Reader, Writer};
+use crate::{
+    BuildParamsCodec, Database, Distance, MetadataCodec, NodeCodec, NodeMode, Reader, Writer,
+};
 
 mod fuzz;
 mod reader;
@@ -89,6 +96,84 @@ impl<D: Distance> fmt::Display for DatabaseHandle<D> {
                         .unwrap();
                     writeln!(f, "Version: {version:?}")?;
                 }
+                NodeMode::Metadata if key.node.item == 2 => {
+                    let calibration = self
+                        .database
+                        .remap_data_type::<CalibrationCodec>()
+                        .get(&rtxn, &key)
+                        .unwrap()
+                        .unwrap();
+                    writeln!(f, "Calibration: {calibration:?}")?;
+                }
+                NodeMode::Metadata if key.node.item == 3 => {
+                    let fields = self
+                        .database
+                        .remap_data_type::<FieldsCodec>()
+                        .get(&rtxn, &key)
+                        .unwrap()
+                        .unwrap();
+                    writeln!(f, "Attribute fields: {fields:?}")?;
+                }
+                NodeMode::Metadata if key.node.item == 4 => {
+                    let next_key_id = self
+                        .database
+                        .remap_data_type::<U32<BE>>()
+                        .get(&rtxn, &key)
+                        .unwrap()
+                        .unwrap();
+                    writeln!(f, "Next key id: {next_key_id}")?;
+                }
+                NodeMode::Metadata if key.node.item == 5 => {
+                    let params = self
+                        .database
+                        .remap_data_type::<BuildParamsCodec>()
+                        .get(&rtxn, &key)
+                        .unwrap()
+                        .unwrap();
+                    writeln!(f, "Build params: {params:?}")?;
+                }
+                NodeMode::ExternalKey => {
+                    let external_key =
+                        self.database.remap_data_type::<Bytes>().get(&rtxn, &key).unwrap().unwrap();
+                    writeln!(f, "ExternalKey {}: {external_key:?}", key.node.item)?;
+                }
+                NodeMode::KeyIndex => {
+                    let ids = self
+                        .database
+                        .remap_data_type::<KeyIndexCodec>()
+                        .get(&rtxn, &key)
+                        .unwrap()
+                        .unwrap();
+                    writeln!(f, "KeyIndex {}: {ids:?}", key.node.item)?;
+                }
+                NodeMode::FreeId => writeln!(f, "FreeId {}", key.node.item)?,
+                NodeMode::Attributes => {
+                    let attributes = self
+                        .database
+                        .remap_data_type::<AttributesCodec>()
+                        .get(&rtxn, &key)
+                        .unwrap()
+                        .unwrap();
+                    writeln!(f, "Attributes {}: {attributes:?}", key.node.item)?;
+                }
+                NodeMode::Postings => {
+                    let postings = self
+                        .database
+                        .remap_data_type::<PostingsCodec>()
+                        .get(&rtxn, &key)
+                        .unwrap()
+                        .unwrap();
+                    writeln!(f, "Postings {}({}): {postings:?}", key.node.item, key.node.layer)?;
+                }
+                NodeMode::FullPrecision => {
+                    let vector = self
+                        .database
+                        .remap_data_type::<FullPrecisionCodec>()
+                        .get(&rtxn, &key)
+                        .unwrap()
+                        .unwrap();
+                    writeln!(f, "FullPrecision {}: {vector:?}", key.node.item)?;
+                }
                 NodeMode::Updated | NodeMode::Metadata => {
                     unreachable!("Mode must be an Updated or Metadata")
                 }
@@ -135,7 +220,7 @@ fn create_database_indices_with_items<
             let vector: [f32; DIM] = std::array::from_fn(|_| thread_rng().sample(unif));
             writer.add_item(&mut wtxn, i as u32, &vector).unwrap();
         }
-        writer.builder(rng).build::<M, M0>(&mut wtxn).unwrap();
+        writer.builder(rng).m(M).m0(M0).build(&mut wtxn).unwrap();
     }
 
     wtxn.commit().unwrap();
@@ -1,3 +1,7 @@
+use std::borrow::Cow;
+
+use heed::types::Bytes;
+use heed::{BytesEncode, EnvOpenOptions};
 #[cfg(not(windows))]
 use proptest::prelude::*;
 use rand::rngs::StdRng;
@@ -5,9 +9,13 @@ use rand::seq::SliceRandom;
 use rand::{thread_rng, Rng, SeedableRng};
 use roaring::RoaringBitmap;
 
-use crate::distance::{BinaryQuantizedCosine, Cosine};
+use crate::distance::{BinaryQuantizedCosine, Cosine, DotProduct, Euclidean};
+use crate::key::{Key, KeyCodec, Prefix, PrefixCodec};
+use crate::node::{Item, Links, Node};
 use crate::tests::{create_database, create_database_indices_with_items, rng, DatabaseHandle};
-use crate::{Reader, Writer};
+use crate::{
+    Attributes, Database, Distance, Error, Filter, ItemId, MultiReader, Reader, Searched, Writer,
+};
 
 const M: usize = 16;
 const M0: usize = 32;
@@ -27,7 +35,7 @@ fn quantized_iter_has_right_dimensions() {
     let mut vec = [0f32; DIM];
     rng.fill(&mut vec);
     writer.add_item(&mut wtxn, 0, &vec).unwrap();
-    writer.builder(&mut rng).build::<M, M0>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     let rtxn = env.read_txn().unwrap();
@@ -168,3 +176,904 @@ fn search_cancellation_works() {
     let searched = reader.nns(10).by_item_with_cancellation(&rtxn, 0, || true).unwrap().unwrap();
     assert!(searched.did_cancel());
 }
+
+#[test]
+fn dot_product_ranks_by_inner_product() {
+    const DIM: usize = 32;
+    let mut rng = rng();
+
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<DotProduct>();
+    let writer = Writer::new(database, 0, DIM);
+
+    // Vectors with very different magnitudes, the biggest ones are added in a second build to
+    // make sure the headers of the already indexed items are updated, the smallest ones in a
+    // third build to make sure only them are linked.
+    let vectors: Vec<[f32; DIM]> = (0..200)
+        .map(|i| {
+            let scale = 1.0 + (i % 10) as f32;
+            std::array::from_fn(|_| rng.gen_range(-1.0..1.0) * scale)
+        })
+        .collect();
+
+    // the biggest norm grows in the second build, the items already linked are relinked in the
+    // new geometry
+    for (range, scale, n_items) in [(0..150, 1.0, 150), (150..180, 2.0, 180), (180..200, 0.5, 20)] {
+        let mut wtxn = env.write_txn().unwrap();
+        for i in range {
+            let vector = vectors[i].map(|x| x * scale);
+            writer.add_item(&mut wtxn, i as u32, &vector).unwrap();
+        }
+        let stats = writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+        assert_eq!(stats.n_items_inserted(), n_items);
+        wtxn.commit().unwrap();
+    }
+
+    let rtxn = env.read_txn().unwrap();
+    let reader = Reader::<DotProduct>::open(&rtxn, 0, database).unwrap();
+    let query: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+
+    let mut expected: Vec<_> = reader
+        .iter(&rtxn)
+        .unwrap()
+        .map(|res| {
+            let (id, vector) = res.unwrap();
+            (id, vector.iter().zip(&query).map(|(a, b)| a * b).sum::<f32>())
+        })
+        .collect();
+    expected.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    // hnsw search
+    let found = reader.nns(10).ef_search(200).by_vector(&rtxn, &query).unwrap().into_nns();
+    let found: Vec<_> = found.into_iter().map(|(id, _)| id).collect();
+    let expected: Vec<_> = expected.into_iter().take(10).map(|(id, _)| id).collect();
+    assert_eq!(found, expected);
+
+    // linear scan over the candidates
+    let candidates = RoaringBitmap::from_iter(0..200);
+    let found = reader.nns(10).candidates(&candidates).by_vector(&rtxn, &query).unwrap();
+    let found: Vec<_> = found.into_nns().into_iter().map(|(id, _)| id).collect();
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn rescore_binary_quantized_with_full_precision() {
+    const DIM: usize = 64;
+    const N: u32 = 300;
+    let mut rng = rng();
+
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<BinaryQuantizedCosine>();
+    let mut writer = Writer::new(database, 0, DIM);
+    writer.set_full_precision(true);
+
+    let mut wtxn = env.write_txn().unwrap();
+    let vectors: Vec<[f32; DIM]> =
+        (0..N).map(|_| std::array::from_fn(|_| rng.gen_range(-1.0..1.0))).collect();
+    for (i, vector) in vectors.iter().enumerate() {
+        writer.add_item(&mut wtxn, i as u32, vector).unwrap();
+    }
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    wtxn.commit().unwrap();
+
+    let rtxn = env.read_txn().unwrap();
+    let reader = Reader::open(&rtxn, 0, database).unwrap();
+    assert_eq!(reader.full_precision_vector(&rtxn, 3).unwrap().as_deref(), Some(&vectors[3][..]));
+
+    let query: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+    let cosine = |v: &[f32]| Cosine::distance(&Item::from_slice(&query), &Item::from_slice(v));
+    let mut expected: Vec<_> =
+        vectors.iter().enumerate().map(|(i, v)| (i as u32, cosine(v))).collect();
+    expected.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    expected.truncate(10);
+
+    // oversampling every candidate gives back the exact ranking
+    let candidates = RoaringBitmap::from_iter(0..N);
+    let found = reader
+        .nns(10)
+        .candidates(&candidates)
+        .linear_below(N as usize + 1)
+        .rescore(N as usize / 10)
+        .by_vector(&rtxn, &query)
+        .unwrap()
+        .into_nns();
+    assert_eq!(found, expected);
+
+    // the hnsw results are ranked and scored with the full-precision distance
+    let found = reader.nns(10).rescore(4).by_vector(&rtxn, &query).unwrap().into_nns();
+    assert_eq!(found.len(), 10);
+    assert!(found.is_sorted_by(|(_, a), (_, b)| a <= b));
+    for (id, distance) in found {
+        assert_eq!(distance, cosine(&vectors[id as usize]));
+    }
+
+    let found = reader.nns(10).rescore(4).by_item(&rtxn, 0).unwrap().unwrap().into_nns();
+    assert_eq!(found.len(), 10);
+    assert!(found.iter().all(|&(id, _)| id != 0));
+    drop(rtxn);
+
+    // deleting an item also deletes its full-precision vector
+    let mut wtxn = env.write_txn().unwrap();
+    writer.del_item(&mut wtxn, 3).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    let reader = Reader::open(&wtxn, 0, database).unwrap();
+    assert_eq!(reader.full_precision_vector(&wtxn, 3).unwrap(), None);
+}
+
+#[test]
+fn diversify_rescored_results_with_full_precision() {
+    const DIM: usize = 32;
+    const N: u32 = 200;
+    const FETCH_K: usize = 30;
+    let mut rng = rng();
+
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<BinaryQuantizedCosine>();
+    let mut writer = Writer::new(database, 0, DIM);
+    writer.set_full_precision(true);
+
+    let mut wtxn = env.write_txn().unwrap();
+    let vectors: Vec<[f32; DIM]> =
+        (0..N).map(|_| std::array::from_fn(|_| rng.gen_range(-1.0..1.0))).collect();
+    for (i, vector) in vectors.iter().enumerate() {
+        writer.add_item(&mut wtxn, i as u32, vector).unwrap();
+    }
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    wtxn.commit().unwrap();
+
+    let rtxn = env.read_txn().unwrap();
+    let reader = Reader::open(&rtxn, 0, database).unwrap();
+    let query: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+    let cosine =
+        |p: &[f32], q: &[f32]| Cosine::distance(&Item::from_slice(p), &Item::from_slice(q));
+
+    // the exact candidates, then the picks of MMR computed with the full-precision vectors only
+    let candidates = RoaringBitmap::from_iter(0..N);
+    let mut fetched: Vec<_> =
+        vectors.iter().enumerate().map(|(i, v)| (i as u32, cosine(&query, v))).collect();
+    fetched.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    fetched.truncate(FETCH_K);
+    let lambda = 0.5;
+    let mut expected: Vec<(ItemId, f32)> = Vec::new();
+    let mut remaining = fetched;
+    while expected.len() < 5 {
+        let score = |&(item, relevance): &(ItemId, f32)| {
+            let novelty = expected
+                .iter()
+                .map(|&(p, _)| cosine(&vectors[item as usize], &vectors[p as usize]))
+                .fold(f32::INFINITY, f32::min);
+            let novelty = if expected.is_empty() { 0.0 } else { novelty };
+            lambda * -relevance + (1.0 - lambda) * novelty
+        };
+        let best = (0..remaining.len())
+            .max_by(|&a, &b| score(&remaining[a]).total_cmp(&score(&remaining[b])).then(b.cmp(&a)))
+            .unwrap();
+        expected.push(remaining.remove(best));
+    }
+
+    let found = reader
+        .nns(5)
+        .candidates(&candidates)
+        .linear_below(N as usize + 1)
+        .rescore(N as usize / 10)
+        .diversify(lambda, FETCH_K)
+        .by_vector(&rtxn, &query)
+        .unwrap()
+        .into_nns();
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn rescore_without_full_precision_keeps_quantized_distances() {
+    const DIM: usize = 16;
+    let mut rng = rng();
+
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<BinaryQuantizedCosine>();
+    let mut writer = Writer::new(database, 0, DIM);
+
+    let mut wtxn = env.write_txn().unwrap();
+    writer.set_full_precision(true);
+    writer.add_item(&mut wtxn, 0, &[1.0; DIM]).unwrap();
+    // updating an item without its full-precision vector deletes the previous one
+    writer.set_full_precision(false);
+    for i in 0..20 {
+        let vector: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+        writer.add_item(&mut wtxn, i, &vector).unwrap();
+    }
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    wtxn.commit().unwrap();
+
+    let rtxn = env.read_txn().unwrap();
+    let reader = Reader::open(&rtxn, 0, database).unwrap();
+    assert_eq!(reader.full_precision_vector(&rtxn, 0).unwrap(), None);
+
+    let expected = reader.nns(5).by_vector(&rtxn, &[0.5; DIM]).unwrap().into_nns();
+    let found = reader.nns(5).rescore(2).by_vector(&rtxn, &[0.5; DIM]).unwrap().into_nns();
+    assert_eq!(found, expected);
+    let expected = reader.nns(5).by_item(&rtxn, 0).unwrap().unwrap().into_nns();
+    let found = reader.nns(5).rescore(2).by_item(&rtxn, 0).unwrap().unwrap().into_nns();
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn range_search_widens_past_ef() {
+    const DIM: usize = 8;
+    const NOISE: u32 = 500;
+    const CLUSTER: u32 = 150;
+    let mut rng = rng();
+
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<Euclidean>();
+    let writer = Writer::new(database, 0, DIM);
+
+    // a tight cluster of near-duplicates lost among random vectors
+    let center = [5.0; DIM];
+    let mut vectors: Vec<[f32; DIM]> =
+        (0..NOISE).map(|_| std::array::from_fn(|_| rng.gen_range(-1.0..1.0))).collect();
+    vectors.extend((0..CLUSTER).map(|_| center.map(|x| x + rng.gen_range(-0.01..0.01))));
+
+    let mut wtxn = env.write_txn().unwrap();
+    for (i, vector) in vectors.iter().enumerate() {
+        writer.add_item(&mut wtxn, i as u32, vector).unwrap();
+    }
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    wtxn.commit().unwrap();
+
+    let rtxn = env.read_txn().unwrap();
+    let reader = Reader::<Euclidean>::open(&rtxn, 0, database).unwrap();
+    let distance =
+        |a: &[f32], b: &[f32]| Euclidean::distance(&Item::from_slice(a), &Item::from_slice(b));
+    let radius = distance(&center, &center.map(|x| x + 0.02));
+    let within = |query: &[f32], candidates: &RoaringBitmap| {
+        let mut expected: Vec<_> = candidates
+            .iter()
+            .map(|i| (i, distance(query, &vectors[i as usize])))
+            .filter(|&(_, d)| d <= radius)
+            .collect();
+        expected.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        expected.into_iter().map(|(i, _)| i).collect::<Vec<_>>()
+    };
+    let ids = |found: Vec<(ItemId, f32)>| found.into_iter().map(|(i, _)| i).collect::<Vec<_>>();
+
+    // the beam is much narrower than the number of items in the radius
+    let all = RoaringBitmap::from_iter(0..NOISE + CLUSTER);
+    let found = reader.nns(0).ef_search(10).by_vector_within(&rtxn, &center, radius).unwrap();
+    assert!(found.nns.iter().all(|&(_, d)| d <= radius));
+    assert_eq!(ids(found.into_nns()), within(&center, &all));
+
+    // searching by item never returns the item itself
+    let query = NOISE + 3;
+    let mut others = all.clone();
+    others.remove(query);
+    let found = reader.nns(0).ef_search(10).by_item_within(&rtxn, query, radius).unwrap();
+    let found = ids(found.unwrap().into_nns());
+    assert_eq!(found, within(&vectors[query as usize], &others));
+
+    // a small enough candidates set falls back to a linear scan
+    let candidates = RoaringBitmap::from_iter((0..NOISE + CLUSTER).step_by(7));
+    let found =
+        reader.nns(0).candidates(&candidates).by_vector_within(&rtxn, &center, radius).unwrap();
+    assert_eq!(ids(found.into_nns()), within(&center, &candidates));
+
+    // nothing is close to a far away query
+    let found = reader.nns(0).by_vector_within(&rtxn, &[-100.0; DIM], radius).unwrap();
+    assert!(found.into_nns().is_empty());
+}
+
+#[test]
+fn batch_queries_match_single_queries() {
+    const DIM: usize = 16;
+    let mut rng = rng();
+
+    let dir = tempfile::tempdir().unwrap();
+    let env = unsafe {
+        EnvOpenOptions::new().read_txn_without_tls().map_size(200 * 1024 * 1024).open(dir.path())
+    }
+    .unwrap();
+    let mut wtxn = env.write_txn().unwrap();
+    let database: Database<Euclidean> = env.create_database(&mut wtxn, None).unwrap();
+    let writer = Writer::new(database, 0, DIM);
+    for i in 0..300 {
+        let vector: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+        writer.add_item(&mut wtxn, i, &vector).unwrap();
+    }
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    wtxn.commit().unwrap();
+
+    let rtxn = env.read_txn().unwrap();
+    let reader = Reader::<Euclidean>::open(&rtxn, 0, database).unwrap();
+    let queries: Vec<[f32; DIM]> =
+        (0..64).map(|_| std::array::from_fn(|_| rng.gen_range(-1.0..1.0))).collect();
+
+    let searched = reader.nns(10).by_vectors(&env, &rtxn, &queries).unwrap();
+    assert_eq!(searched.len(), queries.len());
+    for (query, searched) in queries.iter().zip(searched) {
+        let expected = reader.nns(10).by_vector(&rtxn, query).unwrap();
+        assert_eq!(searched.into_nns(), expected.into_nns());
+    }
+    assert!(reader.nns(10).by_vectors::<[f32; DIM]>(&env, &rtxn, &[]).unwrap().is_empty());
+
+    // the workers can't search a version of the database the reader doesn't know about
+    let mut wtxn = env.write_txn().unwrap();
+    writer.del_item(&mut wtxn, 0).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    wtxn.commit().unwrap();
+
+    let err = reader.nns(10).by_vectors(&env, &rtxn, &queries).unwrap_err();
+    assert!(matches!(err, Error::OutdatedReader), "{err}");
+}
+
+#[test]
+fn batch_queries_start_from_clean_buffers() {
+    const DIM: usize = 8;
+    let mut rng = rng();
+
+    let dir = tempfile::tempdir().unwrap();
+    let env = unsafe {
+        EnvOpenOptions::new().read_txn_without_tls().map_size(200 * 1024 * 1024).open(dir.path())
+    }
+    .unwrap();
+    let mut wtxn = env.write_txn().unwrap();
+    let database: Database<Euclidean> = env.create_database(&mut wtxn, None).unwrap();
+    let writer = Writer::new(database, 0, DIM);
+    for i in 0..5000 {
+        let vector: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+        writer.add_item(&mut wtxn, i, &vector).unwrap();
+    }
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    wtxn.commit().unwrap();
+
+    // with a small beam the items visited by a previous query would hide the closest ones
+    let rtxn = env.read_txn().unwrap();
+    let reader = Reader::<Euclidean>::open(&rtxn, 0, database).unwrap();
+    let queries: Vec<[f32; DIM]> =
+        (0..256).map(|_| std::array::from_fn(|_| rng.gen_range(-1.0..1.0))).collect();
+
+    let searched = reader.nns(5).ef_search(8).by_vectors(&env, &rtxn, &queries).unwrap();
+    for (query, searched) in queries.iter().zip(searched) {
+        let expected = reader.nns(5).ef_search(8).by_vector(&rtxn, query).unwrap();
+        assert_eq!(searched.into_nns(), expected.into_nns());
+    }
+}
+
+#[test]
+fn iterate_over_neighbours_page_by_page() {
+    const DIM: usize = 8;
+    const N: u32 = 500;
+    let mut rng = rng();
+
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<Euclidean>();
+    let writer = Writer::new(database, 0, DIM);
+    let vectors: Vec<[f32; DIM]> =
+        (0..N).map(|_| std::array::from_fn(|_| rng.gen_range(-1.0..1.0))).collect();
+
+    let mut wtxn = env.write_txn().unwrap();
+    for (i, vector) in vectors.iter().enumerate() {
+        writer.add_item(&mut wtxn, i as u32, vector).unwrap();
+    }
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    wtxn.commit().unwrap();
+
+    let rtxn = env.read_txn().unwrap();
+    let reader = Reader::<Euclidean>::open(&rtxn, 0, database).unwrap();
+    let query: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+    let mut expected: Vec<_> = (0..N)
+        .map(|i| {
+            (
+                i,
+                Euclidean::distance(
+                    &Item::from_slice(&query),
+                    &Item::from_slice(&vectors[i as usize]),
+                ),
+            )
+        })
+        .collect();
+    expected.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    let expected: Vec<ItemId> = expected.into_iter().map(|(i, _)| i).collect();
+
+    // the first pages are the exact nearest neighbours
+    let mut iter = reader.nns(0).iter_by_vector(&rtxn, &query).unwrap();
+    let first: Vec<_> = iter.by_ref().take(20).map(|res| res.unwrap().0).collect();
+    let second: Vec<_> = iter.by_ref().take(20).map(|res| res.unwrap().0).collect();
+    assert_eq!(first, expected[..20]);
+    assert_eq!(second, expected[20..40]);
+
+    // and we eventually get every item exactly once
+    let mut seen = RoaringBitmap::from_iter(first.into_iter().chain(second));
+    for res in iter {
+        assert!(seen.insert(res.unwrap().0));
+    }
+    assert_eq!(seen.len(), N as u64);
+
+    // the candidates are ranked all at once when there are few of them
+    let candidates = RoaringBitmap::from_iter((0..N).step_by(10));
+    let found: Vec<_> = reader
+        .nns(0)
+        .candidates(&candidates)
+        .iter_by_vector(&rtxn, &query)
+        .unwrap()
+        .map(|res| res.unwrap().0)
+        .collect();
+    let expected: Vec<_> = expected.into_iter().filter(|i| candidates.contains(*i)).collect();
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn check_reports_corruptions_without_panicking() {
+    const DIM: usize = 4;
+    let mut rng = rng();
+
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<Euclidean>();
+    let writer = Writer::new(database, 0, DIM);
+    let mut wtxn = env.write_txn().unwrap();
+    for i in 0..20 {
+        let vector: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+        writer.add_item(&mut wtxn, i, &vector).unwrap();
+    }
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+
+    let reader = Reader::<Euclidean>::open(&wtxn, 0, database).unwrap();
+    assert!(reader.check(&wtxn).unwrap().is_ok());
+
+    // a truncated version, garbage in an item, a missing links entry, an item deleted behind
+    // the writer's back and an item added but not indexed
+    database.remap_data_type::<Bytes>().put(&mut wtxn, &Key::version(0), &[0, 1]).unwrap();
+    database.remap_data_type::<Bytes>().put(&mut wtxn, &Key::item(0, 3), &[1]).unwrap();
+    database.delete(&mut wtxn, &Key::links(0, 5, 0)).unwrap();
+    database.delete(&mut wtxn, &Key::item(0, 7)).unwrap();
+    writer.add_item(&mut wtxn, 100, &[0.0; DIM]).unwrap();
+
+    let report = crate::fsck(&wtxn, database, 0).unwrap();
+    assert!(!report.is_ok());
+    assert!(!report.missing_metadata);
+    assert_eq!(report.undecodable_nodes.len(), 2, "{report:?}");
+    assert_eq!(
+        report.undecodable_nodes[0].key,
+        KeyCodec::bytes_encode(&Key::version(0)).unwrap().to_vec()
+    );
+    assert_eq!(
+        report.undecodable_nodes[1].key,
+        KeyCodec::bytes_encode(&Key::item(0, 3)).unwrap().to_vec()
+    );
+    assert_eq!(report.missing_items, RoaringBitmap::from_iter([3, 7]));
+    assert_eq!(report.unindexed_items, RoaringBitmap::from_iter([100]));
+    assert_eq!(report.updated_items, RoaringBitmap::from_iter([100]));
+    assert_eq!(report.unlinked_items, RoaringBitmap::from_iter([5, 100]));
+    assert!(report.orphan_links.contains(&(3, 0)));
+    assert!(report.orphan_links.contains(&(7, 0)));
+    assert!(report
+        .dangling_links
+        .iter()
+        .all(|(_, _, dangling)| dangling.is_subset(&RoaringBitmap::from_iter([3, 7]))));
+    assert!(!report.dangling_links.is_empty());
+
+    // an index that was never built has no metadata
+    assert!(crate::fsck(&wtxn, database, 1).unwrap().missing_metadata);
+}
+
+#[test]
+fn graph_stats_describe_the_graph() {
+    const DIM: usize = 8;
+    let mut rng = rng();
+    let DatabaseHandle { env, database, tempdir: _ } =
+        create_database_indices_with_items::<Euclidean, DIM, M, M0, _>(0..1, 200, &mut rng);
+    let rtxn = env.read_txn().unwrap();
+    let reader = Reader::<Euclidean>::open(&rtxn, 0, database).unwrap();
+
+    let stats = reader.graph_stats(&rtxn).unwrap();
+    assert_eq!(stats.layers[0].n_nodes, 200);
+    assert_eq!(stats.layer0_components, 1);
+    assert_eq!(stats.entry_points.len(), reader.n_entrypoints());
+    assert!(stats.avg_links_size > 0.0);
+    for layer in &stats.layers {
+        assert_eq!(layer.out_degrees.iter().sum::<u64>(), layer.n_nodes);
+        assert_eq!(layer.in_degrees.iter().sum::<u64>(), layer.n_nodes);
+        let n_links = |histogram: &[u64]| -> u64 {
+            histogram.iter().enumerate().map(|(degree, n)| degree as u64 * n).sum()
+        };
+        assert_eq!(n_links(&layer.out_degrees), n_links(&layer.in_degrees));
+        assert!(layer.out_degrees.len() <= M0 + 1);
+    }
+    drop(rtxn);
+
+    // an item without any links is a component on its own
+    let mut wtxn = env.write_txn().unwrap();
+    for layer in 0..stats.layers.len() as u8 {
+        database.delete(&mut wtxn, &Key::links(0, 42, layer)).unwrap();
+    }
+    let links: Vec<_> = database
+        .remap_key_type::<PrefixCodec>()
+        .prefix_iter(&wtxn, &Prefix::links(0))
+        .unwrap()
+        .remap_key_type::<KeyCodec>()
+        .map(|result| result.map(|(key, node)| (key, node.links().unwrap().links.into_owned())))
+        .collect::<heed::Result<_>>()
+        .unwrap();
+    for (key, mut links) in links {
+        if links.remove(42) {
+            let links = Links { links: Cow::Owned(links) };
+            database.put(&mut wtxn, &key, &Node::Links(links)).unwrap();
+        }
+    }
+
+    let stats = reader.graph_stats(&wtxn).unwrap();
+    assert_eq!(stats.layers[0].n_nodes, 199);
+    assert_eq!(stats.layer0_components, 2);
+}
+
+#[test]
+fn search_stats_tell_how_the_query_ran() {
+    const DIM: usize = 8;
+    let mut rng = rng();
+    let DatabaseHandle { env, database, tempdir: _ } =
+        create_database_indices_with_items::<Euclidean, DIM, M, M0, _>(0..1, 500, &mut rng);
+    let rtxn = env.read_txn().unwrap();
+    let reader = Reader::<Euclidean>::open(&rtxn, 0, database).unwrap();
+    let query: [f32; DIM] = std::array::from_fn(|_| rng.gen());
+
+    // not collected unless asked for
+    assert_eq!(reader.nns(10).by_vector(&rtxn, &query).unwrap().stats(), None);
+
+    let searched = reader.nns(10).collect_stats(true).by_vector(&rtxn, &query).unwrap();
+    let stats = searched.stats.unwrap();
+    assert!(!stats.linear_scan);
+    assert!(!stats.exhaustive_fallback);
+    assert!(stats.n_visited > 0);
+    assert!(stats.n_distances >= stats.n_visited);
+    assert!(stats.n_lmdb_lookups >= stats.n_distances);
+
+    // a larger beam costs more work
+    let wider = reader.nns(10).ef_search(400).collect_stats(true).by_vector(&rtxn, &query).unwrap();
+    assert!(wider.stats.unwrap().n_distances > stats.n_distances);
+
+    // few candidates are scanned linearly
+    let candidates = RoaringBitmap::from_iter(0..20);
+    let searched = reader
+        .nns(10)
+        .candidates(&candidates)
+        .collect_stats(true)
+        .by_vector(&rtxn, &query)
+        .unwrap();
+    let stats = searched.stats.unwrap();
+    assert!(stats.linear_scan);
+    assert_eq!(stats.n_distances, 20);
+    assert_eq!(stats.n_visited, 0);
+
+    // asking for more neighbours than the beam can hold falls back to an exhaustive search
+    let searched = reader.nns(500).ef_search(10).collect_stats(true).by_item(&rtxn, 0).unwrap();
+    let searched = searched.unwrap();
+    assert_eq!(searched.nns.len(), 499);
+    assert!(searched.stats.unwrap().exhaustive_fallback);
+}
+
+#[test]
+fn evaluate_recall_over_ef_searches() {
+    const DIM: usize = 8;
+    let mut rng = rng();
+    let DatabaseHandle { env, database, tempdir: _ } =
+        create_database_indices_with_items::<Euclidean, DIM, M, M0, _>(0..1, 300, &mut rng);
+    let rtxn = env.read_txn().unwrap();
+    let reader = Reader::<Euclidean>::open(&rtxn, 0, database).unwrap();
+
+    let reports = crate::eval::Evaluator::new(&reader)
+        .n_queries(20)
+        .k(10)
+        .ef_searches(vec![10, 300])
+        .run(&rtxn, &mut rng)
+        .unwrap();
+
+    assert_eq!(reports.iter().map(|r| r.ef_search).collect::<Vec<_>>(), vec![10, 300]);
+    for report in &reports {
+        assert_eq!(report.n_queries, 20);
+        assert!((0.0..=1.0).contains(&report.recall));
+        assert!(report.p50 <= report.p90 && report.p90 <= report.p99);
+        assert!(report.avg_distances > 0.0);
+    }
+    // a beam as large as the index finds the exact neighbours
+    assert_eq!(reports[1].recall, 1.0);
+    assert!(reports[1].avg_distances >= reports[0].avg_distances);
+}
+
+#[test]
+fn search_across_indexes() {
+    const DIM: usize = 8;
+    let mut rng = rng();
+
+    let dir = tempfile::tempdir().unwrap();
+    let env = unsafe {
+        EnvOpenOptions::new().read_txn_without_tls().map_size(200 * 1024 * 1024).open(dir.path())
+    }
+    .unwrap();
+    let mut wtxn = env.write_txn().unwrap();
+    let database: Database<Euclidean> = env.create_database(&mut wtxn, None).unwrap();
+    for index in 0..3 {
+        let writer = Writer::new(database, index, DIM);
+        for i in 0..100 {
+            let vector: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+            writer.add_item(&mut wtxn, i, &vector).unwrap();
+        }
+        writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    }
+    let writer = Writer::new(database, 3, DIM + 1);
+    writer.add_item(&mut wtxn, 0, &[0.0; DIM + 1]).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    wtxn.commit().unwrap();
+
+    let rtxn = env.read_txn().unwrap();
+    let reader = MultiReader::open(&rtxn, [0, 1, 2], database).unwrap();
+    let query = Reader::open(&rtxn, 2, database).unwrap().item_vector(&rtxn, 5).unwrap().unwrap();
+
+    let nns = reader.nns(20).by_vector(&rtxn, &query).unwrap();
+    assert_eq!(nns.len(), 20);
+    assert_eq!(nns[0], (2, 5, 0.0));
+    assert!(nns.windows(2).all(|w| w[0].2 <= w[1].2));
+
+    // the merged top-k is the top-k of the union of the results of each index
+    let mut expected = Vec::new();
+    for r in reader.readers() {
+        let searched = r.nns(20).by_vector(&rtxn, &query).unwrap();
+        expected.extend(searched.into_nns().into_iter().map(|(item, d)| (r.index(), item, d)));
+    }
+    expected.sort_by(|a, b| a.2.total_cmp(&b.2));
+    assert_eq!(nns, expected[..20]);
+    assert_eq!(reader.nns(20).par_by_vector(&env, &rtxn, &query).unwrap(), nns);
+
+    let err = MultiReader::open(&rtxn, [0, 3], database).map(drop).unwrap_err();
+    assert!(matches!(err, Error::InvalidVecDimension { expected: 8, received: 9 }), "{err}");
+}
+
+#[test]
+fn search_with_a_filter_on_attributes() {
+    const DIM: usize = 8;
+    const N: u32 = 300;
+    let mut rng = rng();
+
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<Euclidean>();
+    let writer = Writer::new(database, 0, DIM);
+    let mut wtxn = env.write_txn().unwrap();
+    for i in 0..N {
+        let vector: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+        writer.add_item(&mut wtxn, i, &vector).unwrap();
+        let attributes = Attributes::new()
+            .tag("color", ["red", "green", "blue"][i as usize % 3])
+            .number("price", i as f64 - 100.0);
+        writer.set_attributes(&mut wtxn, i, &attributes).unwrap();
+    }
+    // an item can have several values in a field
+    let attributes =
+        Attributes::new().tag("color", "green").tag("color", "red").number("price", -99.0);
+    writer.set_attributes(&mut wtxn, 1, &attributes).unwrap();
+    // attributes of items outside of the index are never returned
+    writer.set_attributes(&mut wtxn, N, &Attributes::new().tag("color", "red")).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    wtxn.commit().unwrap();
+
+    let rtxn = env.read_txn().unwrap();
+    let reader = Reader::<Euclidean>::open(&rtxn, 0, database).unwrap();
+    let matching = |f: fn(u32) -> bool| RoaringBitmap::from_iter((0..N).filter(|&i| f(i)));
+
+    let red = Filter::tag("color", "red");
+    assert_eq!(reader.filter(&rtxn, &red).unwrap(), matching(|i| i % 3 == 0 || i == 1));
+    let cheap = Filter::range("price", ..0.0);
+    assert_eq!(reader.filter(&rtxn, &cheap).unwrap(), matching(|i| i < 100));
+    let exact = Filter::eq("price", 0.0);
+    assert_eq!(reader.filter(&rtxn, &exact).unwrap(), matching(|i| i == 100));
+    let range = Filter::range("price", -10.5..=20.0);
+    assert_eq!(reader.filter(&rtxn, &range).unwrap(), matching(|i| (90..=120).contains(&i)));
+    let and = red.clone().and(cheap.clone());
+    assert_eq!(reader.filter(&rtxn, &and).unwrap(), matching(|i| i < 100 && i % 3 == 0 || i == 1));
+    let or = Filter::tag("color", "blue").or(exact.clone());
+    assert_eq!(reader.filter(&rtxn, &or).unwrap(), matching(|i| i % 3 == 2 || i == 100));
+    let not = !red.clone();
+    assert_eq!(reader.filter(&rtxn, &not).unwrap(), matching(|i| i % 3 != 0 && i != 1));
+    let unknown = Filter::tag("size", "red");
+    assert!(reader.filter(&rtxn, &unknown).unwrap().is_empty());
+
+    // the search only returns the items matching the filter and within the candidates
+    let query: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+    let expected = reader.filter(&rtxn, &and).unwrap();
+    let found = reader.nns(10).filter(&and).by_vector(&rtxn, &query).unwrap().into_nns();
+    let by_candidates =
+        reader.nns(10).candidates(&expected).by_vector(&rtxn, &query).unwrap().into_nns();
+    assert_eq!(found, by_candidates);
+    assert!(found.iter().all(|(i, _)| expected.contains(*i)));
+
+    let candidates = RoaringBitmap::from_iter(0..30);
+    let found = reader
+        .nns(10)
+        .candidates(&candidates)
+        .filter(&and)
+        .by_item(&rtxn, 3)
+        .unwrap()
+        .unwrap()
+        .into_nns();
+    assert!(found.iter().all(|(i, _)| *i < 30 && expected.contains(*i)));
+
+    let found: Vec<_> = reader
+        .nns(0)
+        .filter(&not)
+        .iter_by_vector(&rtxn, &query)
+        .unwrap()
+        .map(|res| res.unwrap().0)
+        .collect();
+    assert_eq!(RoaringBitmap::from_iter(found), reader.filter(&rtxn, &not).unwrap());
+}
+
+#[test]
+fn filtered_traversal_stays_within_the_candidates() {
+    const DIM: usize = 16;
+    const N: u32 = 2000;
+    let mut rng = rng();
+
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<Euclidean>();
+    let writer = Writer::new(database, 0, DIM);
+    let mut wtxn = env.write_txn().unwrap();
+    for i in 0..N {
+        let vector: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+        writer.add_item(&mut wtxn, i, &vector).unwrap();
+    }
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    wtxn.commit().unwrap();
+
+    let rtxn = env.read_txn().unwrap();
+    let reader = Reader::<Euclidean>::open(&rtxn, 0, database).unwrap();
+    // 5% of the items, too many to be scanned linearly
+    let candidates = RoaringBitmap::from_iter((0..N).filter(|_| rng.gen_bool(0.05)));
+
+    let (mut recall, mut filtered_recall) = (0, 0);
+    for _ in 0..20 {
+        let query: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+        let expected = reader
+            .exact_nns(&rtxn, &query, N as usize)
+            .unwrap()
+            .into_iter()
+            .filter(|(i, _)| candidates.contains(*i))
+            .take(10)
+            .map(|(i, _)| i)
+            .collect::<RoaringBitmap>();
+
+        let mut query_builder = reader.nns(10);
+        query_builder.candidates(&candidates).linear_below(0).ef_search(10).collect_stats(true);
+        let default = query_builder.by_vector(&rtxn, &query).unwrap();
+        let filtered = query_builder.filtered_traversal(true).by_vector(&rtxn, &query).unwrap();
+
+        let found = |searched: &Searched| searched.nns.iter().map(|(i, _)| *i).collect();
+        let (default_found, filtered_found): (RoaringBitmap, RoaringBitmap) =
+            (found(&default), found(&filtered));
+        assert!(filtered_found.is_subset(&candidates));
+        recall += default_found.intersection_len(&expected);
+        filtered_recall += filtered_found.intersection_len(&expected);
+
+        let (default_stats, filtered_stats) = (default.stats().unwrap(), filtered.stats().unwrap());
+        assert!(!filtered_stats.linear_scan);
+        assert!(filtered_stats.n_two_hops > 0);
+        assert!(filtered_stats.n_distances < default_stats.n_distances);
+    }
+
+    // the searches from an item walk the candidates too
+    let mut query_builder = reader.nns(10);
+    query_builder.candidates(&candidates).linear_below(0).ef_search(10).collect_stats(true);
+    for item in (0..N).step_by(100) {
+        let filtered =
+            query_builder.filtered_traversal(true).by_item(&rtxn, item).unwrap().unwrap();
+        assert_eq!(filtered.nns.len(), 10);
+        assert!(filtered.nns.iter().all(|(i, _)| candidates.contains(*i)));
+        assert!(filtered.stats().unwrap().n_two_hops > 0);
+    }
+
+    assert!(filtered_recall >= recall, "{filtered_recall} < {recall}");
+    assert!(filtered_recall >= 190, "{filtered_recall}");
+}
+
+#[test]
+fn search_with_a_filter_fn() {
+    const DIM: usize = 8;
+    const N: u32 = 500;
+    let mut rng = rng();
+
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<Euclidean>();
+    let writer = Writer::new(database, 0, DIM);
+    let mut wtxn = env.write_txn().unwrap();
+    for i in 0..N {
+        let vector: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+        writer.add_item(&mut wtxn, i, &vector).unwrap();
+    }
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    wtxn.commit().unwrap();
+
+    let rtxn = env.read_txn().unwrap();
+    let reader = Reader::<Euclidean>::open(&rtxn, 0, database).unwrap();
+    let allowed = |item: ItemId| !item.is_multiple_of(7);
+    let candidates = RoaringBitmap::from_iter((0..N).filter(|&i| allowed(i)));
+    let query: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+
+    // the predicate admits the same items as the equivalent candidates
+    let found = reader.nns(20).filter_fn(allowed).by_vector(&rtxn, &query).unwrap().into_nns();
+    let expected = reader
+        .nns(20)
+        .candidates(&candidates)
+        .linear_below(0)
+        .by_vector(&rtxn, &query)
+        .unwrap()
+        .into_nns();
+    assert_eq!(found.len(), 20);
+    assert_eq!(found, expected);
+
+    let found = reader.nns(20).filter_fn(allowed).by_item(&rtxn, 7).unwrap().unwrap().into_nns();
+    assert!(found.iter().all(|&(i, _)| allowed(i)));
+    let found = reader.nns(0).filter_fn(allowed).by_vector_within(&rtxn, &query, 1.0).unwrap();
+    assert!(!found.nns.is_empty() && found.nns.iter().all(|&(i, _)| allowed(i)));
+    let found: Vec<_> = reader
+        .nns(0)
+        .filter_fn(allowed)
+        .iter_by_vector(&rtxn, &query)
+        .unwrap()
+        .map(|res| res.unwrap().0)
+        .collect();
+    assert_eq!(RoaringBitmap::from_iter(found), candidates);
+
+    // it's combined with the candidates, even when they are scanned linearly
+    let few = RoaringBitmap::from_iter(0..50);
+    let found = reader.nns(50).candidates(&few).filter_fn(allowed).by_vector(&rtxn, &query);
+    let found = RoaringBitmap::from_iter(found.unwrap().into_nns().into_iter().map(|(i, _)| i));
+    assert_eq!(found, &few & &candidates);
+    let found = reader
+        .nns(20)
+        .filter_fn(allowed)
+        .filtered_traversal(true)
+        .by_vector(&rtxn, &query)
+        .unwrap()
+        .into_nns();
+    assert!(found.iter().all(|&(i, _)| allowed(i)));
+}
+
+#[test]
+fn diversify_the_near_duplicates() {
+    const DIM: usize = 8;
+    const CLUSTERS: u32 = 5;
+    const PER_CLUSTER: u32 = 20;
+    let mut rng = rng();
+
+    // the items of a cluster are near-duplicates of a unit vector
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<Euclidean>();
+    let writer = Writer::new(database, 0, DIM);
+    let mut wtxn = env.write_txn().unwrap();
+    for i in 0..CLUSTERS * PER_CLUSTER {
+        let mut vector: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-0.01..0.01));
+        vector[(i / PER_CLUSTER) as usize] += 1.0;
+        writer.add_item(&mut wtxn, i, &vector).unwrap();
+    }
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    wtxn.commit().unwrap();
+
+    let rtxn = env.read_txn().unwrap();
+    let reader = Reader::<Euclidean>::open(&rtxn, 0, database).unwrap();
+    let mut query = [0.0; DIM];
+    query[0] = 1.0;
+    let clusters =
+        |nns: &[(ItemId, f32)]| nns.iter().map(|&(i, _)| i / PER_CLUSTER).collect::<Vec<_>>();
+
+    let found = reader.nns(5).by_vector(&rtxn, &query).unwrap().into_nns();
+    assert_eq!(clusters(&found), [0; 5]);
+
+    // a lambda of 1 only cares about the relevance
+    let relevant = reader.nns(5).diversify(1.0, 100).by_vector(&rtxn, &query).unwrap().into_nns();
+    assert_eq!(relevant, found);
+
+    // the closest item comes first, then one of each other cluster
+    let diverse = reader.nns(5).diversify(0.3, 100).by_vector(&rtxn, &query).unwrap().into_nns();
+    assert_eq!(diverse[0], found[0]);
+    let mut picked = clusters(&diverse);
+    picked.sort_unstable();
+    assert_eq!(picked, [0, 1, 2, 3, 4]);
+
+    let diverse = reader.nns(5).diversify(0.3, 100).by_item(&rtxn, 42).unwrap().unwrap();
+    let mut picked = clusters(&diverse.into_nns());
+    picked.sort_unstable();
+    assert_eq!(picked, [0, 1, 2, 3, 4]);
+
+    // it never returns more than count items, even when fetching fewer
+    let diverse = reader.nns(3).diversify(0.3, 1).by_vector(&rtxn, &query).unwrap().into_nns();
+    assert_eq!(diverse.len(), 3);
+
+    for lambda in [-0.1, 1.5, f32::NAN] {
+        let err = reader.nns(3).diversify(lambda, 10).by_vector(&rtxn, &query);
+        assert!(matches!(err, Err(Error::InvalidDiversification(_))), "{lambda}");
+    }
+}
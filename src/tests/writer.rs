@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use heed::types::DecodeIgnore;
 use proptest::proptest;
 use rand::distributions::Uniform;
@@ -7,11 +9,19 @@ use rand::{thread_rng, Rng, SeedableRng};
 use roaring::RoaringBitmap;
 
 use super::{create_database, rng};
-use crate::distance::{BinaryQuantizedCosine, Cosine, Euclidean};
+use crate::distance::{
+    BinaryQuantizedCosine, Cosine, CosineF16, Euclidean, ScalarQuantizedCosine,
+    ScalarQuantizedEuclidean,
+};
 use crate::key::{KeyCodec, Prefix, PrefixCodec};
+use crate::node::{Links, Node};
 use crate::reader::get_item;
 use crate::tests::{create_database_indices_with_items, DatabaseHandle};
-use crate::{Reader, Writer};
+use crate::vecs::{NpyReader, VecsFormat, VecsReader};
+use crate::{
+    AttributeValue, Attributes, BuildParams, Error, Filter, HannoyBuild, Key, MetadataCodec,
+    NodeMode, Reader, Writer,
+};
 
 const M: usize = 3;
 const M0: usize = 3;
@@ -26,11 +36,11 @@ fn clear_small_database() {
     let zero_writer = Writer::new(database, 0, 3);
     zero_writer.add_item(&mut wtxn, 0, &[0.0, 1.0, 2.0]).unwrap();
     zero_writer.clear(&mut wtxn).unwrap();
-    zero_writer.builder(&mut rng()).build::<M, M0>(&mut wtxn).unwrap();
+    zero_writer.builder(&mut rng()).m(M).m0(M0).build(&mut wtxn).unwrap();
 
     let one_writer = Writer::new(database, 1, 3);
     one_writer.add_item(&mut wtxn, 0, &[1.0, 2.0, 3.0]).unwrap();
-    one_writer.builder(&mut rng()).build::<M, M0>(&mut wtxn).unwrap();
+    one_writer.builder(&mut rng()).m(M).m0(M0).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     let mut wtxn = env.write_txn().unwrap();
@@ -55,13 +65,13 @@ fn delete_all_but_one_item_and_build() {
     writer.add_item(&mut wtxn, 2, &[2.0, 1.0, 0.0]).unwrap();
     writer.add_item(&mut wtxn, 3, &[1.0, 0.0, 2.0]).unwrap();
     writer.add_item(&mut wtxn, 0, &[0.0, 1.0, 2.0]).unwrap();
-    writer.builder(&mut rng()).build::<M, M0>(&mut wtxn).unwrap();
+    writer.builder(&mut rng()).m(M).m0(M0).build(&mut wtxn).unwrap();
 
     let writer = Writer::new(database, 0, DIMENSIONS);
     writer.del_item(&mut wtxn, 0).unwrap();
     writer.del_item(&mut wtxn, 2).unwrap();
     writer.del_item(&mut wtxn, 3).unwrap();
-    writer.builder(&mut rng()).build::<M, M0>(&mut wtxn).unwrap();
+    writer.builder(&mut rng()).m(M).m0(M0).build(&mut wtxn).unwrap();
 }
 
 #[test]
@@ -71,7 +81,7 @@ fn use_u32_max_minus_one_for_a_vec() {
     let writer = Writer::new(handle.database, 0, 3);
     writer.add_item(&mut wtxn, u32::MAX - 1, &[0.0, 1.0, 2.0]).unwrap();
 
-    writer.builder(&mut rng()).build::<M, M0>(&mut wtxn).unwrap();
+    writer.builder(&mut rng()).m(M).m0(M0).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     insta::assert_snapshot!(handle, @r###"
@@ -79,6 +89,7 @@ fn use_u32_max_minus_one_for_a_vec() {
     Dumping index 0
     Root: Metadata { dimensions: 3, items: RoaringBitmap<[4294967294]>, distance: "euclidean", entry_points: [4294967294], max_level: 1 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     Links 4294967294: Links(Links { links: RoaringBitmap<[]> })
     Links 4294967294: Links(Links { links: RoaringBitmap<[]> })
     Item 4294967294: Item(Item { header: NodeHeaderEuclidean { bias: "0.0000" }, vector: [0.0000, 1.0000, 2.0000] })
@@ -92,7 +103,7 @@ fn use_u32_max_for_a_vec() {
     let writer = Writer::new(handle.database, 0, 3);
     writer.add_item(&mut wtxn, u32::MAX, &[0.0, 1.0, 2.0]).unwrap();
 
-    writer.builder(&mut rng()).build::<M, M0>(&mut wtxn).unwrap();
+    writer.builder(&mut rng()).m(M).m0(M0).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     insta::assert_snapshot!(handle, @r###"
@@ -100,6 +111,7 @@ fn use_u32_max_for_a_vec() {
     Dumping index 0
     Root: Metadata { dimensions: 3, items: RoaringBitmap<[4294967295]>, distance: "euclidean", entry_points: [4294967295], max_level: 1 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     Links 4294967295: Links(Links { links: RoaringBitmap<[]> })
     Links 4294967295: Links(Links { links: RoaringBitmap<[]> })
     Item 4294967295: Item(Item { header: NodeHeaderEuclidean { bias: "0.0000" }, vector: [0.0000, 1.0000, 2.0000] })
@@ -113,7 +125,7 @@ fn write_one_vector() {
     let writer = Writer::new(handle.database, 0, 3);
     writer.add_item(&mut wtxn, 0, &[0.0, 1.0, 2.0]).unwrap();
 
-    writer.builder(&mut rng()).build::<M, M0>(&mut wtxn).unwrap();
+    writer.builder(&mut rng()).m(M).m0(M0).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     insta::assert_snapshot!(handle, @r###"
@@ -121,6 +133,7 @@ fn write_one_vector() {
     Dumping index 0
     Root: Metadata { dimensions: 3, items: RoaringBitmap<[0]>, distance: "euclidean", entry_points: [0], max_level: 1 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Item 0: Item(Item { header: NodeHeaderEuclidean { bias: "0.0000" }, vector: [0.0000, 1.0000, 2.0000] })
@@ -138,7 +151,7 @@ fn write_and_update_lot_of_random_points_with_snapshot() {
         writer.add_item(&mut wtxn, id, &vector).unwrap();
     }
 
-    writer.builder(&mut rng).build::<M, M0>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
     insta::assert_snapshot!(handle);
 
@@ -148,7 +161,7 @@ fn write_and_update_lot_of_random_points_with_snapshot() {
         let vector: [f32; 30] = std::array::from_fn(|_| rng.gen());
         writer.add_item(&mut wtxn, id, &vector).unwrap();
     }
-    writer.builder(&mut rng).build::<M, M0>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     insta::assert_snapshot!(handle);
@@ -162,7 +175,7 @@ fn write_multiple_indexes() {
     for i in 0..5 {
         let writer = Writer::new(handle.database, i, 3);
         writer.add_item(&mut wtxn, 0, &[0.0, 1.0, 2.0]).unwrap();
-        writer.builder(&mut rng()).build::<M, M0>(&mut wtxn).unwrap();
+        writer.builder(&mut rng()).m(M).m0(M0).build(&mut wtxn).unwrap();
     }
     wtxn.commit().unwrap();
 
@@ -171,6 +184,7 @@ fn write_multiple_indexes() {
     Dumping index 0
     Root: Metadata { dimensions: 3, items: RoaringBitmap<[0]>, distance: "euclidean", entry_points: [0], max_level: 1 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Item 0: Item(Item { header: NodeHeaderEuclidean { bias: "0.0000" }, vector: [0.0000, 1.0000, 2.0000] })
@@ -178,6 +192,7 @@ fn write_multiple_indexes() {
     Dumping index 1
     Root: Metadata { dimensions: 3, items: RoaringBitmap<[0]>, distance: "euclidean", entry_points: [0], max_level: 1 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Item 0: Item(Item { header: NodeHeaderEuclidean { bias: "0.0000" }, vector: [0.0000, 1.0000, 2.0000] })
@@ -185,6 +200,7 @@ fn write_multiple_indexes() {
     Dumping index 2
     Root: Metadata { dimensions: 3, items: RoaringBitmap<[0]>, distance: "euclidean", entry_points: [0], max_level: 1 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Item 0: Item(Item { header: NodeHeaderEuclidean { bias: "0.0000" }, vector: [0.0000, 1.0000, 2.0000] })
@@ -192,6 +208,7 @@ fn write_multiple_indexes() {
     Dumping index 3
     Root: Metadata { dimensions: 3, items: RoaringBitmap<[0]>, distance: "euclidean", entry_points: [0], max_level: 1 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Item 0: Item(Item { header: NodeHeaderEuclidean { bias: "0.0000" }, vector: [0.0000, 1.0000, 2.0000] })
@@ -199,6 +216,7 @@ fn write_multiple_indexes() {
     Dumping index 4
     Root: Metadata { dimensions: 3, items: RoaringBitmap<[0]>, distance: "euclidean", entry_points: [0], max_level: 1 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Item 0: Item(Item { header: NodeHeaderEuclidean { bias: "0.0000" }, vector: [0.0000, 1.0000, 2.0000] })
@@ -222,7 +240,7 @@ fn write_random_vectors_to_random_indexes() {
             let vector: [f32; 10] = std::array::from_fn(|_| rng.gen());
             writer.add_item(&mut wtxn, i, &vector).unwrap();
         }
-        writer.builder(&mut rng).build::<M, M0>(&mut wtxn).unwrap();
+        writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
     }
     wtxn.commit().unwrap();
 }
@@ -265,7 +283,7 @@ fn convert_from_arroy_to_hannoy() {
 
     let mut wtxn = env.write_txn().unwrap();
     let rtxn = env.read_txn().unwrap();
-    let database: crate::Database<Cosine> = env.open_database(&mut wtxn, None).unwrap().unwrap();
+    let database: crate::Database<Cosine> = env.open_database(&wtxn, None).unwrap().unwrap();
 
     db_indexes.shuffle(&mut rng);
 
@@ -276,8 +294,8 @@ fn convert_from_arroy_to_hannoy() {
 
         let writer = Writer::new(database, index, pre_commit_arroy_reader.dimensions());
         writer.builder(&mut rng).prepare_arroy_conversion(&mut wtxn).unwrap();
-        assert!(writer.need_build(&mut wtxn).unwrap());
-        writer.builder(&mut rng).build::<16, 32>(&mut wtxn).unwrap();
+        assert!(writer.need_build(&wtxn).unwrap());
+        writer.builder(&mut rng).m(16).m0(32).build(&mut wtxn).unwrap();
 
         for result in pre_commit_arroy_reader.iter(&rtxn).unwrap() {
             let (item_id, mut vector) = result.unwrap();
@@ -337,7 +355,7 @@ fn convert_from_arroy_to_hannoy_binary_quantized() {
     let mut wtxn = env.write_txn().unwrap();
     let rtxn = env.read_txn().unwrap();
     let database: crate::Database<BinaryQuantizedCosine> =
-        env.open_database(&mut wtxn, None).unwrap().unwrap();
+        env.open_database(&wtxn, None).unwrap().unwrap();
 
     db_indexes.shuffle(&mut rng);
 
@@ -352,8 +370,8 @@ fn convert_from_arroy_to_hannoy_binary_quantized() {
 
         let writer = Writer::new(database, index, pre_commit_arroy_reader.dimensions());
         writer.builder(&mut rng).prepare_arroy_conversion(&mut wtxn).unwrap();
-        assert!(writer.need_build(&mut wtxn).unwrap());
-        writer.builder(&mut rng).build::<16, 32>(&mut wtxn).unwrap();
+        assert!(writer.need_build(&wtxn).unwrap());
+        writer.builder(&mut rng).m(16).m0(32).build(&mut wtxn).unwrap();
 
         for result in pre_commit_arroy_reader.iter(&rtxn).unwrap() {
             let (item_id, mut vector) = result.unwrap();
@@ -382,7 +400,7 @@ fn overwrite_one_item_incremental() {
     for i in 0..6 {
         writer.add_item(&mut wtxn, i, &[i as f32, 0.]).unwrap();
     }
-    writer.builder(&mut rng).build::<M, M0>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     insta::assert_snapshot!(handle, @r###"
@@ -390,6 +408,7 @@ fn overwrite_one_item_incremental() {
     Dumping index 0
     Root: Metadata { dimensions: 2, items: RoaringBitmap<[0, 1, 2, 3, 4, 5]>, distance: "euclidean", entry_points: [0, 2, 3], max_level: 1 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     Links 0: Links(Links { links: RoaringBitmap<[1, 2]> })
     Links 0: Links(Links { links: RoaringBitmap<[2]> })
     Links 1: Links(Links { links: RoaringBitmap<[0, 2]> })
@@ -411,7 +430,7 @@ fn overwrite_one_item_incremental() {
     let writer = Writer::new(handle.database, 0, 2);
     writer.add_item(&mut wtxn, 3, &[6., 0.]).unwrap();
 
-    writer.builder(&mut rng).build::<M, M0>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     insta::assert_snapshot!(handle, @r###"
@@ -419,6 +438,7 @@ fn overwrite_one_item_incremental() {
     Dumping index 0
     Root: Metadata { dimensions: 2, items: RoaringBitmap<[0, 1, 2, 3, 4, 5]>, distance: "euclidean", entry_points: [0, 2, 3], max_level: 1 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     Links 0: Links(Links { links: RoaringBitmap<[1, 2]> })
     Links 0: Links(Links { links: RoaringBitmap<[2]> })
     Links 1: Links(Links { links: RoaringBitmap<[0, 2]> })
@@ -446,7 +466,7 @@ fn delete_one_item_in_a_one_item_db() {
     let writer = Writer::new(handle.database, 0, 2);
 
     writer.add_item(&mut wtxn, 0, &[0., 0.]).unwrap();
-    writer.builder(&mut rng).build::<M, M0>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     insta::assert_snapshot!(handle, @r###"
@@ -454,6 +474,7 @@ fn delete_one_item_in_a_one_item_db() {
     Dumping index 0
     Root: Metadata { dimensions: 2, items: RoaringBitmap<[0]>, distance: "euclidean", entry_points: [0], max_level: 1 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Item 0: Item(Item { header: NodeHeaderEuclidean { bias: "0.0000" }, vector: [0.0000, 0.0000] })
@@ -464,7 +485,7 @@ fn delete_one_item_in_a_one_item_db() {
     let writer = Writer::new(handle.database, 0, 2);
 
     writer.del_item(&mut wtxn, 0).unwrap();
-    writer.builder(&mut rng).build::<M, M0>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     insta::assert_snapshot!(handle, @r###"
@@ -472,6 +493,7 @@ fn delete_one_item_in_a_one_item_db() {
     Dumping index 0
     Root: Metadata { dimensions: 2, items: RoaringBitmap<[]>, distance: "euclidean", entry_points: [], max_level: 0 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     "###);
 
     let rtxn = handle.env.read_txn().unwrap();
@@ -489,7 +511,7 @@ fn delete_document_in_an_empty_index_74() {
     let writer = Writer::new(handle.database, 0, 2);
     writer.del_item(&mut wtxn, 0).unwrap();
     writer.add_item(&mut wtxn, 0, &[0., 0.]).unwrap();
-    writer.builder(&mut rng).build::<M, M0>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
 
     wtxn.commit().unwrap();
 
@@ -498,6 +520,7 @@ fn delete_document_in_an_empty_index_74() {
     Dumping index 0
     Root: Metadata { dimensions: 2, items: RoaringBitmap<[0]>, distance: "euclidean", entry_points: [0], max_level: 1 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Item 0: Item(Item { header: NodeHeaderEuclidean { bias: "0.0000" }, vector: [0.0000, 0.0000] })
@@ -511,8 +534,8 @@ fn delete_document_in_an_empty_index_74() {
     let writer2 = Writer::new(handle.database, 1, 2);
     writer2.del_item(&mut wtxn, 0).unwrap();
 
-    writer1.builder(&mut rng).build::<M, M0>(&mut wtxn).unwrap();
-    writer2.builder(&mut rng).build::<M, M0>(&mut wtxn).unwrap();
+    writer1.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    writer2.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
 
     let reader = Reader::open(&wtxn, 1, handle.database).unwrap();
     let ret = reader.nns(10).by_vector(&wtxn, &[0., 0.]).unwrap();
@@ -520,6 +543,7 @@ fn delete_document_in_an_empty_index_74() {
     Searched {
         nns: [],
         did_cancel: false,
+        stats: None,
     }
     ");
 
@@ -530,10 +554,12 @@ fn delete_document_in_an_empty_index_74() {
     Dumping index 0
     Root: Metadata { dimensions: 2, items: RoaringBitmap<[]>, distance: "euclidean", entry_points: [], max_level: 0 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     ==================
     Dumping index 1
     Root: Metadata { dimensions: 2, items: RoaringBitmap<[]>, distance: "euclidean", entry_points: [], max_level: 0 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     "###);
 
     let rtxn = handle.env.read_txn().unwrap();
@@ -543,6 +569,7 @@ fn delete_document_in_an_empty_index_74() {
     Searched {
         nns: [],
         did_cancel: false,
+        stats: None,
     }
     ");
 }
@@ -556,7 +583,7 @@ fn delete_one_item_in_a_single_document_database() {
 
     // first, insert a bunch of elements
     writer.add_item(&mut wtxn, 0, &[0., 0.]).unwrap();
-    writer.builder(&mut rng).build::<M, M0>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     insta::assert_snapshot!(handle, @r###"
@@ -564,6 +591,7 @@ fn delete_one_item_in_a_single_document_database() {
     Dumping index 0
     Root: Metadata { dimensions: 2, items: RoaringBitmap<[0]>, distance: "cosine", entry_points: [0], max_level: 1 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Links 0: Links(Links { links: RoaringBitmap<[]> })
     Item 0: Item(Item { header: NodeHeaderCosine { norm: "0.0000" }, vector: [0.0000, 0.0000] })
@@ -574,7 +602,7 @@ fn delete_one_item_in_a_single_document_database() {
 
     writer.del_item(&mut wtxn, 0).unwrap();
 
-    writer.builder(&mut rng).build::<M, M0>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     insta::assert_snapshot!(handle, @r###"
@@ -582,6 +610,7 @@ fn delete_one_item_in_a_single_document_database() {
     Dumping index 0
     Root: Metadata { dimensions: 2, items: RoaringBitmap<[]>, distance: "cosine", entry_points: [], max_level: 0 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     "###);
 }
 
@@ -596,7 +625,7 @@ fn delete_one_item() {
     for i in 0..6 {
         writer.add_item(&mut wtxn, i, &[i as f32, 0.]).unwrap();
     }
-    writer.builder(&mut rng).build::<3, 3>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(3).m0(3).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     insta::assert_snapshot!(handle, @r###"
@@ -604,6 +633,7 @@ fn delete_one_item() {
     Dumping index 0
     Root: Metadata { dimensions: 2, items: RoaringBitmap<[0, 1, 2, 3, 4, 5]>, distance: "euclidean", entry_points: [0, 2, 3], max_level: 1 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     Links 0: Links(Links { links: RoaringBitmap<[1, 2]> })
     Links 0: Links(Links { links: RoaringBitmap<[2]> })
     Links 1: Links(Links { links: RoaringBitmap<[0, 2]> })
@@ -626,7 +656,7 @@ fn delete_one_item() {
 
     writer.del_item(&mut wtxn, 3).unwrap();
 
-    writer.builder(&mut rng).build::<3, 3>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(3).m0(3).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     insta::assert_snapshot!(handle, @r###"
@@ -634,6 +664,7 @@ fn delete_one_item() {
     Dumping index 0
     Root: Metadata { dimensions: 2, items: RoaringBitmap<[0, 1, 2, 4, 5]>, distance: "euclidean", entry_points: [0, 1, 2], max_level: 1 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     Links 0: Links(Links { links: RoaringBitmap<[1]> })
     Links 0: Links(Links { links: RoaringBitmap<[1, 2]> })
     Links 1: Links(Links { links: RoaringBitmap<[0, 2]> })
@@ -655,7 +686,7 @@ fn delete_one_item() {
 
     writer.del_item(&mut wtxn, 1).unwrap();
 
-    writer.builder(&mut rng).build::<3, 3>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(3).m0(3).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     insta::assert_snapshot!(handle, @r###"
@@ -663,6 +694,7 @@ fn delete_one_item() {
     Dumping index 0
     Root: Metadata { dimensions: 2, items: RoaringBitmap<[0, 2, 4, 5]>, distance: "euclidean", entry_points: [0, 2, 4], max_level: 1 }
     Version: Version { major: 0, minor: 1, patch: 3 }
+    Build params: BuildParams { m: 3, m0: 3, ef_construction: 100, alpha: 1.0 }
     Links 0: Links(Links { links: RoaringBitmap<[0, 2]> })
     Links 0: Links(Links { links: RoaringBitmap<[0, 2]> })
     Links 2: Links(Links { links: RoaringBitmap<[0, 2, 4]> })
@@ -688,7 +720,7 @@ fn delete_one_item_no_snapshots() {
     for i in 0..6 {
         writer.add_item(&mut wtxn, i, &[i as f32, 0.]).unwrap();
     }
-    writer.builder(&mut rng).build::<3, 3>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(3).m0(3).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     let mut wtxn = handle.env.write_txn().unwrap();
@@ -696,7 +728,7 @@ fn delete_one_item_no_snapshots() {
 
     writer.del_item(&mut wtxn, 3).unwrap();
 
-    writer.builder(&mut rng).build::<3, 3>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(3).m0(3).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     // delete another one
@@ -705,7 +737,7 @@ fn delete_one_item_no_snapshots() {
 
     writer.del_item(&mut wtxn, 1).unwrap();
 
-    writer.builder(&mut rng).build::<3, 3>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(3).m0(3).build(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     // verify neither of items are in db nor their links
@@ -742,7 +774,7 @@ proptest! {
             let vector: Vec<f32> = (0..dim).map(|_| rng.gen()).collect();
             writer.add_item(&mut wtxn, i, &vector).unwrap();
         }
-        writer.builder(&mut rng).build::<M, M0>(&mut wtxn).unwrap();
+        writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
     }
 }
 
@@ -758,7 +790,7 @@ fn test_force_rebuild_and_search() {
     // force rebuild the db
     let mut wtxn = env.write_txn().unwrap();
     let writer = Writer::new(database, 0, DIM);
-    writer.builder(&mut rng).force_rebuild::<M, M0>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).force_rebuild(&mut wtxn).unwrap();
     wtxn.commit().unwrap();
 
     // check we can still read over it
@@ -769,3 +801,746 @@ fn test_force_rebuild_and_search() {
     assert!(found.len() == 10);
     assert!(!found.contains(&(0, 0.0)))
 }
+
+#[test]
+fn build_with_runtime_graph_degree() {
+    const DIM: usize = 16;
+    let mut rng = rng();
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<Euclidean>();
+    let mut wtxn = env.write_txn().unwrap();
+
+    let writer = Writer::new(database, 0, DIM);
+    for i in 0..500 {
+        let vector: Vec<f32> = (0..DIM).map(|_| rng.gen()).collect();
+        writer.add_item(&mut wtxn, i, &vector).unwrap();
+    }
+
+    // the degree is no longer a compile time constant
+    let (m, m0) = [(4, 8), (5, 7)].choose(&mut rng).copied().unwrap();
+    writer.builder(&mut rng).m(m).m0(m0).build(&mut wtxn).unwrap();
+    wtxn.commit().unwrap();
+
+    let rtxn = env.read_txn().unwrap();
+    let links_iter = database
+        .remap_key_type::<PrefixCodec>()
+        .prefix_iter(&rtxn, &Prefix::links(0))
+        .unwrap()
+        .remap_key_type::<KeyCodec>();
+
+    for res in links_iter {
+        let (key, node) = res.unwrap();
+        let links = node.links().unwrap().links;
+        let max_links = if key.node.layer == 0 { m0 } else { m };
+        assert!(links.len() as usize <= max_links, "{key:?} has {} links", links.len());
+    }
+
+    let reader = Reader::<Euclidean>::open(&rtxn, 0, database).unwrap();
+    let query = reader.item_vector(&rtxn, 0).unwrap().unwrap();
+    let found = reader.nns(1).ef_search(64).by_vector(&rtxn, &query).unwrap().into_nns();
+    assert_eq!(found[0], (0, 0.0));
+}
+
+#[test]
+fn incremental_build_keeps_the_graph_degree() {
+    let handle = create_database::<Euclidean>();
+    let mut rng = rng();
+    let mut wtxn = handle.env.write_txn().unwrap();
+    let writer = Writer::new(handle.database, 0, 2);
+    for i in 0..10 {
+        writer.add_item(&mut wtxn, i, &[i as f32, 1.0]).unwrap();
+    }
+    writer.builder(&mut rng).m(M).m0(M0).ef_construction(64).build(&mut wtxn).unwrap();
+
+    let reader = Reader::<Euclidean>::open(&wtxn, 0, handle.database).unwrap();
+    let params = BuildParams { m: M, m0: M0, ef_construction: 64, alpha: 1.0 };
+    assert_eq!(reader.build_params(), Some(params));
+
+    // linking new items with another degree is rejected...
+    writer.add_item(&mut wtxn, 10, &[10.0, 1.0]).unwrap();
+    let err = writer.builder(&mut rng).m(M + 1).m0(M0).build(&mut wtxn).unwrap_err();
+    assert!(
+        matches!(err, Error::UnmatchingBuildParams { index: 0, expected, .. } if expected == params)
+    );
+
+    // ...but changing the other parameters is fine
+    writer.builder(&mut rng).m(M).m0(M0).ef_construction(32).build(&mut wtxn).unwrap();
+    let reader = Reader::<Euclidean>::open(&wtxn, 0, handle.database).unwrap();
+    assert_eq!(reader.build_params().unwrap().ef_construction, 32);
+
+    // and a full rebuild can change the degree
+    writer.builder(&mut rng).m(M + 1).m0(M0 + 1).force_rebuild(&mut wtxn).unwrap();
+    let reader = Reader::<Euclidean>::open(&wtxn, 0, handle.database).unwrap();
+    let params = reader.build_params().unwrap();
+    assert_eq!((params.m, params.m0), (M + 1, M0 + 1));
+
+    // a graph can't be built with too few links per node
+    let err = writer.builder(&mut rng).m(1).m0(M0).build(&mut wtxn).unwrap_err();
+    assert!(matches!(err, Error::InvalidBuildParams(BuildParams { m: 1, .. })), "{err}");
+    let err = writer.builder(&mut rng).m(M).m0(0).force_rebuild(&mut wtxn).unwrap_err();
+    assert!(matches!(err, Error::InvalidBuildParams(BuildParams { m0: 0, .. })), "{err}");
+}
+
+#[test]
+fn repair_relinks_unreachable_items() {
+    const DIM: usize = 4;
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<Euclidean>();
+    let mut rng = rng();
+    let mut wtxn = env.write_txn().unwrap();
+    let writer = Writer::new(database, 0, DIM);
+    for i in 0..100 {
+        let vector: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+        writer.add_item(&mut wtxn, i, &vector).unwrap();
+    }
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+
+    // pick an item that is well connected and isn't an entry point
+    let repaired = writer.builder(&mut rng).m(M).m0(M0).repair(&mut wtxn).unwrap();
+    let metadata =
+        database.remap_data_type::<MetadataCodec>().get(&wtxn, &Key::metadata(0)).unwrap().unwrap();
+    let victim = (0..100)
+        .find(|&id| !repaired.contains(id) && !metadata.entry_points.iter().any(|ep| ep == id))
+        .unwrap();
+
+    // cut every link pointing to the victim
+    let links: Vec<_> = database
+        .remap_key_type::<PrefixCodec>()
+        .prefix_iter(&wtxn, &Prefix::links(0))
+        .unwrap()
+        .remap_key_type::<KeyCodec>()
+        .map(|result| {
+            let (key, node) = result.unwrap();
+            (key, node.links().unwrap().links.into_owned())
+        })
+        .collect();
+    for (key, mut bitmap) in links {
+        if bitmap.remove(victim) {
+            let links = Links { links: Cow::Owned(bitmap) };
+            database.put(&mut wtxn, &key, &Node::Links(links)).unwrap();
+        }
+    }
+
+    // a repair with another degree is rejected
+    let err = writer.builder(&mut rng).m(M + 1).m0(M0).repair(&mut wtxn).unwrap_err();
+    assert!(matches!(err, Error::UnmatchingBuildParams { index: 0, .. }));
+
+    let repaired = writer.builder(&mut rng).m(M).m0(M0).repair(&mut wtxn).unwrap();
+    assert!(repaired.contains(victim));
+
+    let reader = Reader::<Euclidean>::open(&wtxn, 0, database).unwrap();
+    assert!(reader.check(&wtxn).unwrap().is_ok());
+    let vector = reader.item_vector(&wtxn, victim).unwrap().unwrap();
+    let found = reader.nns(1).by_vector(&wtxn, &vector).unwrap();
+    assert_eq!(found.into_nns(), vec![(victim, 0.0)]);
+
+    // and pending updates must be built first
+    writer.del_item(&mut wtxn, victim).unwrap();
+    let err = writer.builder(&mut rng).m(M).m0(M0).repair(&mut wtxn).unwrap_err();
+    assert!(matches!(err, Error::NeedBuild(0)));
+}
+
+#[test]
+fn build_returns_stats() {
+    const DIM: usize = 4;
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<Euclidean>();
+    let mut rng = rng();
+    let mut wtxn = env.write_txn().unwrap();
+    let writer = Writer::new(database, 0, DIM);
+    for i in 0..100 {
+        let vector: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+        writer.add_item(&mut wtxn, i, &vector).unwrap();
+    }
+
+    let stats = writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    assert_eq!((stats.n_items_inserted(), stats.n_items_deleted()), (100, 0));
+    assert_eq!(stats.layer_dist().values().sum::<usize>(), 100);
+    assert!(stats.n_links_added() > 0);
+    assert_eq!(stats.n_links_patched(), 0);
+    let phases: Vec<_> = stats.phases().iter().map(|(phase, _)| *phase).collect();
+    assert_eq!(phases.first(), Some(&HannoyBuild::RetrieveTheUpdatedItems));
+    assert!(phases.contains(&HannoyBuild::BuildingTheGraph));
+    assert_eq!(phases.last(), Some(&HannoyBuild::WriteTheMetadata));
+    assert_eq!(stats.duration(), stats.phases().iter().map(|(_, d)| *d).sum());
+
+    // deleting items patches the links pointing to them
+    for i in 0..10 {
+        writer.del_item(&mut wtxn, i).unwrap();
+    }
+    let stats = writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    assert_eq!((stats.n_items_inserted(), stats.n_items_deleted()), (0, 10));
+    assert!(stats.n_links_patched() > 0);
+
+    let stats = writer.builder(&mut rng).m(M).m0(M0).force_rebuild(&mut wtxn).unwrap();
+    assert_eq!((stats.n_items_inserted(), stats.n_items_deleted()), (90, 0));
+    assert_eq!(stats.phases()[0].0, HannoyBuild::DeletingTheLinks);
+}
+
+#[test]
+fn import_and_export_vectors() {
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<Euclidean>();
+    let mut rng = rng();
+    let mut wtxn = env.write_txn().unwrap();
+
+    let vectors = [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 255.0]];
+    let mut fvecs = Vec::new();
+    for vector in &vectors {
+        VecsFormat::Fvecs.write_vector(&mut fvecs, vector).unwrap();
+    }
+    let writer = Writer::new(database, 0, 3);
+    let n_items = writer.import_fvecs(&mut wtxn, &fvecs[..], Some(&mut &b"30\n10\n20\n"[..]));
+    assert_eq!(n_items.unwrap(), 3);
+    assert_eq!(writer.item_vector(&wtxn, 30).unwrap().unwrap(), vectors[0]);
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+
+    // the ids don't match the vectors
+    let ids: &mut dyn std::io::Read = &mut &b"1\n2\n"[..];
+    let err = Writer::new(database, 1, 3).import_fvecs(&mut wtxn, &fvecs[..], Some(ids));
+    assert!(matches!(err, Err(Error::InvalidFileFormat { format: "id-mapping", .. })));
+    let ids: &mut dyn std::io::Read = &mut &b"1\n2\n3\n4\n"[..];
+    let err = Writer::new(database, 1, 3).import_fvecs(&mut wtxn, &fvecs[..], Some(ids));
+    assert!(matches!(err, Err(Error::InvalidFileFormat { format: "id-mapping", .. })));
+    Writer::new(database, 1, 3).clear(&mut wtxn).unwrap();
+
+    // the vectors are exported in the order of their ids
+    let reader = Reader::open(&wtxn, 0, database).unwrap();
+    let (mut exported, mut ids) = (Vec::new(), Vec::new());
+    assert_eq!(reader.export_fvecs(&wtxn, &mut exported, Some(&mut ids)).unwrap(), 3);
+    let sorted: Vec<_> =
+        VecsReader::new(&exported[..], VecsFormat::Fvecs).map(Result::unwrap).collect();
+    assert_eq!(sorted, [vectors[1], vectors[2], vectors[0]]);
+    assert_eq!(String::from_utf8(ids).unwrap(), "10\n20\n30\n");
+
+    let mut bvecs = Vec::new();
+    reader.export_bvecs(&wtxn, &mut bvecs, None).unwrap();
+    assert_eq!(bvecs.len(), 3 * (4 + 3));
+    let mut npy = Vec::new();
+    reader.export_npy(&wtxn, &mut npy, None).unwrap();
+    assert_eq!(NpyReader::new(&npy[..]).unwrap().dimensions(), 3);
+
+    // without ids the items are numbered from 0
+    let writer = Writer::new(database, 1, 3);
+    assert_eq!(writer.import_bvecs(&mut wtxn, &bvecs[..], None).unwrap(), 3);
+    let writer = Writer::new(database, 2, 3);
+    assert_eq!(writer.import_npy(&mut wtxn, &npy[..], None).unwrap(), 3);
+    for index in [1, 2] {
+        let writer = Writer::new(database, index, 3);
+        let imported: Vec<_> = writer.iter(&wtxn).unwrap().map(Result::unwrap).collect();
+        assert_eq!(
+            imported,
+            [(0, vectors[1].to_vec()), (1, vectors[2].to_vec()), (2, vectors[0].to_vec())]
+        );
+    }
+
+    // the bvecs format only stores bytes
+    let writer = Writer::new(database, 3, 3);
+    writer.add_item(&mut wtxn, 0, &[0.5, 256.0, -1.0]).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    let reader = Reader::open(&wtxn, 3, database).unwrap();
+    let err = reader.export_bvecs(&wtxn, Vec::new(), None);
+    assert!(matches!(err, Err(Error::InvalidFileFormat { format: "bvecs", .. })));
+    let err = reader.export_ivecs(&wtxn, Vec::new(), None);
+    assert!(matches!(err, Err(Error::InvalidFileFormat { format: "ivecs", .. })));
+    assert_eq!(reader.export_fvecs(&wtxn, Vec::new(), None).unwrap(), 1);
+
+    // the lengths of a corrupted file are checked before allocating
+    let writer = Writer::new(database, 4, 3);
+    let fvecs = i32::MAX.to_le_bytes();
+    let err = writer.import_fvecs(&mut wtxn, &fvecs[..], None);
+    assert!(matches!(err, Err(Error::InvalidVecDimension { expected: 3, received: 2147483647 })));
+    let mut vectors = VecsReader::new(&fvecs[..], VecsFormat::Fvecs);
+    assert!(matches!(vectors.next(), Some(Err(Error::InvalidFileFormat { format: "fvecs", .. }))));
+    let mut corrupted = b"\x93NUMPY\x02\x00".to_vec();
+    corrupted.extend_from_slice(&u32::MAX.to_le_bytes());
+    let err = NpyReader::new(&corrupted[..]);
+    assert!(matches!(err, Err(Error::InvalidFileFormat { format: "npy", .. })));
+    let header = "{'descr': '<f4', 'fortran_order': False, 'shape': (1, 18446744073709551615), }";
+    let mut corrupted = b"\x93NUMPY\x01\x00".to_vec();
+    corrupted.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    corrupted.extend_from_slice(header.as_bytes());
+    let err = NpyReader::new(&corrupted[..]);
+    assert!(matches!(err, Err(Error::InvalidFileFormat { format: "npy", .. })));
+}
+
+#[test]
+fn dump_and_restore_an_index() {
+    const DIM: usize = 8;
+    let mut rng = rng();
+    let DatabaseHandle { env, database, tempdir: _ } =
+        create_database_indices_with_items::<Euclidean, DIM, M, M0, _>(0..2, 100, &mut rng);
+    let rtxn = env.read_txn().unwrap();
+    let reader = Reader::open(&rtxn, 1, database).unwrap();
+    let mut dump = Vec::new();
+    reader.dump(&rtxn, &mut dump).unwrap();
+
+    // restore it in another environment and under another index
+    let DatabaseHandle { env: other_env, database: other, tempdir: _ } =
+        create_database::<Euclidean>();
+    let mut wtxn = other_env.write_txn().unwrap();
+    let writer = Writer::new(other, 7, DIM);
+    writer.add_item(&mut wtxn, 1000, &[0.0; DIM]).unwrap();
+    writer.restore(&mut wtxn, &dump[..]).unwrap();
+    assert!(!writer.need_build(&wtxn).unwrap());
+    assert!(!writer.contains_item(&wtxn, 1000).unwrap());
+
+    let restored = Reader::open(&wtxn, 7, other).unwrap();
+    assert!(restored.check(&wtxn).unwrap().is_ok());
+    assert_eq!(restored.item_ids(), reader.item_ids());
+    assert_eq!(restored.build_params(), reader.build_params());
+    for item in [0, 42, 99] {
+        let expected = reader.nns(10).by_item(&rtxn, item).unwrap().unwrap().into_nns();
+        let found = restored.nns(10).by_item(&wtxn, item).unwrap().unwrap().into_nns();
+        assert_eq!(found, expected);
+    }
+    let mut restored_dump = Vec::new();
+    restored.dump(&wtxn, &mut restored_dump).unwrap();
+    assert_eq!(restored_dump, dump);
+    wtxn.abort();
+
+    // corrupted, truncated or mismatching dumps are rejected
+    let mut corrupted = dump.clone();
+    // the last byte of the last value, right before the end tag, entries count and checksum
+    corrupted[dump.len() - 14] ^= 1;
+    let mut wtxn = other_env.write_txn().unwrap();
+    let err = writer.restore(&mut wtxn, &corrupted[..]);
+    assert!(matches!(err, Err(Error::InvalidFileFormat { format: "dump", .. })), "{err:?}");
+    let mut too_long = dump.clone();
+    too_long[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+    let err = writer.restore(&mut wtxn, &too_long[..]);
+    assert!(matches!(err, Err(Error::InvalidFileFormat { format: "dump", .. })), "{err:?}");
+    let err = writer.restore(&mut wtxn, &dump[..dump.len() - 1]);
+    assert!(matches!(err, Err(Error::Io(_))), "{err:?}");
+    let err = Writer::new(other, 7, DIM + 1).restore(&mut wtxn, &dump[..]);
+    assert!(matches!(err, Err(Error::InvalidVecDimension { expected: 9, received: 8 })));
+    let err = Writer::<Cosine>::new(other.remap_data_type(), 7, DIM)
+        .restore(&mut wtxn, &dump[..])
+        .map_err(|e| e.to_string());
+    assert_eq!(err, Err("Invalid distance provided. Got cosine but expected euclidean".into()));
+}
+
+#[test]
+fn copy_an_index() {
+    const DIM: usize = 8;
+    let mut rng = rng();
+    let DatabaseHandle { env, database, tempdir: _ } =
+        create_database_indices_with_items::<Euclidean, DIM, M, M0, _>(0..3, 2000, &mut rng);
+    let mut wtxn = env.write_txn().unwrap();
+
+    let writer = Writer::new(database, 1, DIM);
+    writer.copy_from(&mut wtxn, 0).unwrap();
+    let (source, copy) =
+        (Reader::open(&wtxn, 0, database).unwrap(), Reader::open(&wtxn, 1, database).unwrap());
+    assert!(copy.check(&wtxn).unwrap().is_ok());
+    assert_eq!(copy.item_ids(), source.item_ids());
+    for item in [0, 1000, 1999] {
+        let expected = source.nns(10).by_item(&wtxn, item).unwrap().unwrap().into_nns();
+        assert_eq!(copy.nns(10).by_item(&wtxn, item).unwrap().unwrap().into_nns(), expected);
+    }
+    // the index after the copy is untouched
+    let reader = Reader::open(&wtxn, 2, database).unwrap();
+    assert_eq!(reader.n_items(), 2000);
+
+    // the copy is independent from its source
+    writer.del_item(&mut wtxn, 0).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    assert!(Reader::open(&wtxn, 0, database).unwrap().contains_item(&wtxn, 0).unwrap());
+
+    let err = Writer::new(database, 3, DIM + 1).copy_from(&mut wtxn, 0);
+    assert!(matches!(err, Err(Error::InvalidVecDimension { expected: 9, received: 8 })));
+    let err = Writer::<Cosine>::new(database.remap_data_type(), 3, DIM).copy_from(&mut wtxn, 0);
+    assert!(matches!(err, Err(Error::UnmatchingDistance { .. })));
+}
+
+#[test]
+fn merge_an_index_into_another() {
+    const DIM: usize = 8;
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<Euclidean>();
+    let mut rng = rng();
+    let mut wtxn = env.write_txn().unwrap();
+
+    let (shard0, shard1) = (Writer::new(database, 0, DIM), Writer::new(database, 1, DIM));
+    for i in 0..200 {
+        let vector: [f32; DIM] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+        let shard = if i < 100 { &shard0 } else { &shard1 };
+        shard.add_item(&mut wtxn, i, &vector).unwrap();
+    }
+    // a denser graph than the other tests so that the search finds the merged items
+    shard0.builder(&mut rng).m(16).m0(32).build(&mut wtxn).unwrap();
+    shard1.builder(&mut rng).m(16).m0(32).build(&mut wtxn).unwrap();
+
+    let merged = shard0.merge_from(&mut wtxn, 1).unwrap();
+    assert_eq!(merged, RoaringBitmap::from_iter(100..200));
+    assert!(shard0.need_build(&wtxn).unwrap());
+    let stats = shard0.builder(&mut rng).m(16).m0(32).build(&mut wtxn).unwrap();
+    assert_eq!(stats.n_items_inserted(), 100);
+
+    let reader = Reader::open(&wtxn, 0, database).unwrap();
+    assert_eq!(reader.item_ids(), &RoaringBitmap::from_iter(0..200));
+    assert!(reader.check(&wtxn).unwrap().is_ok());
+    for item in [0, 150, 199] {
+        let vector = reader.item_vector(&wtxn, item).unwrap().unwrap();
+        let nns = reader.nns(1).ef_search(200).by_vector(&wtxn, &vector).unwrap().into_nns();
+        assert_eq!(nns[0].0, item);
+    }
+    // the other index is untouched
+    assert_eq!(Reader::open(&wtxn, 1, database).unwrap().n_items(), 100);
+
+    // an index that was never built has no metadata, its items are checked instead
+    Writer::new(database, 2, DIM + 1).add_item(&mut wtxn, 0, &[0.0; DIM + 1]).unwrap();
+    let err = shard0.merge_from(&mut wtxn, 2);
+    assert!(matches!(err, Err(Error::InvalidVecDimension { expected: 8, received: 9 })), "{err:?}");
+    let err = shard0.copy_from(&mut wtxn, 2);
+    assert!(matches!(err, Err(Error::InvalidVecDimension { expected: 8, received: 9 })), "{err:?}");
+    Writer::new(database, 3, DIM).add_item(&mut wtxn, 0, &[0.0; DIM]).unwrap();
+    assert_eq!(shard0.merge_from(&mut wtxn, 3).unwrap(), RoaringBitmap::from_iter([0]));
+}
+
+#[test]
+fn update_and_delete_attributes() {
+    const DIM: usize = 4;
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<Euclidean>();
+    let mut rng = rng();
+    let mut wtxn = env.write_txn().unwrap();
+
+    let writer = Writer::new(database, 0, DIM);
+    for i in 0..10 {
+        writer.add_item(&mut wtxn, i, &[i as f32; DIM]).unwrap();
+        let attributes = Attributes::new().tag("kind", "even").number("n", i as f64);
+        let attributes = if i % 2 == 0 { attributes } else { attributes.tag("kind", "odd") };
+        writer.set_attributes(&mut wtxn, i, &attributes).unwrap();
+    }
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    let odd = Filter::tag("kind", "odd");
+    let even = Filter::tag("kind", "even").and(!odd.clone());
+
+    let attributes = writer.attributes(&wtxn, 3).unwrap().unwrap();
+    assert_eq!(attributes.get("n").collect::<Vec<_>>(), [&AttributeValue::Number(3.0)]);
+    assert_eq!(attributes.get("kind").count(), 2);
+
+    // the old values aren't indexed anymore
+    writer.set_attributes(&mut wtxn, 3, &Attributes::new().number("n", f64::NAN)).unwrap();
+    writer.set_attributes(&mut wtxn, 4, &Attributes::new().tag("kind", "odd")).unwrap();
+    let reader = Reader::open(&wtxn, 0, database).unwrap();
+    assert_eq!(reader.filter(&wtxn, &odd).unwrap(), RoaringBitmap::from_iter([1, 4, 5, 7, 9]));
+    assert_eq!(reader.filter(&wtxn, &even).unwrap(), RoaringBitmap::from_iter([0, 2, 6, 8]));
+    let all = Filter::range("n", ..);
+    assert_eq!(
+        reader.filter(&wtxn, &all).unwrap(),
+        RoaringBitmap::from_iter([0, 1, 2, 5, 6, 7, 8, 9])
+    );
+
+    // the attributes are copied along with the items
+    let other = Writer::new(database, 1, DIM);
+    other.merge_from(&mut wtxn, 0).unwrap();
+    assert_eq!(other.attributes(&wtxn, 4).unwrap(), writer.attributes(&wtxn, 4).unwrap());
+
+    assert!(writer.del_attributes(&mut wtxn, 1).unwrap());
+    assert!(!writer.del_attributes(&mut wtxn, 1).unwrap());
+    assert!(writer.attributes(&wtxn, 1).unwrap().is_none());
+    assert!(writer.del_item(&mut wtxn, 5).unwrap());
+    assert!(writer.attributes(&wtxn, 5).unwrap().is_none());
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    let reader = Reader::open(&wtxn, 0, database).unwrap();
+    assert_eq!(reader.filter(&wtxn, &odd).unwrap(), RoaringBitmap::from_iter([4, 7, 9]));
+    assert!(reader.check(&wtxn).unwrap().is_ok());
+
+    // the postings are removed along with the last item having their value
+    for i in 0..10 {
+        writer.del_attributes(&mut wtxn, i).unwrap();
+    }
+    let postings = database
+        .remap_types::<PrefixCodec, DecodeIgnore>()
+        .prefix_iter(&wtxn, &Prefix::all(0))
+        .unwrap()
+        .remap_key_type::<KeyCodec>()
+        .filter(|res| res.as_ref().unwrap().0.node.mode == NodeMode::Postings)
+        .count();
+    assert_eq!(postings, 0);
+
+    let many_fields = (0..=256).fold(Attributes::new(), |a, i| a.tag(format!("f{i}"), "x"));
+    let err = writer.set_attributes(&mut wtxn, 0, &many_fields);
+    assert!(matches!(err, Err(Error::TooManyAttributeFields(0))));
+}
+
+#[test]
+fn add_and_delete_items_by_key() {
+    const DIM: usize = 4;
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<Euclidean>();
+    let mut rng = rng();
+    let mut wtxn = env.write_txn().unwrap();
+    let writer = Writer::new(database, 0, DIM);
+
+    let uuid = b"67e55044-10b1-426f-9247-bb680e5fe0c8";
+    let long_id = u64::MAX.to_be_bytes();
+    assert_eq!(writer.add_item_by_key(&mut wtxn, uuid, &[0.0; DIM]).unwrap(), 0);
+    assert_eq!(writer.add_item_by_key(&mut wtxn, &long_id, &[1.0; DIM]).unwrap(), 1);
+    // adding a key again updates its item
+    assert_eq!(writer.add_item_by_key(&mut wtxn, uuid, &[0.5; DIM]).unwrap(), 0);
+    assert_eq!(writer.item_vector(&wtxn, 0).unwrap().unwrap(), [0.5; DIM]);
+    // the ids of the items added without a key are skipped
+    writer.add_item(&mut wtxn, 2, &[2.0; DIM]).unwrap();
+    assert_eq!(writer.add_item_by_key(&mut wtxn, b"c", &[3.0; DIM]).unwrap(), 3);
+
+    assert_eq!(writer.item_id(&wtxn, &long_id).unwrap(), Some(1));
+    assert_eq!(writer.external_key(&wtxn, 1).unwrap().as_deref(), Some(&long_id[..]));
+    assert_eq!(writer.external_key(&wtxn, 2).unwrap(), None);
+
+    // the ids of the deleted keys are recycled
+    assert!(writer.del_item_by_key(&mut wtxn, &long_id).unwrap());
+    assert!(!writer.del_item_by_key(&mut wtxn, &long_id).unwrap());
+    assert_eq!(writer.item_id(&wtxn, &long_id).unwrap(), None);
+    assert_eq!(writer.external_key(&wtxn, 1).unwrap(), None);
+    assert_eq!(writer.add_item_by_key(&mut wtxn, b"d", &[1.0; DIM]).unwrap(), 1);
+    assert!(writer.del_item(&mut wtxn, 3).unwrap());
+    assert_eq!(writer.item_id(&wtxn, b"c").unwrap(), None);
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+
+    let reader = Reader::open(&wtxn, 0, database).unwrap();
+    assert_eq!(reader.item_ids(), &RoaringBitmap::from_iter([0, 1, 2]));
+    assert!(reader.check(&wtxn).unwrap().is_ok());
+    let searched = reader.nns(2).by_key(&wtxn, b"d").unwrap().unwrap();
+    let keys: Vec<_> =
+        reader.external_keys(&wtxn, &searched.nns).unwrap().into_iter().map(|(k, _)| k).collect();
+    assert_eq!(keys, [Some(uuid.to_vec()), None]);
+    assert!(reader.nns(2).by_key(&wtxn, &long_id).unwrap().is_none());
+
+    // a freed id taken by an item added without a key isn't allocated again
+    assert!(writer.del_item_by_key(&mut wtxn, b"d").unwrap());
+    writer.add_item(&mut wtxn, 1, &[1.0; DIM]).unwrap();
+    assert_eq!(writer.add_item_by_key(&mut wtxn, b"e", &[4.0; DIM]).unwrap(), 3);
+    assert_eq!(writer.add_item_by_key(&mut wtxn, b"f", &[5.0; DIM]).unwrap(), 4);
+    assert_eq!(writer.external_key(&wtxn, 1).unwrap(), None);
+}
+
+#[test]
+fn merge_indexes_with_external_keys() {
+    const DIM: usize = 4;
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<Euclidean>();
+    let mut rng = rng();
+    let mut wtxn = env.write_txn().unwrap();
+
+    let (target, source) = (Writer::new(database, 0, DIM), Writer::new(database, 1, DIM));
+    for (i, key) in [b"a", b"b", b"c"].into_iter().enumerate() {
+        target.add_item_by_key(&mut wtxn, key, &[i as f32; DIM]).unwrap();
+    }
+    // the keys of the source get the ids 0 and 1, already taken by other keys in the target
+    source.add_item_by_key(&mut wtxn, b"c", &[10.0; DIM]).unwrap();
+    source.add_item_by_key(&mut wtxn, b"d", &[11.0; DIM]).unwrap();
+    source.set_attributes(&mut wtxn, 1, &Attributes::new().tag("kind", "d")).unwrap();
+    source.add_item(&mut wtxn, 3, &[12.0; DIM]).unwrap();
+
+    let merged = target.merge_from(&mut wtxn, 1).unwrap();
+    assert_eq!(merged, RoaringBitmap::from_iter([2, 3, 4]));
+    // the item without a key keeps its id, the new key is allocated after it
+    assert_eq!(target.item_vector(&wtxn, 3).unwrap().unwrap(), [12.0; DIM]);
+    assert_eq!(target.item_id(&wtxn, b"d").unwrap(), Some(4));
+    assert_eq!(target.item_vector(&wtxn, 4).unwrap().unwrap(), [11.0; DIM]);
+    assert_eq!(target.attributes(&wtxn, 4).unwrap(), Some(Attributes::new().tag("kind", "d")));
+    // the existing key is updated and the other keys keep their items
+    assert_eq!(target.item_id(&wtxn, b"c").unwrap(), Some(2));
+    assert_eq!(target.item_vector(&wtxn, 2).unwrap().unwrap(), [10.0; DIM]);
+    assert_eq!(target.item_id(&wtxn, b"a").unwrap(), Some(0));
+    assert_eq!(target.item_vector(&wtxn, 0).unwrap().unwrap(), [0.0; DIM]);
+    assert_eq!(target.external_key(&wtxn, 1).unwrap().as_deref(), Some(&b"b"[..]));
+
+    target.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    let reader = Reader::open(&wtxn, 0, database).unwrap();
+    assert_eq!(reader.item_ids(), &RoaringBitmap::from_iter(0..5));
+    assert!(reader.check(&wtxn).unwrap().is_ok());
+    // the source is untouched
+    assert_eq!(source.item_id(&wtxn, b"d").unwrap(), Some(1));
+}
+
+#[test]
+fn convert_cosine_to_half_precision_keeps_links() {
+    const DIM: usize = 64;
+    let mut rng = rng();
+
+    let DatabaseHandle { env, database, tempdir: _ } =
+        create_database_indices_with_items::<Cosine, DIM, M, M0, _>(0..1, 100, &mut rng);
+
+    let count_links = |rtxn: &heed::RoTxn| {
+        database
+            .remap_types::<PrefixCodec, DecodeIgnore>()
+            .prefix_iter(rtxn, &Prefix::links(0))
+            .unwrap()
+            .remap_key_type::<KeyCodec>()
+            .count()
+    };
+
+    let mut wtxn = env.write_txn().unwrap();
+    let n_links = count_links(&wtxn);
+    let writer = Writer::<Cosine>::new(database, 0, DIM);
+    let original = writer.item_vector(&wtxn, 0).unwrap().unwrap();
+
+    let writer = writer.prepare_changing_distance::<CosineF16>(&mut wtxn).unwrap();
+    assert_eq!(count_links(&wtxn), n_links);
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    wtxn.commit().unwrap();
+
+    let rtxn = env.read_txn().unwrap();
+    let database = database.remap_data_type();
+    let reader = Reader::<CosineF16>::open(&rtxn, 0, database).unwrap();
+
+    let vector = reader.item_vector(&rtxn, 0).unwrap().unwrap();
+    for (o, v) in original.iter().zip(&vector) {
+        assert!((o - v).abs() < 1e-3);
+    }
+
+    let found = reader.nns(10).by_vector(&rtxn, &original).unwrap().into_nns();
+    assert_eq!(found.len(), 10);
+
+    // the graph of this test is too sparse to always find the closest item, scan instead
+    let all = RoaringBitmap::from_iter(0..100);
+    let found = reader
+        .nns(1)
+        .candidates(&all)
+        .linear_below(101)
+        .linear_below_ratio(1.0)
+        .by_vector(&rtxn, &original)
+        .unwrap()
+        .into_nns();
+    assert_eq!(found[0].0, 0);
+}
+
+#[test]
+fn half_precision_rejects_values_out_of_range() {
+    let DatabaseHandle { env, database, tempdir: _ } = create_database::<CosineF16>();
+    let mut wtxn = env.write_txn().unwrap();
+    let writer = Writer::new(database, 0, 3);
+
+    writer.add_item(&mut wtxn, 0, &[65504.0, -65504.0, 1.0]).unwrap();
+    let err = writer.add_item(&mut wtxn, 1, &[1.0, -70000.0, 1.0]).unwrap_err();
+    assert!(matches!(err, Error::ValueOutOfRange { value, .. } if value == -70000.0), "{err}");
+    assert!(!writer.contains_item(&wtxn, 1).unwrap());
+
+    // the single precision distances can store them
+    let writer = Writer::<Cosine>::new(database.remap_data_type(), 1, 3);
+    writer.add_item(&mut wtxn, 0, &[1.0, -70000.0, 1.0]).unwrap();
+}
+
+#[test]
+fn convert_cosine_to_scalar_quantized_keeps_links() {
+    const DIM: usize = 64;
+    let mut rng = rng();
+
+    let DatabaseHandle { env, database, tempdir: _ } =
+        create_database_indices_with_items::<Cosine, DIM, M, M0, _>(0..1, 100, &mut rng);
+
+    let count_links = |rtxn: &heed::RoTxn| {
+        database
+            .remap_types::<PrefixCodec, DecodeIgnore>()
+            .prefix_iter(rtxn, &Prefix::links(0))
+            .unwrap()
+            .remap_key_type::<KeyCodec>()
+            .count()
+    };
+
+    let mut wtxn = env.write_txn().unwrap();
+    let n_links = count_links(&wtxn);
+    let writer = Writer::<Cosine>::new(database, 0, DIM);
+    let original = writer.item_vector(&wtxn, 0).unwrap().unwrap();
+
+    let writer = writer.prepare_changing_distance::<ScalarQuantizedCosine>(&mut wtxn).unwrap();
+    assert_eq!(count_links(&wtxn), n_links);
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    wtxn.commit().unwrap();
+
+    let rtxn = env.read_txn().unwrap();
+    let database = database.remap_data_type();
+    let reader = Reader::<ScalarQuantizedCosine>::open(&rtxn, 0, database).unwrap();
+
+    // the random vectors are in [-1, 1] so the quantization error is at most half a step
+    let vector = reader.item_vector(&rtxn, 0).unwrap().unwrap();
+    for (o, v) in original.iter().zip(&vector) {
+        assert!((o - v).abs() <= 1.0 / 255.0 + 1e-6);
+    }
+
+    let found = reader.nns(10).by_vector(&rtxn, &original).unwrap().into_nns();
+    assert_eq!(found.len(), 10);
+
+    // the quantized query is encoded exactly like its quantized item
+    let all = RoaringBitmap::from_iter(0..100);
+    let found = reader
+        .nns(1)
+        .candidates(&all)
+        .linear_below(101)
+        .linear_below_ratio(1.0)
+        .by_vector(&rtxn, &original)
+        .unwrap()
+        .into_nns();
+    assert_eq!(found[0].0, 0);
+}
+
+#[test]
+fn scalar_quantized_is_calibrated_at_build() {
+    const DIM: usize = 16;
+    let mut rng = rng();
+    let DatabaseHandle { env, database, tempdir: _ } =
+        create_database::<ScalarQuantizedEuclidean>();
+    let vectors: Vec<Vec<f32>> =
+        (0..50).map(|_| (0..DIM).map(|_| rng.gen_range(-1.0..1.0)).collect()).collect();
+
+    // Clearing the index also deletes its calibration, the next build computes a new one
+    for _ in 0..2 {
+        let mut wtxn = env.write_txn().unwrap();
+        let writer = Writer::new(database, 0, DIM);
+        writer.clear(&mut wtxn).unwrap();
+        for (id, vector) in vectors.iter().enumerate() {
+            writer.add_item(&mut wtxn, id as u32, vector).unwrap();
+        }
+        assert_eq!(writer.item_vector(&wtxn, 3).unwrap().as_ref(), Some(&vectors[3]));
+        writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = env.read_txn().unwrap();
+        let reader = Reader::open(&rtxn, 0, database).unwrap();
+        let all = RoaringBitmap::from_iter(0..vectors.len() as u32);
+        for (id, vector) in vectors.iter().enumerate() {
+            let found = reader
+                .nns(1)
+                .candidates(&all)
+                .linear_below(vectors.len() + 1)
+                .linear_below_ratio(1.0)
+                .by_vector(&rtxn, vector)
+                .unwrap()
+                .into_nns();
+            assert_eq!(found[0], (id as u32, 0.0));
+        }
+    }
+
+    // an item out of the calibrated range recalibrates the index instead of being clamped
+    let mut wtxn = env.write_txn().unwrap();
+    let writer = Writer::new(database, 0, DIM);
+    writer.add_item(&mut wtxn, 50, &[10.0; DIM]).unwrap();
+    let stats = writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    assert_eq!(stats.n_items_inserted(), 51);
+    let reader = Reader::open(&wtxn, 0, database).unwrap();
+    let decoded = reader.item_vector(&wtxn, 50).unwrap().unwrap();
+    assert!(decoded.iter().all(|x| (x - 10.0).abs() < 0.1), "{decoded:?}");
+    assert!(reader.check(&wtxn).unwrap().is_ok());
+}
+
+#[test]
+fn convert_to_binary_quantized_keeps_full_precision() {
+    const DIM: usize = 32;
+    let mut rng = rng();
+
+    let DatabaseHandle { env, database, tempdir: _ } =
+        create_database_indices_with_items::<Cosine, DIM, M, M0, _>(0..1, 50, &mut rng);
+
+    let mut wtxn = env.write_txn().unwrap();
+    let mut writer = Writer::<Cosine>::new(database, 0, DIM);
+    let original = writer.item_vector(&wtxn, 7).unwrap().unwrap();
+
+    writer.set_full_precision(true);
+    let writer = writer.prepare_changing_distance::<BinaryQuantizedCosine>(&mut wtxn).unwrap();
+    writer.builder(&mut rng).m(M).m0(M0).build(&mut wtxn).unwrap();
+    wtxn.commit().unwrap();
+
+    let rtxn = env.read_txn().unwrap();
+    let reader =
+        Reader::<BinaryQuantizedCosine>::open(&rtxn, 0, database.remap_data_type()).unwrap();
+    assert_eq!(reader.full_precision_vector(&rtxn, 7).unwrap(), Some(original.clone()));
+
+    let found = reader.nns(5).rescore(10).by_vector(&rtxn, &original).unwrap().into_nns();
+    assert_eq!(found[0].0, 7);
+}
@@ -72,6 +72,10 @@ impl UnalignedVectorCodec for Binary {
         vec.as_bytes().iter().all(|b| *b == 0)
     }
 
+    fn element_size() -> usize {
+        PACKED_WORD_BYTES
+    }
+
     fn word_size() -> usize {
         PACKED_WORD_BITS
     }
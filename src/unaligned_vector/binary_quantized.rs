@@ -72,6 +72,10 @@ impl UnalignedVectorCodec for BinaryQuantized {
         vec.as_bytes().iter().all(|b| *b == 0)
     }
 
+    fn element_size() -> usize {
+        QUANTIZED_WORD_BYTES
+    }
+
     fn word_size() -> usize {
         QUANTIZED_WORD_BITS
     }
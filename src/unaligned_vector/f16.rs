@@ -0,0 +1,59 @@
+use std::borrow::Cow;
+use std::mem::{size_of, transmute};
+
+use half::f16;
+
+use super::{SizeMismatch, UnalignedVector, UnalignedVectorCodec};
+
+impl UnalignedVectorCodec for f16 {
+    /// Creates an unaligned slice of f16 wrapper from a slice of bytes.
+    fn from_bytes(bytes: &[u8]) -> Result<Cow<'_, UnalignedVector<Self>>, SizeMismatch> {
+        let rem = bytes.len() % size_of::<f16>();
+        if rem == 0 {
+            // safety: `UnalignedVector` is transparent
+            Ok(Cow::Borrowed(unsafe { transmute::<&[u8], &UnalignedVector<f16>>(bytes) }))
+        } else {
+            Err(SizeMismatch { vector_codec: "f16", rem })
+        }
+    }
+
+    /// Creates an unaligned slice of f16 wrapper from a slice of f32.
+    /// Allocates since every f32 must be converted.
+    fn from_slice(slice: &[f32]) -> Cow<'static, UnalignedVector<Self>> {
+        let bytes = slice.iter().flat_map(|f| f16::from_f32(*f).to_ne_bytes()).collect();
+        Cow::Owned(bytes)
+    }
+
+    fn from_vec(vec: Vec<f32>) -> Cow<'static, UnalignedVector<Self>> {
+        Cow::Owned(Self::from_slice(&vec).into_owned())
+    }
+
+    fn to_vec(vec: &UnalignedVector<Self>) -> Vec<f32> {
+        let iter = vec.iter();
+        let mut ret = Vec::with_capacity(iter.len());
+        ret.extend(iter);
+        ret
+    }
+
+    /// Returns an iterator of f32 that are read from the slice and widened.
+    fn iter(vec: &UnalignedVector<Self>) -> impl ExactSizeIterator<Item = f32> + '_ {
+        vec.vector.chunks_exact(size_of::<f16>()).map(|b| f16::from_ne_bytes([b[0], b[1]]).to_f32())
+    }
+
+    /// Return the number of f16 that fits into this slice.
+    fn len(vec: &UnalignedVector<Self>) -> usize {
+        vec.vector.len() / size_of::<f16>()
+    }
+
+    fn is_zero(vec: &UnalignedVector<Self>) -> bool {
+        vec.iter().all(|v| v == 0.0)
+    }
+
+    fn element_size() -> usize {
+        size_of::<f16>()
+    }
+
+    fn max_value() -> f32 {
+        f16::MAX.to_f32()
+    }
+}
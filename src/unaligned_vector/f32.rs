@@ -52,4 +52,8 @@ impl UnalignedVectorCodec for f32 {
     fn is_zero(vec: &UnalignedVector<Self>) -> bool {
         vec.iter().all(|v| v == 0.0)
     }
+
+    fn element_size() -> usize {
+        size_of::<f32>()
+    }
 }
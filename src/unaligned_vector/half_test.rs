@@ -0,0 +1,73 @@
+use half::{bf16, f16};
+use insta::assert_debug_snapshot;
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use crate::internals::UnalignedVectorCodec;
+
+#[test]
+fn test_f16_from_slice() {
+    let original = [0.1, 0.2, -0.3, 65504.0, 1e-8];
+    let vector = f16::from_slice(&original);
+    assert_eq!(vector.as_bytes().len(), original.len() * 2);
+
+    let iter_vec: Vec<_> = f16::iter(&vector).collect();
+    assert_debug_snapshot!(iter_vec, @r###"
+    [
+        0.099975586,
+        0.19995117,
+        -0.30004883,
+        65504.0,
+        0.0,
+    ]
+    "###);
+}
+
+#[test]
+fn test_bf16_from_slice() {
+    let original = [0.1, 0.2, -0.3, 65504.0, 1e-8];
+    let vector = bf16::from_slice(&original);
+    assert_eq!(vector.as_bytes().len(), original.len() * 2);
+
+    let iter_vec: Vec<_> = bf16::iter(&vector).collect();
+    assert_debug_snapshot!(iter_vec, @r###"
+    [
+        0.100097656,
+        0.20019531,
+        -0.30078125,
+        65536.0,
+        1.0011718e-8,
+    ]
+    "###);
+}
+
+#[test]
+fn test_from_bytes_size_mismatch() {
+    assert!(f16::from_bytes(&[0, 0, 0]).is_err());
+    assert!(bf16::from_bytes(&[0, 0, 0]).is_err());
+    assert!(f16::from_bytes(&[0, 0, 0, 0]).is_ok());
+}
+
+proptest! {
+    #[test]
+    fn f16_roundtrip_is_close(original in vec(-1000.0f32..1000.0, 0..1000)) {
+        let vector = f16::from_slice(&original);
+        let decoded = f16::to_vec(&vector);
+        prop_assert_eq!(decoded.len(), original.len());
+        for (o, d) in original.iter().zip(&decoded) {
+            // 11 bits of precision
+            prop_assert!((o - d).abs() <= o.abs() / 1024.0 + 1e-4);
+        }
+    }
+
+    #[test]
+    fn bf16_roundtrip_is_close(original in vec(-1e30f32..1e30, 0..1000)) {
+        let vector = bf16::from_slice(&original);
+        let decoded = bf16::to_vec(&vector);
+        prop_assert_eq!(decoded.len(), original.len());
+        for (o, d) in original.iter().zip(&decoded) {
+            // 8 bits of precision
+            prop_assert!((o - d).abs() <= o.abs() / 128.0);
+        }
+    }
+}
@@ -6,13 +6,19 @@ use std::mem::transmute;
 pub use binary::Binary;
 pub use binary_quantized::BinaryQuantized;
 use bytemuck::pod_collect_to_vec;
+pub use scalar_quantized::ScalarQuantized;
 
+mod bf16;
 mod binary;
 mod binary_quantized;
+mod f16;
 mod f32;
+mod scalar_quantized;
 
 #[cfg(test)]
 mod binary_quantized_test;
+#[cfg(test)]
+mod half_test;
 
 /// Determine the way the vectors should be read and written from the database
 pub trait UnalignedVectorCodec: std::borrow::ToOwned + Sized {
@@ -43,10 +49,20 @@ pub trait UnalignedVectorCodec: std::borrow::ToOwned + Sized {
     /// Returns true if all the elements in the vector are equal to 0.
     fn is_zero(vec: &UnalignedVector<Self>) -> bool;
 
+    /// Returns the size in bytes of the elements, or the packed words, making the vector. Their
+    /// bytes are stored in the byte order of the machine.
+    fn element_size() -> usize;
+
     /// Returns the bit-packing size if quantized
     fn word_size() -> usize {
         1
     }
+
+    /// Returns the biggest magnitude the codec can store, the bigger values would become
+    /// infinite.
+    fn max_value() -> f32 {
+        f32::INFINITY
+    }
 }
 
 /// A wrapper struct that is used to read unaligned vectors directly from memory.
@@ -0,0 +1,47 @@
+use std::borrow::Cow;
+use std::mem::{size_of, transmute};
+
+use super::{SizeMismatch, UnalignedVector, UnalignedVectorCodec};
+
+/// Stores every dimension as a `u8` code.
+///
+/// The codec only knows about the codes, the f32 given to it are considered to already be codes.
+/// Mapping the user vectors to codes and back is done with the [`Calibration`](crate::internals::Calibration)
+/// of the index by the scalar quantized distances.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScalarQuantized {}
+
+impl UnalignedVectorCodec for ScalarQuantized {
+    fn from_bytes(bytes: &[u8]) -> Result<Cow<'_, UnalignedVector<Self>>, SizeMismatch> {
+        // safety: `UnalignedVector` is transparent
+        Ok(Cow::Borrowed(unsafe { transmute::<&[u8], &UnalignedVector<Self>>(bytes) }))
+    }
+
+    fn from_slice(slice: &[f32]) -> Cow<'static, UnalignedVector<Self>> {
+        Cow::Owned(slice.iter().map(|code| code.round().clamp(0.0, u8::MAX as f32) as u8).collect())
+    }
+
+    fn from_vec(vec: Vec<f32>) -> Cow<'static, UnalignedVector<Self>> {
+        Cow::Owned(Self::from_slice(&vec).into_owned())
+    }
+
+    fn to_vec(vec: &UnalignedVector<Self>) -> Vec<f32> {
+        vec.iter().collect()
+    }
+
+    fn iter(vec: &UnalignedVector<Self>) -> impl ExactSizeIterator<Item = f32> + '_ {
+        vec.vector.iter().map(|code| *code as f32)
+    }
+
+    fn len(vec: &UnalignedVector<Self>) -> usize {
+        vec.vector.len()
+    }
+
+    fn is_zero(vec: &UnalignedVector<Self>) -> bool {
+        vec.vector.iter().all(|code| *code == 0)
+    }
+
+    fn element_size() -> usize {
+        size_of::<u8>()
+    }
+}
@@ -0,0 +1,345 @@
+//! Streams vectors in and out of the file formats of the [ANN-benchmarks] datasets and of numpy.
+//!
+//! The `.fvecs`, `.bvecs` and `.ivecs` formats store each vector as its little-endian `i32`
+//! number of dimensions followed by its components, respectively `f32`s, `u8`s and `i32`s. The
+//! `.npy` format stores a two-dimensional array of floats behind a small header.
+//!
+//! Most of the time you want [`Writer::import_fvecs`](crate::Writer::import_fvecs) and
+//! [`Reader::export_fvecs`](crate::Reader::export_fvecs), or their siblings, which insert and
+//! dump the vectors of an index. The readers of this module are useful to look at a file before
+//! importing it, e.g. to check its number of dimensions.
+//!
+//! The optional id-mapping files read and written along with the vectors contain one item id per
+//! line, the id of the n-th vector of the file being on the n-th line.
+//!
+//! [ANN-benchmarks]: http://corpus-texmex.irisa.fr/
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use crate::{Error, ItemId, Result};
+
+/// The largest vector read from a file, larger lengths can only come from a corrupted file.
+const MAX_VECTOR_LEN: usize = 1 << 30;
+
+/// The largest `.npy` header, numpy itself refuses to read headers above 10kB.
+const MAX_NPY_HEADER_LEN: usize = 1 << 16;
+
+/// One of the `.*vecs` formats, which only differ by the type of the components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VecsFormat {
+    /// The components are `f32`s.
+    Fvecs,
+    /// The components are `u8`s.
+    Bvecs,
+    /// The components are `i32`s.
+    Ivecs,
+}
+
+impl VecsFormat {
+    /// The name of the format, as in the extension of its files.
+    pub fn name(self) -> &'static str {
+        match self {
+            VecsFormat::Fvecs => "fvecs",
+            VecsFormat::Bvecs => "bvecs",
+            VecsFormat::Ivecs => "ivecs",
+        }
+    }
+
+    fn component_size(self) -> usize {
+        match self {
+            VecsFormat::Fvecs | VecsFormat::Ivecs => 4,
+            VecsFormat::Bvecs => 1,
+        }
+    }
+
+    /// Writes a vector in this format, the `bvecs` and `ivecs` formats only accept integers in
+    /// the range of their components.
+    pub(crate) fn write_vector(self, output: &mut impl Write, vector: &[f32]) -> Result<()> {
+        output.write_all(&(vector.len() as i32).to_le_bytes())?;
+        for &x in vector {
+            match self {
+                VecsFormat::Fvecs => output.write_all(&x.to_le_bytes())?,
+                VecsFormat::Bvecs if x.fract() == 0.0 && (0.0..=255.0).contains(&x) => {
+                    output.write_all(&[x as u8])?
+                }
+                VecsFormat::Ivecs
+                    if x.fract() == 0.0 && (-2.0f32.powi(31)..2.0f32.powi(31)).contains(&x) =>
+                {
+                    output.write_all(&(x as i32).to_le_bytes())?
+                }
+                VecsFormat::Bvecs | VecsFormat::Ivecs => {
+                    return Err(invalid(self.name(), format!("{x} doesn't fit the components")))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Iterates over the vectors of a `.fvecs`, `.bvecs` or `.ivecs` file.
+pub struct VecsReader<R> {
+    reader: BufReader<R>,
+    format: VecsFormat,
+    dimensions: Option<usize>,
+    buffer: Vec<u8>,
+}
+
+impl<R: Read> VecsReader<R> {
+    /// Reads the vectors of `reader` stored in the given format.
+    pub fn new(reader: R, format: VecsFormat) -> Self {
+        VecsReader { reader: BufReader::new(reader), format, dimensions: None, buffer: Vec::new() }
+    }
+
+    /// Returns an [`Error::InvalidVecDimension`] for the vectors that don't have these
+    /// dimensions, before reading them.
+    pub fn expect_dimensions(mut self, dimensions: usize) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    fn read_vector(&mut self) -> Result<Option<Vec<f32>>> {
+        let mut dimensions = [0; 4];
+        if !read_or_eof(&mut self.reader, &mut dimensions)? {
+            return Ok(None);
+        }
+        let dimensions = usize::try_from(i32::from_le_bytes(dimensions))
+            .map_err(|_| invalid(self.format.name(), "negative dimensions".to_string()))?;
+        if let Some(expected) = self.dimensions.filter(|&expected| expected != dimensions) {
+            return Err(Error::InvalidVecDimension { expected, received: dimensions });
+        }
+
+        let len = vector_len(self.format.name(), dimensions, self.format.component_size())?;
+        self.buffer.resize(len, 0);
+        self.reader.read_exact(&mut self.buffer)?;
+        let vector = match self.format {
+            VecsFormat::Fvecs => self.buffer.chunks_exact(4).map(f32_from_le).collect(),
+            VecsFormat::Bvecs => self.buffer.iter().map(|&x| x as f32).collect(),
+            VecsFormat::Ivecs => self
+                .buffer
+                .chunks_exact(4)
+                .map(|b| i32::from_le_bytes(b.try_into().unwrap()) as f32)
+                .collect(),
+        };
+        Ok(Some(vector))
+    }
+}
+
+impl<R: Read> Iterator for VecsReader<R> {
+    type Item = Result<Vec<f32>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_vector().transpose()
+    }
+}
+
+/// Iterates over the rows of a `.npy` file storing a two-dimensional, C-ordered, array of
+/// little-endian `f32`s or `f64`s.
+pub struct NpyReader<R> {
+    reader: BufReader<R>,
+    rows_left: usize,
+    columns: usize,
+    double: bool,
+    buffer: Vec<u8>,
+}
+
+impl<R: Read> NpyReader<R> {
+    /// Reads the header of the file and returns an error if the array isn't supported.
+    pub fn new(reader: R) -> Result<Self> {
+        let mut reader = BufReader::new(reader);
+
+        let mut prelude = [0; 8];
+        reader.read_exact(&mut prelude)?;
+        if &prelude[..6] != b"\x93NUMPY" {
+            return Err(invalid("npy", "missing magic string".to_string()));
+        }
+        let len = match prelude[6] {
+            1 => {
+                let mut len = [0; 2];
+                reader.read_exact(&mut len)?;
+                u16::from_le_bytes(len) as usize
+            }
+            _ => {
+                let mut len = [0; 4];
+                reader.read_exact(&mut len)?;
+                u32::from_le_bytes(len) as usize
+            }
+        };
+        if len > MAX_NPY_HEADER_LEN {
+            return Err(invalid("npy", format!("a header of {len} bytes is too long")));
+        }
+        let mut header = vec![0; len];
+        reader.read_exact(&mut header)?;
+        let header = String::from_utf8_lossy(&header);
+
+        let double = match header_value(&header, "descr") {
+            Some("'<f4'") => false,
+            Some("'<f8'") => true,
+            descr => {
+                return Err(invalid("npy", format!("unsupported dtype {}", descr.unwrap_or("?"))))
+            }
+        };
+        if header_value(&header, "fortran_order") != Some("False") {
+            return Err(invalid("npy", "only C-ordered arrays are supported".to_string()));
+        }
+        let shape: Vec<usize> = header_value(&header, "shape")
+            .and_then(|shape| shape.strip_prefix('(')?.strip_suffix(')'))
+            .ok_or_else(|| invalid("npy", "missing shape".to_string()))?
+            .split(',')
+            .map(str::trim)
+            .filter(|dim| !dim.is_empty())
+            .map(|dim| dim.parse().map_err(|_| invalid("npy", format!("invalid dimension {dim}"))))
+            .collect::<Result<_>>()?;
+        let [rows, columns] = shape[..] else {
+            return Err(invalid("npy", format!("expected a two-dimensional array, got {shape:?}")));
+        };
+        vector_len("npy", columns, if double { 8 } else { 4 })?;
+
+        Ok(NpyReader { reader, rows_left: rows, columns, double, buffer: Vec::new() })
+    }
+
+    /// The number of columns of the array, the dimensions of the vectors.
+    pub fn dimensions(&self) -> usize {
+        self.columns
+    }
+
+    fn read_vector(&mut self) -> Result<Option<Vec<f32>>> {
+        if self.rows_left == 0 {
+            return Ok(None);
+        }
+        self.rows_left -= 1;
+
+        // the length was checked along with the header
+        let size = if self.double { 8 } else { 4 };
+        self.buffer.resize(self.columns * size, 0);
+        self.reader.read_exact(&mut self.buffer)?;
+        let vector = if self.double {
+            self.buffer
+                .chunks_exact(8)
+                .map(|b| f64::from_le_bytes(b.try_into().unwrap()) as f32)
+                .collect()
+        } else {
+            self.buffer.chunks_exact(4).map(f32_from_le).collect()
+        };
+        Ok(Some(vector))
+    }
+}
+
+impl<R: Read> Iterator for NpyReader<R> {
+    type Item = Result<Vec<f32>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_vector().transpose()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // the file may be shorter than its header says, it must not be trusted to allocate
+        (0, Some(self.rows_left))
+    }
+}
+
+/// Writes the header of a `.npy` file storing a `rows` x `columns` array of `f32`s, the rows
+/// must then be written one after the other.
+pub(crate) fn write_npy_header(output: &mut impl Write, rows: usize, columns: usize) -> Result<()> {
+    let mut header =
+        format!("{{'descr': '<f4', 'fortran_order': False, 'shape': ({rows}, {columns}), }}");
+    // The data must be aligned on 64 bytes, counting the prelude and the final newline.
+    let unpadded = 10 + header.len() + 1;
+    header.extend(std::iter::repeat_n(' ', unpadded.next_multiple_of(64) - unpadded));
+    header.push('\n');
+
+    output.write_all(b"\x93NUMPY\x01\x00")?;
+    output.write_all(&(header.len() as u16).to_le_bytes())?;
+    output.write_all(header.as_bytes())?;
+    Ok(())
+}
+
+/// Reads the item ids of an id-mapping file, or numbers the items from 0 without one.
+pub(crate) struct IdsReader<'a> {
+    lines: Option<io::Lines<BufReader<&'a mut dyn Read>>>,
+    next: ItemId,
+}
+
+impl<'a> IdsReader<'a> {
+    pub fn new(ids: Option<&'a mut dyn Read>) -> Self {
+        IdsReader { lines: ids.map(|ids| BufReader::new(ids).lines()), next: 0 }
+    }
+
+    /// Returns the id of the next vector.
+    pub fn next_id(&mut self) -> Result<ItemId> {
+        match &mut self.lines {
+            Some(lines) => match next_line(lines)? {
+                Some(line) => line
+                    .parse()
+                    .map_err(|_| invalid("id-mapping", format!("{line:?} is not an item id"))),
+                None => Err(invalid("id-mapping", "fewer ids than vectors".to_string())),
+            },
+            None => {
+                let id = self.next;
+                self.next = id.checked_add(1).ok_or(Error::DatabaseFull)?;
+                Ok(id)
+            }
+        }
+    }
+
+    /// Returns an error if there are ids left without a vector.
+    pub fn finish(mut self) -> Result<()> {
+        match &mut self.lines {
+            Some(lines) => match next_line(lines)? {
+                Some(_) => Err(invalid("id-mapping", "more ids than vectors".to_string())),
+                None => Ok(()),
+            },
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the next non-blank line, trimmed.
+fn next_line(lines: &mut impl Iterator<Item = io::Result<String>>) -> Result<Option<String>> {
+    for line in lines {
+        let line = line?;
+        if !line.trim().is_empty() {
+            return Ok(Some(line.trim().to_string()));
+        }
+    }
+    Ok(None)
+}
+
+/// Fills `buf` and returns `false` if the reader was already at its end.
+fn read_or_eof(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
+
+/// Returns the raw value of a key of the python dictionary of a `.npy` header.
+fn header_value<'h>(header: &'h str, key: &str) -> Option<&'h str> {
+    let (_, rest) = header.split_once(&format!("'{key}':"))?;
+    let rest = rest.trim_start();
+    let end = if rest.starts_with('(') { rest.find(')')? + 1 } else { rest.find(',')? };
+    Some(rest[..end].trim())
+}
+
+/// Returns the length in bytes of a vector of `dimensions` components of `size` bytes, or an
+/// error if it's too long to be read.
+fn vector_len(format: &'static str, dimensions: usize, size: usize) -> Result<usize> {
+    dimensions
+        .checked_mul(size)
+        .filter(|&len| len <= MAX_VECTOR_LEN)
+        .ok_or_else(|| invalid(format, format!("a vector of {dimensions} dimensions is too long")))
+}
+
+fn f32_from_le(bytes: &[u8]) -> f32 {
+    f32::from_le_bytes(bytes.try_into().unwrap())
+}
+
+fn invalid(format: &'static str, reason: String) -> Error {
+    Error::InvalidFileFormat { format, reason }
+}
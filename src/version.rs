@@ -49,6 +49,10 @@ impl heed::BytesDecode<'_> for VersionCodec {
     type DItem = Version;
 
     fn bytes_decode(bytes: &'_ [u8]) -> Result<Self::DItem, BoxedError> {
+        if bytes.len() != size_of::<u32>() * 3 {
+            return Err(format!("invalid version of {} bytes", bytes.len()).into());
+        }
+
         let major = BigEndian::read_u32(bytes);
         let bytes = &bytes[size_of_val(&major)..];
         let minor = BigEndian::read_u32(bytes);
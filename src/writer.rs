@@ -1,28 +1,39 @@
 use std::any::TypeId;
+use std::io::Read;
 use std::path::PathBuf;
 
-use heed::types::DecodeIgnore;
-use heed::{PutFlags, RoTxn, RwTxn};
+use heed::types::{Bytes, DecodeIgnore};
+use heed::{RoTxn, RwTxn};
 use rand::{Rng, SeedableRng};
 use roaring::RoaringBitmap;
 use steppe::NoProgress;
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
+use crate::attributes;
+use crate::calibration::{Calibration, CalibrationCodec};
 use crate::distance::Distance;
+use crate::dump;
+use crate::external_keys;
 use crate::hnsw::HnswBuilder;
 use crate::internals::KeyCodec;
 use crate::item_iter::ItemIter;
-use crate::node::{Item, ItemIds, NodeCodec};
+use crate::node::{FullPrecisionCodec, ItemIds, NodeCodec};
 use crate::progress::HannoyBuild;
 use crate::reader::get_item;
-use crate::unaligned_vector::UnalignedVector;
+use crate::stats::BuildStats;
+use crate::unaligned_vector::{UnalignedVector, UnalignedVectorCodec};
 use crate::update_status::{UpdateStatus, UpdateStatusCodec};
+use crate::vecs::{IdsReader, NpyReader, VecsFormat, VecsReader};
 use crate::version::{Version, VersionCodec};
 use crate::{
-    Database, Error, ItemId, Key, Metadata, MetadataCodec, Node, Prefix, PrefixCodec, Result,
-    CANCELLATION_PROBING,
+    Attributes, BuildParams, BuildParamsCodec, Database, Error, ItemId, Key, Metadata,
+    MetadataCodec, Node, Prefix, PrefixCodec, Result, CANCELLATION_PROBING,
 };
 
+/// The number of entries [`Writer::copy_from`] reads before writing them, the source can't be
+/// iterated while the destination is being written.
+const COPY_BATCH_SIZE: usize = 1024;
+
 /// The options available when configuring the hannoy database.
 pub struct HannoyBuilder<'a, D: Distance, R: Rng + SeedableRng, P> {
     writer: &'a Writer<D>,
@@ -32,6 +43,8 @@ pub struct HannoyBuilder<'a, D: Distance, R: Rng + SeedableRng, P> {
 
 /// The options available when building the hannoy database.
 pub(crate) struct BuildOption<'a, P> {
+    pub(crate) m: usize,
+    pub(crate) m0: usize,
     pub(crate) ef_construction: usize,
     pub(crate) alpha: f32,
     pub(crate) available_memory: Option<usize>,
@@ -47,6 +60,8 @@ pub(crate) struct BuildOption<'a, P> {
 impl Default for BuildOption<'_, NoProgress> {
     fn default() -> Self {
         Self {
+            m: 16,
+            m0: 32,
             ef_construction: 100,
             alpha: 1.0,
             available_memory: None,
@@ -57,6 +72,26 @@ impl Default for BuildOption<'_, NoProgress> {
     }
 }
 
+impl<P> BuildOption<'_, P> {
+    /// The parameters persisted in the metadata of the built index.
+    pub(crate) fn build_params(&self) -> BuildParams {
+        BuildParams {
+            m: self.m,
+            m0: self.m0,
+            ef_construction: self.ef_construction,
+            alpha: self.alpha,
+        }
+    }
+
+    /// Returns an error if the graph can't be built with these numbers of links per node.
+    pub(crate) fn check_build_params(&self) -> Result<()> {
+        if self.m < 2 || self.m0 < 1 {
+            return Err(Error::InvalidBuildParams(self.build_params()));
+        }
+        Ok(())
+    }
+}
+
 impl<'a, D: Distance, R: Rng + SeedableRng, P> HannoyBuilder<'a, D, R, P> {
     // NOTE: unused in hannoy
     // pub fn available_memory(&mut self, memory: usize) -> &mut Self {
@@ -92,7 +127,7 @@ impl<'a, D: Distance, R: Rng + SeedableRng, P> HannoyBuilder<'a, D, R, P> {
     /// });
     ///
     /// let mut rng = StdRng::seed_from_u64(92);
-    /// writer.builder(&mut rng).cancel(|| stops_after.load(Ordering::Relaxed)).build::<16,32>(&mut wtxn);
+    /// writer.builder(&mut rng).cancel(|| stops_after.load(Ordering::Relaxed)).build(&mut wtxn);
     /// ```
     pub fn cancel(&mut self, cancel: impl Fn() -> bool + 'a + Sync + Send) -> &mut Self {
         self.inner.cancel = Box::new(cancel);
@@ -112,7 +147,7 @@ impl<'a, D: Distance, R: Rng + SeedableRng, P> HannoyBuilder<'a, D, R, P> {
     /// use steppe::NoProgress;
     ///
     /// let mut rng = StdRng::seed_from_u64(4729);
-    /// writer.builder(&mut rng).progress(NoProgress).build::<16,32>(&mut wtxn);
+    /// writer.builder(&mut rng).progress(NoProgress).build(&mut wtxn);
     /// ```
     pub fn progress<NP: steppe::Progress>(self, progress: NP) -> HannoyBuilder<'a, D, R, NP> {
         let HannoyBuilder {
@@ -120,6 +155,8 @@ impl<'a, D: Distance, R: Rng + SeedableRng, P> HannoyBuilder<'a, D, R, P> {
             rng,
             inner:
                 BuildOption {
+                    m,
+                    m0,
                     ef_construction,
                     available_memory,
                     cancel,
@@ -132,6 +169,8 @@ impl<'a, D: Distance, R: Rng + SeedableRng, P> HannoyBuilder<'a, D, R, P> {
             writer,
             rng,
             inner: BuildOption {
+                m,
+                m0,
                 ef_construction,
                 available_memory,
                 cancel,
@@ -142,8 +181,51 @@ impl<'a, D: Distance, R: Rng + SeedableRng, P> HannoyBuilder<'a, D, R, P> {
         }
     }
 
+    /// The maximum number of links per node in the layers above the first one. The default value
+    /// used in hannoy is 16.
+    ///
+    /// Some common choices include 8, 12, 16 and 32. Increasing `m` produces a denser graph, with
+    /// a better recall, at the cost of longer build times and a bigger index. It must be at least
+    /// 2, building returns [`Error::InvalidBuildParams`] otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use hannoy::{Writer, distances::Euclidean};
+    /// # let (writer, wtxn): (Writer<Euclidean>, heed::RwTxn) = todo!();
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let mut rng = StdRng::seed_from_u64(4729);
+    /// writer.builder(&mut rng).m(24).m0(48).build(&mut wtxn);
+    /// ```
+    pub fn m(&mut self, m: usize) -> &mut Self {
+        self.inner.m = m;
+        self
+    }
+
+    /// The maximum number of links per node in the first layer, which contains all the items.
+    /// A general rule of thumb is to take `m0 = 2 * m`. The default value used in hannoy is 32.
+    /// It must be at least 1, building returns [`Error::InvalidBuildParams`] otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use hannoy::{Writer, distances::Euclidean};
+    /// # let (writer, wtxn): (Writer<Euclidean>, heed::RwTxn) = todo!();
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let mut rng = StdRng::seed_from_u64(4729);
+    /// writer.builder(&mut rng).m(24).m0(48).build(&mut wtxn);
+    /// ```
+    pub fn m0(&mut self, m0: usize) -> &mut Self {
+        self.inner.m0 = m0;
+        self
+    }
+
     /// Controls the search range when inserting a new item into the graph. This value must be
-    /// greater than or equal to the `M` used in [`Self::build<M,M0>`]
+    /// greater than or equal to the `M` set with [`Self::m`].
     ///
     /// Typical values range from 50 to 500, with larger `ef_construction` producing higher
     /// quality hnsw graphs at the expense of longer builds. The default value used in hannoy is
@@ -158,7 +240,7 @@ impl<'a, D: Distance, R: Rng + SeedableRng, P> HannoyBuilder<'a, D, R, P> {
     /// use rand::SeedableRng;
     ///
     /// let mut rng = StdRng::seed_from_u64(4729);
-    /// writer.builder(&mut rng).ef_construction(100).build::<16,32>(&mut wtxn);
+    /// writer.builder(&mut rng).ef_construction(100).build(&mut wtxn);
     /// ```
     pub fn ef_construction(&mut self, ef_construction: usize) -> &mut Self {
         self.inner.ef_construction = ef_construction;
@@ -181,22 +263,21 @@ impl<'a, D: Distance, R: Rng + SeedableRng, P> HannoyBuilder<'a, D, R, P> {
     /// use rand::SeedableRng;
     ///
     /// let mut rng = StdRng::seed_from_u64(4729);
-    /// writer.builder(&mut rng).alpha(1.1).build::<16,32>(&mut wtxn);
+    /// writer.builder(&mut rng).alpha(1.1).build(&mut wtxn);
     /// ```
     pub fn alpha(&mut self, alpha: f32) -> &mut Self {
         self.inner.alpha = alpha;
         self
     }
 
-    /// Generates an HNSW graph with max `M` links per node in layers > 0 and max `M0` links in layer 0.
-    ///
-    /// A general rule of thumb is to take `M0`= 2*`M`, with `M` >=3.  Some common choices for
-    /// `M` include : 8, 12, 16, 32. Note that increasing `M` produces a denser graph at the cost
-    /// of longer build times.
+    /// Generates an HNSW graph with max `m` links per node in layers > 0 and max `m0` links in
+    /// layer 0, see [`Self::m`] and [`Self::m0`].
     ///
     /// This function is using rayon to spawn threads. It can be configured by using the
     /// [`rayon::ThreadPoolBuilder`].
     ///
+    /// Returns the [`BuildStats`] of this build, e.g. the time spent in each phase.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -210,13 +291,19 @@ impl<'a, D: Distance, R: Rng + SeedableRng, P> HannoyBuilder<'a, D, R, P> {
     /// rayon::ThreadPoolBuilder::new().num_threads(4).build_global().unwrap();
     ///
     /// let mut rng = StdRng::seed_from_u64(4729);
-    /// writer.builder(&mut rng).build::<16,32>(&mut wtxn);
+    /// let stats = writer.builder(&mut rng).build(&mut wtxn)?;
+    /// println!("inserted {} items in {:?}", stats.n_items_inserted(), stats.duration());
+    /// # Ok::<(), hannoy::Error>(())
     /// ```
-    pub fn build<const M: usize, const M0: usize>(&mut self, wtxn: &mut RwTxn) -> Result<()>
+    pub fn build(&mut self, wtxn: &mut RwTxn) -> Result<BuildStats>
     where
         P: steppe::Progress,
     {
-        self.writer.build::<R, P, M, M0>(wtxn, self.rng, &self.inner)
+        self.inner.check_build_params()?;
+        let mut stats = BuildStats::new();
+        self.writer.build(wtxn, self.rng, &self.inner, &mut stats)?;
+        stats.finish();
+        Ok(stats)
     }
 
     /// Rebuilds an HNSW graph from scratch.
@@ -225,7 +312,7 @@ impl<'a, D: Distance, R: Rng + SeedableRng, P> HannoyBuilder<'a, D, R, P> {
     /// from previous builds and reconstruct the hnsw with the vectors found in the db.
     ///
     /// Standard builds work by first adding or deleting some nodes, here we're marking all
-    /// vectors found on disk as updated to force a rebuild. When in doubt prefer [`Self::build<M,M0>`] over
+    /// vectors found on disk as updated to force a rebuild. When in doubt prefer [`Self::build`] over
     /// this method.
     ///
     /// # Example
@@ -241,21 +328,57 @@ impl<'a, D: Distance, R: Rng + SeedableRng, P> HannoyBuilder<'a, D, R, P> {
     /// rayon::ThreadPoolBuilder::new().num_threads(4).build_global().unwrap();
     ///
     /// let mut rng = StdRng::seed_from_u64(4729);
-    /// writer.builder(&mut rng).force_rebuild::<16,32>(&mut wtxn);
+    /// writer.builder(&mut rng).force_rebuild(&mut wtxn);
     /// ```
-    pub fn force_rebuild<const M: usize, const M0: usize>(&mut self, wtxn: &mut RwTxn) -> Result<()>
+    pub fn force_rebuild(&mut self, wtxn: &mut RwTxn) -> Result<BuildStats>
     where
         P: steppe::Progress,
     {
+        self.inner.check_build_params()?;
+
         // Use this option to mark all nodes as updated
         self.inner.relink_all_items = true;
 
-        self.writer.force_rebuild::<R, P, M, M0>(wtxn, self.rng, &self.inner)?;
+        let mut stats = BuildStats::new();
+        let result = self.writer.force_rebuild(wtxn, self.rng, &self.inner, &mut stats);
 
         // As this builder can be reused, we need to reset this parameter
         self.inner.relink_all_items = false;
 
-        Ok(())
+        result?;
+        stats.finish();
+        Ok(stats)
+    }
+
+    /// Rewires the weakly connected nodes of an already built HNSW graph.
+    ///
+    /// After many incremental builds with deletions some nodes end up with very few incoming links,
+    /// or even unreachable from the entry points, which degrades the recall. Unlike
+    /// [`Self::force_rebuild`] this method keeps the graph and only searches the neighbours of
+    /// those nodes again. It returns the repaired items.
+    ///
+    /// The graph must have been built with the same `m` and `m0`, and there must be no pending
+    /// updates, call [`Self::build`] first otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use hannoy::{Writer, distances::Euclidean};
+    /// # let (writer, wtxn): (Writer<Euclidean>, heed::RwTxn) = todo!();
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let mut rng = StdRng::seed_from_u64(4729);
+    /// let repaired = writer.builder(&mut rng).repair(&mut wtxn)?;
+    /// println!("rewired {} items", repaired.len());
+    /// # Ok::<(), hannoy::Error>(())
+    /// ```
+    pub fn repair(&self, wtxn: &mut RwTxn) -> Result<RoaringBitmap>
+    where
+        P: steppe::Progress,
+    {
+        self.inner.check_build_params()?;
+        self.writer.repair(wtxn, &self.inner)
     }
 
     /// Converts an arroy db into a hannoy one.
@@ -278,12 +401,14 @@ pub struct Writer<D: Distance> {
     dimensions: usize,
     /// The folder in which tempfile will write its temporary files.
     tmpdir: Option<PathBuf>,
+    /// Whether the full-precision vectors are kept next to the quantized items.
+    full_precision: bool,
 }
 
 impl<D: Distance> Writer<D> {
     /// Creates a new writer from a database, index and dimensions.
     pub fn new(database: Database<D>, index: u16, dimensions: usize) -> Writer<D> {
-        Writer { database, index, dimensions, tmpdir: None }
+        Writer { database, index, dimensions, tmpdir: None, full_precision: false }
     }
 
     /// After opening an arroy database this function will prepare it for conversion,
@@ -294,8 +419,8 @@ impl<D: Distance> Writer<D> {
         wtxn: &mut RwTxn,
         options: &BuildOption<P>,
     ) -> Result<()> {
+        use crate::node::Item;
         use crate::node_id::{NodeId, NodeMode};
-        use crate::unaligned_vector::UnalignedVectorCodec;
 
         debug!("Preparing dumpless upgrade from arroy to hannoy");
         options.progress.update(HannoyBuild::ConvertingArroyToHannoy);
@@ -357,45 +482,43 @@ impl<D: Distance> Writer<D> {
     /// for the new [`Distance`] format to be able to modify items safely.
     pub fn prepare_changing_distance<ND: Distance>(self, wtxn: &mut RwTxn) -> Result<Writer<ND>> {
         if TypeId::of::<ND>() != TypeId::of::<D>() {
-            // If we are moving from a distance to the same but binary quantized
-            // distance we do not need to clear links, otherwise we do.
-            if ND::name()
-                .strip_prefix("binary quantized ")
-                .is_none_or(|raw_name| raw_name != D::name())
+            // If we are moving from a distance to the same but quantized or
+            // half-precision distance we do not need to clear links, otherwise we do.
+            if ["binary quantized ", "scalar quantized ", "f16 ", "bf16 "]
+                .iter()
+                .filter_map(|prefix| ND::name().strip_prefix(prefix))
+                .all(|raw_name| raw_name != D::name())
             {
                 clear_links(wtxn, self.database, self.index)?;
                 self.database.delete(wtxn, &Key::metadata(self.index))?;
             }
 
-            let mut cursor = self
-                .database
-                .remap_key_type::<PrefixCodec>()
-                .prefix_iter_mut(wtxn, &Prefix::item(self.index))?
-                .remap_key_type::<KeyCodec>();
+            // Keep the vectors before they are quantized to be able to rescore them later on
+            if self.keeps_full_precision::<ND>() {
+                self.store_full_precision_vectors(wtxn)?;
+            }
 
-            let mut updated_items = RoaringBitmap::new();
-            while let Some((item_id, node)) = cursor.next().transpose()? {
-                match node {
-                    Node::Item(Item { header: _, vector }) => {
-                        updated_items.insert(item_id.node.item);
-                        let vector = vector.to_vec();
-                        let vector = UnalignedVector::from_vec(vector);
-                        let new_leaf = Node::Item(Item { header: ND::new_header(&vector), vector });
-                        unsafe {
-                            // safety: We do not keep a reference to the current value, we own it.
-                            cursor.put_current_with_options::<NodeCodec<ND>>(
-                                PutFlags::empty(),
-                                &item_id,
-                                &new_leaf,
-                            )?
-                        };
-                    }
-                    Node::Links(_) => unreachable!("Node must not be a link"),
+            // Quantized distances need a calibration computed over the original vectors
+            let new_calibration = if ND::needs_calibration() {
+                let vectors = self.iter(wtxn)?.map(|res| res.map(|(_, vector)| vector));
+                Calibration::try_from_vectors(vectors)?
+            } else {
+                None
+            };
+
+            let updated_items = self.reencode_items::<ND>(wtxn, new_calibration.as_ref())?;
+
+            match &new_calibration {
+                Some(new_calibration) => self.database.remap_data_type::<CalibrationCodec>().put(
+                    wtxn,
+                    &Key::calibration(self.index),
+                    new_calibration,
+                )?,
+                None => {
+                    self.database.delete(wtxn, &Key::calibration(self.index))?;
                 }
             }
 
-            drop(cursor);
-
             for item in updated_items {
                 self.database.remap_types::<KeyCodec, UpdateStatusCodec>().put(
                     wtxn,
@@ -405,8 +528,141 @@ impl<D: Distance> Writer<D> {
             }
         }
 
-        let Writer { database, index, dimensions, tmpdir } = self;
-        Ok(Writer { database: database.remap_data_type(), index, dimensions, tmpdir })
+        let Writer { database, index, dimensions, tmpdir, full_precision } = self;
+        Ok(Writer {
+            database: database.remap_data_type(),
+            index,
+            dimensions,
+            tmpdir,
+            full_precision,
+        })
+    }
+
+    /// Stores the current vector of every item as its full-precision vector, unless it already
+    /// has one.
+    fn store_full_precision_vectors(&self, wtxn: &mut RwTxn) -> Result<()> {
+        let item_ids = self.item_ids(wtxn)?;
+        let full_precision = self.database.remap_data_type::<FullPrecisionCodec>();
+        for item in item_ids {
+            let key = Key::full_precision(self.index, item);
+            if full_precision.remap_data_type::<DecodeIgnore>().get(wtxn, &key)?.is_some() {
+                continue;
+            }
+            if let Some(vector) = self.item_vector(wtxn, item)? {
+                full_precision.put(wtxn, &key, &UnalignedVector::from_vec(vector))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the ids of all the items of the index.
+    fn item_ids(&self, rtxn: &RoTxn) -> Result<RoaringBitmap> {
+        Ok(self
+            .database
+            .remap_types::<PrefixCodec, DecodeIgnore>()
+            .prefix_iter(rtxn, &Prefix::item(self.index))?
+            .remap_key_type::<KeyCodec>()
+            .map(|result| result.map(|(key, _)| key.node.item))
+            .collect::<Result<_, _>>()?)
+    }
+
+    /// Returns `true` if the full-precision vectors of the items are stored next to them for
+    /// the distance `ND`. The scalar quantized distances always need them to be calibrated.
+    fn keeps_full_precision<ND: Distance>(&self) -> bool {
+        (self.full_precision || ND::needs_calibration()) && ND::full_precision_distance().is_some()
+    }
+
+    /// Computes the scalar quantization [`Calibration`] over the full-precision vectors when
+    /// the index has none yet, when `recompute` is set or when an `updated` item is out of its
+    /// range, and re-encodes all the items with it. Otherwise only encodes the `updated` items
+    /// with the current calibration.
+    ///
+    /// Returns the re-encoded items, distances that don't need a calibration, see
+    /// [`Distance::needs_calibration`], do nothing.
+    fn calibrate(
+        &self,
+        wtxn: &mut RwTxn,
+        updated: &RoaringBitmap,
+        recompute: bool,
+    ) -> Result<RoaringBitmap> {
+        if !D::needs_calibration() {
+            return Ok(RoaringBitmap::new());
+        }
+
+        let mut calibration = self.calibration(wtxn)?.filter(|_| !recompute);
+        if let Some(current) = &calibration {
+            for item in updated {
+                let vector = self
+                    .item_vector(wtxn, item)?
+                    .ok_or_else(|| Error::missing_key(Key::item(self.index, item)))?;
+                if !current.covers(&vector) {
+                    debug!("item {item} is out of the calibrated range, recalibrating the index");
+                    calibration = None;
+                    break;
+                }
+            }
+        }
+
+        let (calibration, items) = match calibration {
+            Some(calibration) => (calibration, updated.clone()),
+            None => {
+                let vectors = self.iter(wtxn)?.map(|res| res.map(|(_, vector)| vector));
+                let Some(calibration) = Calibration::try_from_vectors(vectors)? else {
+                    return Ok(RoaringBitmap::new());
+                };
+                self.database.remap_data_type::<CalibrationCodec>().put(
+                    wtxn,
+                    &Key::calibration(self.index),
+                    &calibration,
+                )?;
+                (calibration, self.item_ids(wtxn)?)
+            }
+        };
+
+        for item in &items {
+            let vector = self
+                .item_vector(wtxn, item)?
+                .ok_or_else(|| Error::missing_key(Key::item(self.index, item)))?;
+            let db_item = D::encode(&vector, Some(&calibration));
+            self.database.put(wtxn, &Key::item(self.index, item), &Node::Item(db_item))?;
+        }
+
+        Ok(items)
+    }
+
+    /// Returns the scalar quantization calibration of the index, if the distance needs one.
+    fn calibration(&self, rtxn: &RoTxn) -> Result<Option<Calibration>> {
+        if !D::needs_calibration() {
+            return Ok(None);
+        }
+
+        Ok(self
+            .database
+            .remap_data_type::<CalibrationCodec>()
+            .get(rtxn, &Key::calibration(self.index))?)
+    }
+
+    /// Decodes all the items, or reads their full-precision vectors, and encodes them back in
+    /// the new distance format. Returns the ids of the re-encoded items.
+    fn reencode_items<ND: Distance>(
+        &self,
+        wtxn: &mut RwTxn,
+        new_calibration: Option<&Calibration>,
+    ) -> Result<RoaringBitmap> {
+        let calibration = self.calibration(wtxn)?;
+        let database = self.database.remap_data_type::<NodeCodec<ND>>();
+        let item_ids = self.item_ids(wtxn)?;
+        for item in &item_ids {
+            let vector = self
+                .vector(wtxn, item, calibration.as_ref())?
+                .ok_or_else(|| Error::missing_key(Key::item(self.index, item)))?;
+            check_values::<ND>(&vector)?;
+            let db_item = ND::encode(&vector, new_calibration);
+            database.put(wtxn, &Key::item(self.index, item), &Node::Item(db_item))?;
+        }
+
+        Ok(item_ids)
     }
 
     /// Sets the path to the temporary directory where files are written.
@@ -414,6 +670,17 @@ impl<D: Distance> Writer<D> {
         self.tmpdir = Some(path.into());
     }
 
+    /// Keeps the full-precision vectors of the items next to their quantized version, so that
+    /// the search results can be rescored with [`QueryBuilder::rescore`](crate::QueryBuilder::rescore).
+    ///
+    /// It only applies to the quantized distances, see [`Distance::full_precision_distance`],
+    /// and to the items added, or converted with [`Self::prepare_changing_distance`], while
+    /// it is enabled. The scalar quantized distances always keep them to compute their
+    /// calibration.
+    pub fn set_full_precision(&mut self, enabled: bool) {
+        self.full_precision = enabled;
+    }
+
     /// Returns `true` if the index is empty.
     pub fn is_empty(&self, rtxn: &RoTxn) -> Result<bool> {
         self.iter(rtxn).map(|mut iter| iter.next().is_none())
@@ -437,8 +704,29 @@ impl<D: Distance> Writer<D> {
 
     /// Returns an `Option`al vector previous stored in this database.
     pub fn item_vector(&self, rtxn: &RoTxn, item: ItemId) -> Result<Option<Vec<f32>>> {
+        let calibration = self.calibration(rtxn)?;
+        self.vector(rtxn, item, calibration.as_ref())
+    }
+
+    /// Returns the full-precision vector of a scalar quantized item, its code is only encoded
+    /// with the calibration at build time, or decodes the item with `calibration`.
+    fn vector(
+        &self,
+        rtxn: &RoTxn,
+        item: ItemId,
+        calibration: Option<&Calibration>,
+    ) -> Result<Option<Vec<f32>>> {
+        if D::needs_calibration() {
+            let full_precision = self.database.remap_data_type::<FullPrecisionCodec>();
+            if let Some(vector) =
+                full_precision.get(rtxn, &Key::full_precision(self.index, item))?
+            {
+                return Ok(Some(vector.to_vec()));
+            }
+        }
+
         Ok(get_item(self.database, self.index, rtxn, item)?.map(|item| {
-            let mut vec = item.vector.to_vec();
+            let mut vec = D::decode(&item, calibration);
             vec.truncate(self.dimensions);
             vec
         }))
@@ -455,10 +743,24 @@ impl<D: Distance> Writer<D> {
 
     /// Returns an iterator over the items vector.
     pub fn iter<'t>(&self, rtxn: &'t RoTxn) -> Result<ItemIter<'t, D>> {
-        Ok(ItemIter::new(self.database, self.index, self.dimensions, rtxn)?)
+        let calibration = self.calibration(rtxn)?;
+        if D::needs_calibration() {
+            Ok(ItemIter::with_full_precision(
+                self.database,
+                self.index,
+                self.dimensions,
+                calibration,
+                rtxn,
+            )?)
+        } else {
+            Ok(ItemIter::new(self.database, self.index, self.dimensions, calibration, rtxn)?)
+        }
     }
 
     /// Add an item associated to a vector in the database.
+    ///
+    /// Returns [`Error::ValueOutOfRange`] if a value is too big for the codec of the distance,
+    /// e.g. above 65504 in magnitude for the `f16` distances.
     pub fn add_item(&self, wtxn: &mut RwTxn, item: ItemId, vector: &[f32]) -> Result<()> {
         if vector.len() != self.dimensions {
             return Err(Error::InvalidVecDimension {
@@ -466,10 +768,21 @@ impl<D: Distance> Writer<D> {
                 received: vector.len(),
             });
         }
+        check_values::<D>(vector)?;
 
-        let vector = UnalignedVector::from_slice(vector);
-        let db_item = Item { header: D::new_header(&vector), vector };
+        // The scalar quantized items are encoded with the calibration at build time
+        let db_item = D::encode(vector, None);
         self.database.put(wtxn, &Key::item(self.index, item), &Node::Item(db_item))?;
+        // The full-precision vector of a previous version of the item must not be kept
+        if self.keeps_full_precision::<D>() {
+            self.database.remap_data_type::<FullPrecisionCodec>().put(
+                wtxn,
+                &Key::full_precision(self.index, item),
+                &UnalignedVector::from_slice(vector),
+            )?;
+        } else {
+            self.database.delete(wtxn, &Key::full_precision(self.index, item))?;
+        }
         self.database.remap_data_type::<UpdateStatusCodec>().put(
             wtxn,
             &Key::updated(self.index, item),
@@ -479,8 +792,151 @@ impl<D: Distance> Writer<D> {
         Ok(())
     }
 
-    /// Deletes an item stored in this database and returns `true` if it existed.
+    /// Adds the vectors of an `.fvecs` file to the index and returns the number of items added.
+    ///
+    /// The items are numbered from 0 in the order of the file, unless an id-mapping file gives
+    /// their ids, one per line, see the [`vecs`](crate::vecs) module. The vectors are streamed
+    /// through [`Self::add_item`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{distances::Euclidean, Writer};
+    /// # let (writer, mut wtxn): (Writer<Euclidean>, heed::RwTxn) = todo!();
+    /// use std::fs::File;
+    ///
+    /// let vectors = File::open("sift_base.fvecs")?;
+    /// let mut ids = File::open("sift_base.ids")?;
+    /// let n_items = writer.import_fvecs(&mut wtxn, vectors, Some(&mut ids))?;
+    /// # Ok::<(), hannoy::Error>(())
+    /// ```
+    pub fn import_fvecs(
+        &self,
+        wtxn: &mut RwTxn,
+        vectors: impl Read,
+        ids: Option<&mut dyn Read>,
+    ) -> Result<u64> {
+        let vectors =
+            VecsReader::new(vectors, VecsFormat::Fvecs).expect_dimensions(self.dimensions);
+        self.import(wtxn, vectors, ids)
+    }
+
+    /// Same as [`Self::import_fvecs`] for a `.bvecs` file, with `u8` components.
+    pub fn import_bvecs(
+        &self,
+        wtxn: &mut RwTxn,
+        vectors: impl Read,
+        ids: Option<&mut dyn Read>,
+    ) -> Result<u64> {
+        let vectors =
+            VecsReader::new(vectors, VecsFormat::Bvecs).expect_dimensions(self.dimensions);
+        self.import(wtxn, vectors, ids)
+    }
+
+    /// Same as [`Self::import_fvecs`] for an `.ivecs` file, with `i32` components.
+    pub fn import_ivecs(
+        &self,
+        wtxn: &mut RwTxn,
+        vectors: impl Read,
+        ids: Option<&mut dyn Read>,
+    ) -> Result<u64> {
+        let vectors =
+            VecsReader::new(vectors, VecsFormat::Ivecs).expect_dimensions(self.dimensions);
+        self.import(wtxn, vectors, ids)
+    }
+
+    /// Same as [`Self::import_fvecs`] for a `.npy` file storing a two-dimensional array of
+    /// `f32`s or `f64`s, one vector per row.
+    pub fn import_npy(
+        &self,
+        wtxn: &mut RwTxn,
+        vectors: impl Read,
+        ids: Option<&mut dyn Read>,
+    ) -> Result<u64> {
+        let vectors = NpyReader::new(vectors)?;
+        if vectors.dimensions() != self.dimensions {
+            return Err(Error::InvalidVecDimension {
+                expected: self.dimensions,
+                received: vectors.dimensions(),
+            });
+        }
+        self.import(wtxn, vectors, ids)
+    }
+
+    fn import(
+        &self,
+        wtxn: &mut RwTxn,
+        vectors: impl Iterator<Item = Result<Vec<f32>>>,
+        ids: Option<&mut dyn Read>,
+    ) -> Result<u64> {
+        let mut ids = IdsReader::new(ids);
+        let mut n_items = 0;
+        for vector in vectors {
+            let vector = vector?;
+            self.add_item(wtxn, ids.next_id()?, &vector)?;
+            n_items += 1;
+        }
+        ids.finish()?;
+
+        Ok(n_items)
+    }
+
+    /// Adds an item identified by an external key, e.g. the bytes of a UUID or of a 64-bit id,
+    /// and returns the [`ItemId`] it's stored under.
+    ///
+    /// A new key gets the id of a deleted key if there is one, or the next id never allocated to
+    /// a key. Adding a key again replaces the vector of its item, like [`Self::add_item`]. The
+    /// ids of the items added without a key are never allocated to a key, but mixing both in an
+    /// index makes the allocation slower.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{distances::Euclidean, Writer};
+    /// # let (writer, mut wtxn): (Writer<Euclidean>, heed::RwTxn) = todo!();
+    /// let document_id: u64 = 4_242_424_242;
+    /// let item = writer.add_item_by_key(&mut wtxn, &document_id.to_be_bytes(), &[0.5, -1.0, 0.1])?;
+    /// let uuid = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+    /// writer.add_item_by_key(&mut wtxn, uuid.as_bytes(), &[-0.3, 0.8, 0.2])?;
+    /// # Ok::<(), hannoy::Error>(())
+    /// ```
+    pub fn add_item_by_key(&self, wtxn: &mut RwTxn, key: &[u8], vector: &[f32]) -> Result<ItemId> {
+        if let Some(item) = self.item_id(wtxn, key)? {
+            self.add_item(wtxn, item, vector)?;
+            return Ok(item);
+        }
+
+        let item = external_keys::allocate(self.database, self.index, wtxn)?;
+        self.add_item(wtxn, item, vector)?;
+        external_keys::put(self.database, self.index, wtxn, key, item)?;
+        Ok(item)
+    }
+
+    /// Deletes the item identified by an external key and returns `true` if it existed, its id
+    /// can be allocated to another key right away.
+    pub fn del_item_by_key(&self, wtxn: &mut RwTxn, key: &[u8]) -> Result<bool> {
+        match self.item_id(wtxn, key)? {
+            Some(item) => self.del_item(wtxn, item),
+            None => Ok(false),
+        }
+    }
+
+    /// Returns the id of the item identified by an external key.
+    pub fn item_id(&self, rtxn: &RoTxn, key: &[u8]) -> Result<Option<ItemId>> {
+        external_keys::get_id(self.database, self.index, rtxn, key)
+    }
+
+    /// Returns the external key of an item, if it was added with one.
+    pub fn external_key(&self, rtxn: &RoTxn, item: ItemId) -> Result<Option<Vec<u8>>> {
+        external_keys::get_key(self.database, self.index, rtxn, item)
+    }
+
+    /// Deletes an item stored in this database, along with its attributes and external key, and
+    /// returns `true` if it existed.
     pub fn del_item(&self, wtxn: &mut RwTxn, item: ItemId) -> Result<bool> {
+        self.database.delete(wtxn, &Key::full_precision(self.index, item))?;
+        attributes::put(self.database, self.index, wtxn, item, None)?;
+        external_keys::delete(self.database, self.index, wtxn, item)?;
         if self.database.delete(wtxn, &Key::item(self.index, item))? {
             self.database.remap_data_type::<UpdateStatusCodec>().put(
                 wtxn,
@@ -494,6 +950,30 @@ impl<D: Distance> Writer<D> {
         }
     }
 
+    /// Returns the attributes of an item, see [`Self::set_attributes`].
+    pub fn attributes(&self, rtxn: &RoTxn, item: ItemId) -> Result<Option<Attributes>> {
+        attributes::get(self.database, self.index, rtxn, item)
+    }
+
+    /// Replaces the attributes of an item, the tags and numbers the
+    /// [filters](crate::QueryBuilder::filter) are evaluated on.
+    ///
+    /// The attributes are indexed right away and don't require to build the index again. They
+    /// can be set before the item is added, but only the items of the index are ever returned.
+    pub fn set_attributes(
+        &self,
+        wtxn: &mut RwTxn,
+        item: ItemId,
+        attributes: &Attributes,
+    ) -> Result<()> {
+        attributes::put(self.database, self.index, wtxn, item, Some(attributes)).map(drop)
+    }
+
+    /// Deletes the attributes of an item and returns `true` if it had some.
+    pub fn del_attributes(&self, wtxn: &mut RwTxn, item: ItemId) -> Result<bool> {
+        attributes::put(self.database, self.index, wtxn, item, None)
+    }
+
     /// Removes everything in the database, user items and internal graph links.
     pub fn clear(&self, wtxn: &mut RwTxn) -> Result<()> {
         let mut cursor = self
@@ -510,6 +990,209 @@ impl<D: Distance> Writer<D> {
         Ok(())
     }
 
+    /// Replaces the content of the index with a dump made by [`Reader::dump`](crate::Reader::dump).
+    ///
+    /// The dump must have been made with the same distance and dimensions, on any machine. The
+    /// index is usable as soon as the transaction is committed, without building it.
+    ///
+    /// The entries are written while the dump is read and its checksum is only verified at the
+    /// end, the transaction must be aborted if an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{distances::Euclidean, Writer};
+    /// # let (writer, mut wtxn): (Writer<Euclidean>, heed::RwTxn) = todo!();
+    /// use std::fs::File;
+    ///
+    /// writer.restore(&mut wtxn, File::open("index.dump")?)?;
+    /// wtxn.commit()?;
+    /// # Ok::<(), hannoy::Error>(())
+    /// ```
+    pub fn restore(&self, wtxn: &mut RwTxn, input: impl Read) -> Result<()> {
+        self.clear(wtxn)?;
+        dump::restore(self.database, self.index, self.dimensions, wtxn, input)
+    }
+
+    /// Replaces the content of the index with a copy of the items and graph of `src_index`,
+    /// stored in the same database.
+    ///
+    /// The copy is in the same state as the source, built or not, and can then be updated
+    /// independently. Copying an index onto itself does nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{distances::Euclidean, Database, Writer};
+    /// # let (database, mut wtxn): (Database<Euclidean>, heed::RwTxn) = todo!();
+    /// // prepare the next version of index 0 in index 1
+    /// let writer = Writer::new(database, 1, 768);
+    /// writer.copy_from(&mut wtxn, 0)?;
+    /// # Ok::<(), hannoy::Error>(())
+    /// ```
+    pub fn copy_from(&self, wtxn: &mut RwTxn, src_index: u16) -> Result<()> {
+        if src_index == self.index {
+            return Ok(());
+        }
+        self.check_compatibility(wtxn, src_index)?;
+        self.clear(wtxn)?;
+
+        let database = self.database.remap_data_type::<Bytes>();
+        let mut batch = Vec::with_capacity(COPY_BATCH_SIZE);
+        let mut next = Some(Key::metadata(src_index));
+        while let Some(start) = next.take() {
+            for result in database.range(wtxn, &(start..))? {
+                let (key, value) = result?;
+                if key.index != src_index {
+                    break;
+                } else if batch.len() == COPY_BATCH_SIZE {
+                    next = Some(key);
+                    break;
+                }
+                batch.push((key.node, value.to_vec()));
+            }
+
+            for (node, value) in batch.drain(..) {
+                database.put(wtxn, &Key::new(self.index, node), &value)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Inserts the items of `other_index`, stored in the same database, into this index and
+    /// returns their ids in this index.
+    ///
+    /// The items are added as if they were inserted with [`Self::add_item`], the next call to
+    /// [`HannoyBuilder::build`] links them to the graph of this index incrementally. The items
+    /// of this index with the same ids are replaced and `other_index` is left untouched.
+    ///
+    /// The items having an external key are added with [`Self::add_item_by_key`] instead, they
+    /// replace the item of this index with the same key or get a new id.
+    ///
+    /// The full-precision vectors of `other_index` are used when they are available. The
+    /// attributes of the items are copied.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use hannoy::{distances::Euclidean, Database, Writer};
+    /// # let (database, mut wtxn): (Database<Euclidean>, heed::RwTxn) = todo!();
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// // consolidate the shard 1 into the shard 0
+    /// let writer = Writer::new(database, 0, 768);
+    /// writer.merge_from(&mut wtxn, 1)?;
+    /// writer.builder(&mut StdRng::seed_from_u64(42)).build(&mut wtxn)?;
+    /// Writer::new(database, 1, 768).clear(&mut wtxn)?;
+    /// # Ok::<(), hannoy::Error>(())
+    /// ```
+    pub fn merge_from(&self, wtxn: &mut RwTxn, other_index: u16) -> Result<RoaringBitmap> {
+        if other_index == self.index {
+            return Ok(RoaringBitmap::new());
+        }
+        self.check_compatibility(wtxn, other_index)?;
+
+        let other = Writer::<D>::new(self.database, other_index, self.dimensions);
+        let items = self
+            .database
+            .remap_types::<PrefixCodec, DecodeIgnore>()
+            .prefix_iter(wtxn, &Prefix::item(other_index))?
+            .remap_key_type::<KeyCodec>()
+            .map(|result| result.map(|(key, _)| key.node.item))
+            .collect::<heed::Result<RoaringBitmap>>()?;
+
+        let full_precision = self.database.remap_data_type::<FullPrecisionCodec>();
+        let vector = |rtxn: &RoTxn, item| -> Result<Vec<f32>> {
+            match full_precision.get(rtxn, &Key::full_precision(other_index, item))? {
+                Some(vector) => Ok(vector.to_vec()),
+                None => other
+                    .item_vector(rtxn, item)?
+                    .ok_or_else(|| Error::missing_key(Key::item(other_index, item))),
+            }
+        };
+
+        // the items without a key keep their id, they are merged first so that the ids
+        // allocated to the keyed items can't be taken by them
+        let mut merged = RoaringBitmap::new();
+        let mut keyed = Vec::new();
+        for item in &items {
+            if let Some(key) = other.external_key(wtxn, item)? {
+                keyed.push((item, key));
+                continue;
+            }
+            self.add_item(wtxn, item, &vector(wtxn, item)?)?;
+            // the replaced item doesn't have the key it may have had anymore
+            external_keys::delete(self.database, self.index, wtxn, item)?;
+            self.merge_attributes(wtxn, other_index, item, item)?;
+            merged.insert(item);
+        }
+        for (item, key) in keyed {
+            let id = self.add_item_by_key(wtxn, &key, &vector(wtxn, item)?)?;
+            self.merge_attributes(wtxn, other_index, item, id)?;
+            merged.insert(id);
+        }
+
+        Ok(merged)
+    }
+
+    /// Copies the attributes of an `item` of `other_index` to the item `id` of this index.
+    fn merge_attributes(
+        &self,
+        wtxn: &mut RwTxn,
+        other_index: u16,
+        item: ItemId,
+        id: ItemId,
+    ) -> Result<()> {
+        match attributes::get(self.database, other_index, wtxn, item)? {
+            Some(attributes) => self.set_attributes(wtxn, id, &attributes),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns an error if the metadata of `index` describe another distance or dimensions. An
+    /// index that was never built has no metadata, the dimensions of its first item are checked.
+    fn check_compatibility(&self, rtxn: &RoTxn, index: u16) -> Result<()> {
+        let Some(metadata) =
+            self.database.remap_data_type::<MetadataCodec>().get(rtxn, &Key::metadata(index))?
+        else {
+            let mut items = self
+                .database
+                .remap_key_type::<PrefixCodec>()
+                .prefix_iter(rtxn, &Prefix::item(index))?
+                .remap_key_type::<KeyCodec>();
+            let Some((key, node)) = items.next().transpose()? else {
+                return Ok(());
+            };
+            let item = node.item().ok_or_else(|| Error::missing_key(key))?;
+            // the binary codecs pad the vectors, compare them once encoded
+            let expected = UnalignedVector::<D::VectorCodec>::from_vec(vec![0.0; self.dimensions]);
+            return if item.vector.len() == expected.len() {
+                Ok(())
+            } else {
+                Err(Error::InvalidVecDimension {
+                    expected: self.dimensions,
+                    received: item.vector.len(),
+                })
+            };
+        };
+
+        if metadata.distance != D::name() {
+            Err(Error::UnmatchingDistance {
+                expected: metadata.distance.to_string(),
+                received: D::name(),
+            })
+        } else if metadata.dimensions as usize != self.dimensions {
+            Err(Error::InvalidVecDimension {
+                expected: self.dimensions,
+                received: metadata.dimensions as usize,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
     /// Returns an [`HannoyBuilder`] to configure the available options to build the database.
     pub fn builder<'a, R>(&'a self, rng: &'a mut R) -> HannoyBuilder<'a, D, R, NoProgress>
     where
@@ -518,31 +1201,62 @@ impl<D: Distance> Writer<D> {
         HannoyBuilder { writer: self, rng, inner: BuildOption::default() }
     }
 
-    fn build<R, P, const M: usize, const M0: usize>(
+    fn build<R, P>(
         &self,
         wtxn: &mut RwTxn,
         rng: &mut R,
         options: &BuildOption<P>,
+        stats: &mut BuildStats,
     ) -> Result<()>
     where
         R: Rng + SeedableRng,
         P: steppe::Progress,
     {
+        let build_params = options.build_params();
+
         // Get the list of items we already registered in the metadata
         let indexed_items = self
             .database
             .remap_data_type::<MetadataCodec>()
             .get(wtxn, &Key::metadata(self.index))?
             .map_or_else(RoaringBitmap::default, |m| m.items);
+        let previous_params = self
+            .database
+            .remap_data_type::<BuildParamsCodec>()
+            .get(wtxn, &Key::build_params(self.index))?;
+
+        // Linking new items with another degree would silently produce a mixed-degree graph,
+        // only a full rebuild is allowed to change it.
+        if let Some(previous) = previous_params.filter(|_| !options.relink_all_items) {
+            if !previous.same_degree(&build_params) && !indexed_items.is_empty() {
+                return Err(Error::UnmatchingBuildParams {
+                    index: self.index,
+                    expected: previous,
+                    received: build_params,
+                });
+            }
+            if previous != build_params {
+                warn!(
+                    "building index {} with {build_params}, it was built with {previous}",
+                    self.index
+                );
+            }
+        }
+
+        // Some distances need to update the item headers with knowledge of the whole index, the
+        // items already linked whose header changed are relinked like updated ones
+        debug!("preprocess the items...");
+        let preprocessed = D::preprocess(wtxn, self.database, self.index)?;
 
         // In case we have to rebuild all links we can skip the deletion step.
-        let (item_indices, to_delete, to_insert) = if options.relink_all_items {
+        let (item_indices, to_delete, mut to_insert) = if options.relink_all_items {
             (indexed_items.clone(), RoaringBitmap::new(), indexed_items)
         } else {
             // updated items can be an update, an addition or a removed item
             // they are identified by a "updated" stone key
-            let (all_updated_items, deleted_items) =
-                self.reset_and_retrieve_updated_items(wtxn, options)?;
+            let (mut all_updated_items, deleted_items) =
+                self.reset_and_retrieve_updated_items(wtxn, options, stats)?;
+            all_updated_items |= preprocessed & &indexed_items;
 
             // Item indices corresponds to all items, known ones and updates ones
             let updated_items = &all_updated_items - &deleted_items;
@@ -553,6 +1267,12 @@ impl<D: Distance> Writer<D> {
             (item_indices, to_delete, to_insert)
         };
 
+        // The scalar quantized items are encoded once the calibration is known, computing it
+        // re-encodes all the items which must then be relinked
+        debug!("calibrate the items...");
+        let calibrated = self.calibrate(wtxn, &to_insert, options.relink_all_items)?;
+        to_insert |= calibrated & &item_indices;
+
         let metadata = self
             .database
             .remap_data_type::<MetadataCodec>()
@@ -566,21 +1286,21 @@ impl<D: Distance> Writer<D> {
         // we should not keep a reference to the metadata since they're going to be moved by LMDB
         drop(metadata);
 
-        let mut hnsw = HnswBuilder::<D, M, M0>::new(options)
+        let mut hnsw = HnswBuilder::<D>::new(options)
             .with_entry_points(entry_points)
             .with_max_level(max_level);
 
-        let stats =
-            hnsw.build(to_insert, &to_delete, self.database, self.index, wtxn, rng, options)?;
+        stats.set_updated_items(to_insert.len(), to_delete.len());
+        hnsw.build(to_insert, &to_delete, self.database, self.index, wtxn, rng, options, stats)?;
         debug!("{stats:?}");
 
         // Remove deleted links from lmdb AFTER build; in DiskANN we use a deleted item's
         // neighbours when filling in the "gaps" left in the graph from deletions. See
         // [`HnswBuilder::maybe_patch_old_links`] for more details.
-        self.delete_links_from_db(&to_delete, wtxn, options)?;
+        self.delete_links_from_db(&to_delete, wtxn, options, stats)?;
 
         debug!("write the metadata...");
-        options.progress.update(HannoyBuild::WriteTheMetadata);
+        stats.step(&options.progress, HannoyBuild::WriteTheMetadata);
 
         self.database.remap_data_type::<MetadataCodec>().put(
             wtxn,
@@ -593,6 +1313,11 @@ impl<D: Distance> Writer<D> {
                 distance: D::name(),
             },
         )?;
+        self.database.remap_data_type::<BuildParamsCodec>().put(
+            wtxn,
+            &Key::build_params(self.index),
+            &build_params,
+        )?;
         self.database.remap_data_type::<VersionCodec>().put(
             wtxn,
             &Key::version(self.index),
@@ -602,16 +1327,57 @@ impl<D: Distance> Writer<D> {
         Ok(())
     }
 
+    fn repair<P>(&self, wtxn: &mut RwTxn, options: &BuildOption<P>) -> Result<RoaringBitmap>
+    where
+        P: steppe::Progress,
+    {
+        if self.need_build(wtxn)? {
+            return Err(Error::NeedBuild(self.index));
+        }
+
+        let build_params = options.build_params();
+        let metadata = self
+            .database
+            .remap_data_type::<MetadataCodec>()
+            .get(wtxn, &Key::metadata(self.index))?
+            .ok_or(Error::MissingMetadata(self.index))?;
+
+        let previous = self
+            .database
+            .remap_data_type::<BuildParamsCodec>()
+            .get(wtxn, &Key::build_params(self.index))?;
+        if let Some(previous) = previous {
+            if !previous.same_degree(&build_params) {
+                return Err(Error::UnmatchingBuildParams {
+                    index: self.index,
+                    expected: previous,
+                    received: build_params,
+                });
+            }
+        }
+
+        let items = metadata.items;
+        let entry_points = metadata.entry_points.iter().collect();
+        let max_level = metadata.max_level as usize;
+
+        let mut hnsw = HnswBuilder::<D>::new(options)
+            .with_entry_points(entry_points)
+            .with_max_level(max_level);
+
+        hnsw.repair(&items, self.database, self.index, wtxn, options)
+    }
+
     /// Kinda like clear and create, but only for links
     ///
     /// You must ensure that no items were inserted that are not
     /// part of the metadata. Which means that a build must have
     /// been performed or you'll see item leaking.
-    fn force_rebuild<R, P, const M: usize, const M0: usize>(
+    fn force_rebuild<R, P>(
         &self,
         wtxn: &mut RwTxn,
         rng: &mut R,
         options: &BuildOption<P>,
+        stats: &mut BuildStats,
     ) -> Result<()>
     where
         R: Rng + SeedableRng,
@@ -631,10 +1397,10 @@ impl<D: Distance> Writer<D> {
             .expect("The metadata must be there");
 
         // 3. delete all links
-        self.delete_links_from_db(&item_ids, wtxn, options)?;
+        self.delete_links_from_db(&item_ids, wtxn, options, stats)?;
 
         // 4. trigger build
-        self.build::<R, P, M, M0>(wtxn, rng, options)
+        self.build(wtxn, rng, options, stats)
     }
 
     /// Removes all the "updated" stones from the database
@@ -646,12 +1412,13 @@ impl<D: Distance> Writer<D> {
         &self,
         wtxn: &mut RwTxn,
         options: &BuildOption<P>,
+        stats: &BuildStats,
     ) -> Result<(RoaringBitmap, RoaringBitmap), Error>
     where
         P: steppe::Progress,
     {
         debug!("reset and retrieve the updated items...");
-        options.progress.update(HannoyBuild::RetrieveTheUpdatedItems);
+        stats.step(&options.progress, HannoyBuild::RetrieveTheUpdatedItems);
 
         let mut updated_items = RoaringBitmap::new();
         let mut deleted_items = RoaringBitmap::new();
@@ -694,12 +1461,13 @@ impl<D: Distance> Writer<D> {
         to_delete: &RoaringBitmap,
         wtxn: &mut RwTxn,
         options: &BuildOption<P>,
+        stats: &BuildStats,
     ) -> Result<()>
     where
         P: steppe::Progress,
     {
         debug!("started deleting the links...");
-        options.progress.update(HannoyBuild::DeletingTheLinks);
+        stats.step(&options.progress, HannoyBuild::DeletingTheLinks);
 
         let mut cursor = self
             .database
@@ -732,3 +1500,12 @@ fn clear_links<D: Distance>(wtxn: &mut RwTxn, database: Database<D>, index: u16)
 
     Ok(())
 }
+
+/// Returns an error if a value of the vector is too big for the codec of the distance.
+fn check_values<D: Distance>(vector: &[f32]) -> Result<()> {
+    let max = <D::VectorCodec as UnalignedVectorCodec>::max_value();
+    match vector.iter().find(|value| value.abs() > max) {
+        Some(&value) => Err(Error::ValueOutOfRange { value, max }),
+        None => Ok(()),
+    }
+}
@@ -34,6 +34,17 @@ def test_read(db: hannoy.Database) -> None:
     assert dist == 0.0
 
 
+def test_batch_read(db: hannoy.Database) -> None:
+    import numpy as np
+
+    reader: Reader = db.reader(0)
+    queries = np.array([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]], dtype=np.float32)
+
+    res = reader.by_vecs(queries, n=1)
+    assert len(res) == 2
+    assert [nns[0][0] for nns in res] == [0, 1]
+
+
 def test_multithreaded_reads(db) -> None:
     import threading
 